@@ -0,0 +1,84 @@
+//! Bevy integration for `verlet-core`: the world is a resource stepped in `FixedUpdate`, and
+//! entities carrying a `VerletParticle` follow their particle through `Transform`.
+
+use bevy_app::{App, FixedUpdate, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_time::{Fixed, Time};
+use bevy_transform::components::Transform;
+use verlet_core::{ParticleHandle, World};
+
+pub use verlet_core;
+
+/// The simulated world, stepped once per fixed timestep.
+#[derive(Resource, Default)]
+pub struct VerletWorld(pub World);
+
+/// The entity's particle in `VerletWorld`, e.g. from `World::insert`. Entities whose particle
+/// is removed are despawned.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerletParticle(pub ParticleHandle);
+
+/// A world event, re-sent through Bevy after the step that raised it.
+#[derive(Event, Clone, Debug)]
+pub struct VerletEvent(pub verlet_core::Event);
+
+/// Ordering of the plugin's systems within `FixedUpdate`.
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VerletSet {
+    /// Advances the world and forwards its events.
+    Step,
+    /// Despawns entities of removed particles and copies positions into transforms.
+    Sync,
+}
+
+/// Adds `VerletWorld` (unless already inserted), `VerletEvent` and the stepping systems.
+#[derive(Default)]
+pub struct VerletPlugin;
+
+impl Plugin for VerletPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world().contains_resource::<VerletWorld>() {
+            app.init_resource::<VerletWorld>();
+        }
+        app.add_event::<VerletEvent>()
+            .configure_sets(FixedUpdate, (VerletSet::Step, VerletSet::Sync).chain())
+            .add_systems(FixedUpdate, step.in_set(VerletSet::Step))
+            .add_systems(
+                FixedUpdate,
+                (despawn_removed, sync_transforms)
+                    .chain()
+                    .in_set(VerletSet::Sync),
+            );
+    }
+}
+
+fn step(
+    time: Res<Time<Fixed>>,
+    mut world: ResMut<VerletWorld>,
+    mut events: EventWriter<VerletEvent>,
+) {
+    world.0.step(time.delta_seconds());
+    events.send_batch(world.0.drain_events().into_iter().map(VerletEvent));
+}
+
+fn despawn_removed(
+    mut commands: Commands,
+    world: Res<VerletWorld>,
+    handles: Query<(Entity, &VerletParticle)>,
+) {
+    for (entity, handle) in handles.iter() {
+        if world.0.index(handle.0).is_none() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn sync_transforms(world: Res<VerletWorld>, mut query: Query<(&VerletParticle, &mut Transform)>) {
+    for (handle, mut transform) in query.iter_mut() {
+        if let Some(i) = world.0.index(handle.0) {
+            let pos = &world.0.particles.pos[i];
+            transform.translation.x = pos.x;
+            transform.translation.y = pos.y;
+        }
+    }
+}
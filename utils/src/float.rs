@@ -0,0 +1,39 @@
+use std::fmt::Debug;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, Neg, Sub, SubAssign};
+
+/// Scalar type vectors are built from. Only `Vec2` is generic: particles, the solver and the
+/// world all run on `f32`, so `Vec2<f64>` is for callers' own math, not f64 simulations.
+pub trait Float:
+    Copy
+    + Debug
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + DivAssign
+{
+    fn zero() -> Self;
+    fn sqrt(self) -> Self;
+}
+
+impl Float for f32 {
+    fn zero() -> Self {
+        0_f32
+    }
+    fn sqrt(self) -> Self {
+        f32::sqrt(self)
+    }
+}
+
+impl Float for f64 {
+    fn zero() -> Self {
+        0_f64
+    }
+    fn sqrt(self) -> Self {
+        f64::sqrt(self)
+    }
+}
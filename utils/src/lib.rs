@@ -1 +1,2 @@
+pub mod float;
 pub mod vec;
\ No newline at end of file
@@ -1,44 +1,57 @@
 
 use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
 
+use crate::float::Float;
+
 #[derive(Clone, Debug)]
-pub struct Vec2 {
-    pub x: f32,
-    pub y: f32,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vec2<T = f32> {
+    pub x: T,
+    pub y: T,
 }
 
-impl Vec2 {
-    pub fn new(x: f32, y: f32) -> Vec2 {
+impl<T: Float> Vec2<T> {
+    pub fn new(x: T, y: T) -> Vec2<T> {
         Vec2 { x, y }
     }
-    pub fn zero() -> Vec2 {
-        Vec2 { x: 0_f32, y: 0_f32 }
+    pub fn zero() -> Vec2<T> {
+        Vec2 { x: T::zero(), y: T::zero() }
+    }
+
+    pub fn len(&self) -> T {
+        T::sqrt(self.x*self.x + self.y * self.y)
+    }
+
+    pub fn dot(&self, other: &Vec2<T>) -> T {
+        self.x * other.x + self.y * other.y
     }
 
-    pub fn len(&self) -> f32 {
-        f32::sqrt(self.x*self.x + self.y * self.y)
+    /// Counter-clockwise perpendicular.
+    pub fn perp(&self) -> Vec2<T> {
+        Vec2::new(-self.y, self.x)
     }
 
     pub fn normalize(&mut self) {
-        self.x = self.x / self.len();
-        self.y = self.y / self.len();
+        let len = self.len();
+        self.x /= len;
+        self.y /= len;
     }
 }
 
-impl Div<f32> for Vec2 {
-    type Output = Vec2;
+impl<T: Float> Div<T> for Vec2<T> {
+    type Output = Vec2<T>;
 
-    fn div(self, rhs: f32) -> Self::Output {
+    fn div(self, rhs: T) -> Self::Output {
         Vec2 {
             x: self.x / rhs,
             y: self.y / rhs,
         }
     }
 }
-impl Mul<f32> for Vec2 {
-    type Output = Vec2;
+impl<T: Float> Mul<T> for Vec2<T> {
+    type Output = Vec2<T>;
 
-    fn mul(self, rhs: f32) -> Self::Output {
+    fn mul(self, rhs: T) -> Self::Output {
         Vec2 {
             x: self.x * rhs,
             y: self.y * rhs,
@@ -46,10 +59,10 @@ impl Mul<f32> for Vec2 {
     }
 }
 
-impl Add<Vec2> for Vec2 {
-    type Output = Vec2;
+impl<T: Float> Add<Vec2<T>> for Vec2<T> {
+    type Output = Vec2<T>;
 
-    fn add(self, rhs: Vec2) -> Self::Output {
+    fn add(self, rhs: Vec2<T>) -> Self::Output {
         Vec2 {
             x: self.x + rhs.x,
             y: self.y + rhs.y,
@@ -57,24 +70,24 @@ impl Add<Vec2> for Vec2 {
     }
 }
 
-impl AddAssign<Vec2> for Vec2 {
-    fn add_assign(&mut self, rhs: Vec2) {
+impl<T: Float> AddAssign<Vec2<T>> for Vec2<T> {
+    fn add_assign(&mut self, rhs: Vec2<T>) {
         self.x += rhs.x;
         self.y += rhs.y;
     }
 }
 
-impl SubAssign<Vec2> for Vec2 {
-    fn sub_assign(&mut self, rhs: Vec2) {
+impl<T: Float> SubAssign<Vec2<T>> for Vec2<T> {
+    fn sub_assign(&mut self, rhs: Vec2<T>) {
         self.x -= rhs.x;
         self.y -= rhs.y;
     }
 }
 
-impl Sub<Vec2> for Vec2 {
-    type Output = Vec2;
+impl<T: Float> Sub<Vec2<T>> for Vec2<T> {
+    type Output = Vec2<T>;
 
-    fn sub(self, rhs: Vec2) -> Self::Output {
+    fn sub(self, rhs: Vec2<T>) -> Self::Output {
         Vec2 {
             x: self.x - rhs.x,
             y: self.y - rhs.y,
@@ -82,13 +95,61 @@ impl Sub<Vec2> for Vec2 {
     }
 }
 
-impl Sub<f32> for Vec2 {
-    type Output = Vec2;
+impl<T: Float> Sub<T> for Vec2<T> {
+    type Output = Vec2<T>;
 
-    fn sub(self, rhs: f32) -> Self::Output {
+    fn sub(self, rhs: T) -> Self::Output {
         Vec2 {
             x: self.x - rhs,
             y: self.y - rhs,
         }
     }
 }
+
+/// Borrowing versions of the operators, so hot loops can combine positions without cloning.
+impl<T: Float> Sub<&Vec2<T>> for &Vec2<T> {
+    type Output = Vec2<T>;
+
+    fn sub(self, rhs: &Vec2<T>) -> Self::Output {
+        Vec2 {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+impl<T: Float> Add<&Vec2<T>> for &Vec2<T> {
+    type Output = Vec2<T>;
+
+    fn add(self, rhs: &Vec2<T>) -> Self::Output {
+        Vec2 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+impl<T: Float> Mul<T> for &Vec2<T> {
+    type Output = Vec2<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Vec2 {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+
+impl<T: Float> AddAssign<&Vec2<T>> for Vec2<T> {
+    fn add_assign(&mut self, rhs: &Vec2<T>) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl<T: Float> SubAssign<&Vec2<T>> for Vec2<T> {
+    fn sub_assign(&mut self, rhs: &Vec2<T>) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+    }
+}
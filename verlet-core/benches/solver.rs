@@ -0,0 +1,142 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+use verlet_core::broadphase::{Aabb, Broadphase, BruteForce};
+use verlet_core::{Constraint, Particle, Particles, Vec2, World};
+
+const DT: f32 = 1_f32 / 60_f32;
+
+/// `count` particles laid out on a square grid `spacing` apart, inside a box container wide
+/// enough to hold them.
+fn grid_world(count: usize, radius: f32, spacing: f32) -> World {
+    let side = f32::ceil(f32::sqrt(count as f32)) as usize;
+    let half = 0.5_f32 * side as f32 * spacing + radius;
+    let mut world = World::new(
+        Vec2::new(0_f32, -1000_f32),
+        vec![Constraint::rect(
+            Vec2::new(-half, -half),
+            Vec2::new(half, 4_f32 * half),
+        )],
+    );
+    for i in 0..count {
+        let (row, col) = (i / side, i % side);
+        let pos = Vec2::new(
+            -half + radius + col as f32 * spacing,
+            -half + radius + row as f32 * spacing,
+        );
+        let mut p = Particle::new(pos);
+        p.radius = radius;
+        world.add_particle(p);
+    }
+    world
+}
+
+/// Particles spread out with gaps, so most steps are integration with few contacts.
+fn free_fall(count: usize) -> World {
+    grid_world(count, 2_f32, 12_f32)
+}
+
+/// Particles packed edge to edge on the floor, so every particle is in contact.
+fn dense_pile(count: usize) -> World {
+    grid_world(count, 2_f32, 4_f32)
+}
+
+fn chain(links: usize) -> World {
+    let mut world = World::new(
+        Vec2::new(0_f32, -1000_f32),
+        vec![Constraint::circle(Vec2::zero(), 3000_f32)],
+    );
+    world.spawn_chain(
+        Vec2::new(-2000_f32, 0_f32),
+        Vec2::new(2000_f32, 0_f32),
+        links,
+        3_f32,
+    );
+    world
+}
+
+/// Each iteration is one `step`, so throughput reads as steps per second. The world keeps
+/// evolving across iterations rather than being reset.
+fn bench_scene(c: &mut Criterion, name: &str, sizes: &[usize], build: fn(usize) -> World) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(1));
+    for &size in sizes {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            let mut world = build(size);
+            b.iter(|| world.step(DT));
+        });
+    }
+    group.finish();
+}
+
+/// Hands out the candidate pairs it was built with, so a bench only times what the solver
+/// does with them.
+#[derive(Clone, Debug)]
+struct Fixed(Vec<(usize, usize)>);
+
+impl Broadphase for Fixed {
+    fn name(&self) -> &'static str {
+        "fixed"
+    }
+
+    fn update(&mut self, _particles: &Particles) {}
+
+    fn pairs(&mut self, _particles: &Particles) -> Vec<(usize, usize)> {
+        self.0.clone()
+    }
+
+    fn query(&self, particles: &Particles, aabb: &Aabb, out: &mut Vec<usize>) {
+        BruteForce.query(particles, aabb, out);
+    }
+
+    fn clone_box(&self) -> Box<dyn Broadphase> {
+        Box::new(self.clone())
+    }
+}
+
+/// One contact pass over a freshly built dense pile with the grid's candidate pairs, so
+/// every iteration sees the same overlaps. Run with and without the `simd` feature to
+/// compare the narrowphase.
+fn narrowphase(c: &mut Criterion) {
+    let mut group = c.benchmark_group("narrowphase");
+    group.sample_size(10);
+    for size in [10_000, 50_000] {
+        let mut world = dense_pile(size);
+        let pairs = world.solver.pairs(&world.particles);
+        world.solver.set_broadphase(Box::new(Fixed(pairs)));
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &world, |b, world| {
+            b.iter_batched_ref(
+                || world.clone(),
+                |world| world.solve_collisions(DT),
+                BatchSize::LargeInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+/// Picks the touching pairs out of a dense pile's grid candidates, which is only the
+/// distance test the contact pass starts with.
+fn overlap(c: &mut Criterion) {
+    let mut group = c.benchmark_group("overlap");
+    for size in [10_000, 50_000] {
+        let mut world = dense_pile(size);
+        let pairs = world.solver.pairs(&world.particles);
+        group.throughput(Throughput::Elements(pairs.len() as u64));
+        world.solver.set_broadphase(Box::new(Fixed(pairs)));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| world.contacts());
+        });
+    }
+    group.finish();
+}
+
+fn solver(c: &mut Criterion) {
+    bench_scene(c, "free_fall", &[1_000, 10_000, 50_000], free_fall);
+    bench_scene(c, "dense_pile", &[1_000, 10_000, 50_000], dense_pile);
+    bench_scene(c, "chain", &[500], chain);
+}
+
+criterion_group!(benches, solver, narrowphase, overlap);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+//! Drops a pile of particles with collisions resolved on the GPU and compares it against the
+//! CPU solver.
+//!
+//!     cargo run --release --example gpu --features gpu [particles]
+
+use std::time::Instant;
+
+use verlet_core::{Constraint, GpuSolver, Particle, Vec2, World};
+
+const DT: f32 = 1_f32 / 60_f32;
+const STEPS: usize = 300;
+
+fn pile(count: usize) -> World {
+    let side = f32::ceil(f32::sqrt(count as f32)) as usize;
+    let half = 0.5_f32 * side as f32 * 6_f32;
+    let mut world = World::new(
+        Vec2::new(0_f32, -1000_f32),
+        vec![Constraint::rect(
+            Vec2::new(-half, -half),
+            Vec2::new(half, 4_f32 * half),
+        )],
+    );
+    for i in 0..count {
+        let pos = Vec2::new(
+            -half + 3_f32 + (i % side) as f32 * 6.5_f32,
+            -half + 3_f32 + (i / side) as f32 * 6.5_f32,
+        );
+        let mut p = Particle::new(pos);
+        p.radius = 3_f32;
+        world.add_particle(p);
+    }
+    world
+}
+
+fn run(name: &str, mut world: World) {
+    let start = Instant::now();
+    for _ in 0..STEPS {
+        world.step(DT);
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    let mean_y =
+        world.particles.pos.iter().map(|p| p.y).sum::<f32>() / world.particles.len() as f32;
+    println!(
+        "{:<4} {:.3} ms/step, mean height {:.1}",
+        name,
+        1000_f64 * elapsed / STEPS as f64,
+        mean_y
+    );
+}
+
+fn main() {
+    let count = std::env::args()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(10_000);
+    let gpu = match GpuSolver::new() {
+        Ok(gpu) => gpu,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("{} particles, adapter {}", count, gpu.adapter_name());
+
+    run("cpu", pile(count));
+    let mut world = pile(count);
+    world.solver.set_gpu(Some(gpu));
+    run("gpu", world);
+}
@@ -0,0 +1,32 @@
+use utils::vec::Vec2;
+
+/// Point gravity: pulls particles towards `center` with an acceleration of `strength / r²`,
+/// for orbits and planet-style scenes.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attractor {
+    pub center: Vec2,
+    pub strength: f32,
+    /// Distance below which the pull stops growing, so particles passing through the center
+    /// aren't flung out.
+    pub min_distance: f32,
+}
+
+impl Attractor {
+    pub fn new(center: Vec2, strength: f32) -> Self {
+        Attractor {
+            center,
+            strength,
+            min_distance: 10_f32,
+        }
+    }
+
+    pub fn acceleration(&self, pos: &Vec2) -> Vec2 {
+        let offset = self.center.clone() - pos.clone();
+        let dist = offset.len().max(self.min_distance);
+        if dist == 0_f32 {
+            return Vec2::zero();
+        }
+        offset * (self.strength / (dist * dist * dist))
+    }
+}
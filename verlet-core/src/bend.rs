@@ -0,0 +1,59 @@
+use crate::particle::Particles;
+
+/// Resists changes of the angle at `b` formed by `a`-`b`-`c`. The angle is held by pulling
+/// the outer particles towards the distance they would have at `rest_angle`, given the current
+/// arm lengths, with `stiffness` in `[0, 1]` scaling the correction.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bend {
+    pub a: usize,
+    pub b: usize,
+    pub c: usize,
+    pub rest_angle: f32,
+    pub stiffness: f32,
+}
+
+impl Bend {
+    pub fn new(a: usize, b: usize, c: usize, rest_angle: f32, stiffness: f32) -> Self {
+        Bend {
+            a,
+            b,
+            c,
+            rest_angle,
+            stiffness,
+        }
+    }
+
+    /// Bend constraint holding the current angle at `b`.
+    pub fn between(particles: &Particles, a: usize, b: usize, c: usize, stiffness: f32) -> Self {
+        let mut bend = Bend::new(a, b, c, 0_f32, stiffness);
+        bend.rest_angle = bend.angle(particles);
+        bend
+    }
+
+    pub fn angle(&self, particles: &Particles) -> f32 {
+        let ba = particles.pos[self.a].clone() - particles.pos[self.b].clone();
+        let bc = particles.pos[self.c].clone() - particles.pos[self.b].clone();
+        let cos = ba.dot(&bc) / (ba.len() * bc.len());
+        f32::acos(f32::clamp(cos, -1_f32, 1_f32))
+    }
+
+    pub fn solve(&self, particles: &mut Particles) {
+        let la = (particles.pos[self.a].clone() - particles.pos[self.b].clone()).len();
+        let lc = (particles.pos[self.c].clone() - particles.pos[self.b].clone()).len();
+        let rest = f32::sqrt(la * la + lc * lc - 2_f32 * la * lc * self.rest_angle.cos());
+
+        let v = particles.pos[self.a].clone() - particles.pos[self.c].clone();
+        let dist = v.len();
+        let inv_a = particles.inv_mass(self.a);
+        let inv_c = particles.inv_mass(self.c);
+        if dist <= f32::EPSILON || inv_a + inv_c == 0_f32 {
+            return;
+        }
+        let n = v / dist;
+        let delta = self.stiffness * (dist - rest) / (inv_a + inv_c);
+
+        particles.pos[self.a] -= n.clone() * (inv_a * delta);
+        particles.pos[self.c] += n * (inv_c * delta);
+    }
+}
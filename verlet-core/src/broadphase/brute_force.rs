@@ -0,0 +1,37 @@
+use crate::broadphase::{Aabb, Broadphase, BroadphaseKind};
+use crate::particle::Particles;
+
+#[derive(Clone, Debug, Default)]
+pub struct BruteForce;
+
+impl Broadphase for BruteForce {
+    fn name(&self) -> &'static str {
+        "brute force"
+    }
+
+    fn update(&mut self, _particles: &Particles) {}
+
+    fn pairs(&mut self, particles: &Particles) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for i in 0..particles.len() {
+            for k in (i + 1)..particles.len() {
+                if particles.collides(i, k) {
+                    pairs.push((i, k));
+                }
+            }
+        }
+        pairs
+    }
+
+    fn query(&self, particles: &Particles, _aabb: &Aabb, out: &mut Vec<usize>) {
+        out.extend(0..particles.len());
+    }
+
+    fn clone_box(&self) -> Box<dyn Broadphase> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> Option<BroadphaseKind> {
+        Some(BroadphaseKind::BruteForce)
+    }
+}
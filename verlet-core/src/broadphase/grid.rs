@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use utils::vec::Vec2;
+
+use crate::broadphase::{Aabb, Broadphase, BroadphaseKind};
+use crate::particle::Particles;
+use crate::solver::COLLISION_MARGIN;
+
+/// Smallest cell size the grid uses; smaller sizes, zero, negative and NaN included, are
+/// raised to it.
+const MIN_CELL_SIZE: f32 = 1_f32;
+
+#[derive(Clone, Debug, Default)]
+pub struct SpatialGrid {
+    /// Cell size; `None` sizes cells to the largest particle diameter.
+    pub cell_size: Option<f32>,
+    current_size: f32,
+    max_radius: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        let cell_size = f32::max(cell_size, MIN_CELL_SIZE);
+        SpatialGrid {
+            cell_size: Some(cell_size),
+            current_size: cell_size,
+            max_radius: 0_f32,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn auto() -> Self {
+        SpatialGrid::default()
+    }
+
+    pub fn current_cell_size(&self) -> f32 {
+        self.current_size
+    }
+
+    pub fn cell_of(&self, pos: &Vec2) -> (i32, i32) {
+        (
+            f32::floor(pos.x / self.current_size) as i32,
+            f32::floor(pos.y / self.current_size) as i32,
+        )
+    }
+
+    pub fn clear(&mut self) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, pos: &Vec2) {
+        let cell = self.cell_of(pos);
+        self.cells.entry(cell).or_default().push(index);
+    }
+
+    pub fn build(&mut self, particles: &Particles) {
+        self.max_radius = particles.max_radius();
+        self.current_size = f32::max(
+            self.cell_size
+                .unwrap_or(2_f32 * self.max_radius + COLLISION_MARGIN),
+            MIN_CELL_SIZE,
+        );
+
+        self.clear();
+        for (i, pos) in particles.pos.iter().enumerate() {
+            self.insert(i, pos);
+        }
+        // Cells keep their storage while occupied, but ones particles left are dropped so
+        // the map doesn't grow with every cell ever visited.
+        self.cells.retain(|_, cell| !cell.is_empty());
+    }
+
+    pub fn cell(&self, cell: (i32, i32)) -> &[usize] {
+        self.cells.get(&cell).map(|c| c.as_slice()).unwrap_or(&[])
+    }
+}
+
+impl Broadphase for SpatialGrid {
+    fn name(&self) -> &'static str {
+        "spatial grid"
+    }
+
+    fn update(&mut self, particles: &Particles) {
+        self.build(particles);
+    }
+
+    fn pairs(&mut self, particles: &Particles) -> Vec<(usize, usize)> {
+        self.build(particles);
+
+        let reach = 2_f32 * self.max_radius + COLLISION_MARGIN;
+        let span = f32::ceil(reach / self.current_size) as i32;
+
+        let mut pairs = Vec::new();
+        for (i, pos) in particles.pos.iter().enumerate() {
+            let (cx, cy) = self.cell_of(pos);
+            for dx in -span..=span {
+                for dy in -span..=span {
+                    for &k in self.cell((cx.saturating_add(dx), cy.saturating_add(dy))) {
+                        if k > i && particles.collides(i, k) {
+                            pairs.push((i, k));
+                        }
+                    }
+                }
+            }
+        }
+        pairs
+    }
+
+    fn query(&self, particles: &Particles, aabb: &Aabb, out: &mut Vec<usize>) {
+        if self.cells.is_empty() || self.current_size <= 0_f32 {
+            return;
+        }
+        let (x0, y0) = self.cell_of(&(aabb.min.clone() - self.max_radius));
+        let (x1, y1) =
+            self.cell_of(&(aabb.max.clone() + Vec2::new(self.max_radius, self.max_radius)));
+        let cells = (x1 as i64 - x0 as i64 + 1) * (y1 as i64 - y0 as i64 + 1);
+        if cells > particles.len() as i64 {
+            out.extend(0..particles.len());
+            return;
+        }
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                out.extend_from_slice(self.cell((x, y)));
+            }
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Broadphase> {
+        Box::new(self.clone())
+    }
+
+    fn cells(&self) -> Vec<(Aabb, usize)> {
+        let size = self.current_size;
+        self.cells
+            .iter()
+            .filter(|(_, items)| !items.is_empty())
+            .map(|(&(x, y), items)| {
+                let min = Vec2::new(x as f32 * size, y as f32 * size);
+                let max = Vec2::new(min.x + size, min.y + size);
+                (Aabb::new(min, max), items.len())
+            })
+            .collect()
+    }
+
+    fn kind(&self) -> Option<BroadphaseKind> {
+        Some(BroadphaseKind::SpatialGrid {
+            cell_size: self.cell_size,
+        })
+    }
+}
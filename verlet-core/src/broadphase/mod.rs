@@ -0,0 +1,152 @@
+use std::fmt::Debug;
+
+use utils::vec::Vec2;
+
+use crate::particle::Particles;
+use crate::solver::COLLISION_MARGIN;
+
+mod brute_force;
+mod grid;
+mod quadtree;
+mod sweep_and_prune;
+
+pub use brute_force::BruteForce;
+pub use grid::SpatialGrid;
+pub use quadtree::Quadtree;
+pub use sweep_and_prune::SweepAndPrune;
+
+pub trait Broadphase: Debug + Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Rebuilds the index from the current particle positions.
+    fn update(&mut self, particles: &Particles);
+
+    /// Candidate pairs `(i, k)` with `i < k` that may be in contact. Rebuilds the index first.
+    fn pairs(&mut self, particles: &Particles) -> Vec<(usize, usize)>;
+
+    /// Appends the indices of particles that may overlap `aabb`, as of the last `update`.
+    fn query(&self, particles: &Particles, aabb: &Aabb, out: &mut Vec<usize>);
+
+    fn clone_box(&self) -> Box<dyn Broadphase>;
+
+    /// Cells or nodes of the index with the number of particles stored in each, as of the last
+    /// `update`, e.g. to check the cell size. Empty for indexes without any.
+    fn cells(&self) -> Vec<(Aabb, usize)> {
+        Vec::new()
+    }
+
+    /// Settings to rebuild this broadphase from; custom implementations return `None`.
+    fn kind(&self) -> Option<BroadphaseKind> {
+        None
+    }
+}
+
+impl Clone for Box<dyn Broadphase> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Configuration of a built-in broadphase, used to save and restore the solver's choice.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BroadphaseKind {
+    BruteForce,
+    SpatialGrid {
+        cell_size: Option<f32>,
+    },
+    Quadtree {
+        node_capacity: usize,
+        max_depth: usize,
+    },
+    SweepAndPrune,
+}
+
+impl BroadphaseKind {
+    pub fn build(&self) -> Box<dyn Broadphase> {
+        match self {
+            BroadphaseKind::BruteForce => Box::new(BruteForce),
+            BroadphaseKind::SpatialGrid { cell_size: None } => Box::new(SpatialGrid::auto()),
+            BroadphaseKind::SpatialGrid {
+                cell_size: Some(cell_size),
+            } => Box::new(SpatialGrid::new(*cell_size)),
+            BroadphaseKind::Quadtree {
+                node_capacity,
+                max_depth,
+            } => Box::new(Quadtree::new(*node_capacity, *max_depth)),
+            BroadphaseKind::SweepAndPrune => Box::new(SweepAndPrune::new()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Aabb {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Aabb {
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Aabb { min, max }
+    }
+
+    pub fn around(center: &Vec2, half_extent: f32) -> Self {
+        Aabb {
+            min: center.clone() - half_extent,
+            max: center.clone() + Vec2::new(half_extent, half_extent),
+        }
+    }
+
+    /// Box around particle `i` that overlaps another particle's box whenever they may
+    /// collide.
+    pub fn of_particle(particles: &Particles, i: usize) -> Self {
+        Aabb::around(
+            &particles.pos[i],
+            particles.radius[i] + 0.5_f32 * COLLISION_MARGIN,
+        )
+    }
+
+    pub fn overlaps(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+    }
+
+    pub fn contains(&self, other: &Aabb) -> bool {
+        self.min.x <= other.min.x
+            && self.max.x >= other.max.x
+            && self.min.y <= other.min.y
+            && self.max.y >= other.max.y
+    }
+
+    pub fn contains_point(&self, p: &Vec2) -> bool {
+        self.min.x <= p.x && p.x <= self.max.x && self.min.y <= p.y && p.y <= self.max.y
+    }
+
+    /// Whether the disc at `center` overlaps the box.
+    pub fn overlaps_circle(&self, center: &Vec2, radius: f32) -> bool {
+        let dx = f32::max(
+            f32::max(self.min.x - center.x, center.x - self.max.x),
+            0_f32,
+        );
+        let dy = f32::max(
+            f32::max(self.min.y - center.y, center.y - self.max.y),
+            0_f32,
+        );
+        dx * dx + dy * dy <= radius * radius
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Vec2::new(
+                f32::min(self.min.x, other.min.x),
+                f32::min(self.min.y, other.min.y),
+            ),
+            max: Vec2::new(
+                f32::max(self.max.x, other.max.x),
+                f32::max(self.max.y, other.max.y),
+            ),
+        }
+    }
+}
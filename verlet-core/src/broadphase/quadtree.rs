@@ -0,0 +1,192 @@
+use utils::vec::Vec2;
+
+use crate::broadphase::{Aabb, Broadphase, BroadphaseKind};
+use crate::particle::Particles;
+
+#[derive(Clone, Debug)]
+struct Node {
+    bounds: Aabb,
+    /// `bounds` grown by half its size on every side; what the node's items must fit in.
+    loose: Aabb,
+    children: Option<usize>,
+    items: Vec<usize>,
+}
+
+impl Node {
+    fn new(bounds: Aabb) -> Self {
+        let half = Vec2::new(
+            0.5_f32 * (bounds.max.x - bounds.min.x),
+            0.5_f32 * (bounds.max.y - bounds.min.y),
+        );
+        Node {
+            loose: Aabb::new(bounds.min.clone() - half.clone(), bounds.max.clone() + half),
+            bounds,
+            children: None,
+            items: Vec::new(),
+        }
+    }
+}
+
+/// Loose quadtree over particle bounding boxes. Each item goes to the child holding its center
+/// as long as it fits that child's loose bounds, twice the child's size, so boxes straddling a
+/// split still sink and only particles larger than a node's half stay high in the tree.
+#[derive(Clone, Debug)]
+pub struct Quadtree {
+    pub node_capacity: usize,
+    pub max_depth: usize,
+    nodes: Vec<Node>,
+    boxes: Vec<Aabb>,
+}
+
+impl Default for Quadtree {
+    fn default() -> Self {
+        Quadtree::new(8, 8)
+    }
+}
+
+impl Quadtree {
+    pub fn new(node_capacity: usize, max_depth: usize) -> Self {
+        Quadtree {
+            node_capacity,
+            max_depth,
+            nodes: Vec::new(),
+            boxes: Vec::new(),
+        }
+    }
+
+    pub fn build(&mut self, particles: &Particles) {
+        self.nodes.clear();
+        self.boxes.clear();
+        self.boxes
+            .extend((0..particles.len()).map(|i| Aabb::of_particle(particles, i)));
+
+        let Some(first) = self.boxes.first() else {
+            return;
+        };
+        let bounds = self.boxes.iter().fold(first.clone(), |acc, b| acc.union(b));
+        self.nodes.push(Node::new(bounds));
+
+        for i in 0..self.boxes.len() {
+            self.insert(0, i, 0);
+        }
+    }
+
+    /// Indices of particles whose boxes overlap `aabb`.
+    pub fn query_boxes(&self, aabb: &Aabb, out: &mut Vec<usize>) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let mut stack = vec![0];
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if !node.loose.overlaps(aabb) {
+                continue;
+            }
+            out.extend(
+                node.items
+                    .iter()
+                    .filter(|&&i| self.boxes[i].overlaps(aabb))
+                    .copied(),
+            );
+            if let Some(c) = node.children {
+                stack.extend(c..c + 4);
+            }
+        }
+    }
+
+    fn insert(&mut self, node: usize, item: usize, depth: usize) {
+        if let Some(child) = self.child_containing(node, item) {
+            self.insert(child, item, depth + 1);
+            return;
+        }
+
+        self.nodes[node].items.push(item);
+        if self.nodes[node].children.is_none()
+            && self.nodes[node].items.len() > self.node_capacity
+            && depth < self.max_depth
+        {
+            self.split(node);
+            let items = std::mem::take(&mut self.nodes[node].items);
+            for i in items {
+                match self.child_containing(node, i) {
+                    Some(child) => self.insert(child, i, depth + 1),
+                    None => self.nodes[node].items.push(i),
+                }
+            }
+        }
+    }
+
+    fn child_containing(&self, node: usize, item: usize) -> Option<usize> {
+        let c = self.nodes[node].children?;
+        let (b, item) = (&self.nodes[node].bounds, &self.boxes[item]);
+        let mid = (b.min.clone() + b.max.clone()) / 2_f32;
+        let center = (item.min.clone() + item.max.clone()) / 2_f32;
+        // Same quadrant order as `split`.
+        let child = c + usize::from(center.x >= mid.x) + 2 * usize::from(center.y >= mid.y);
+        self.nodes[child].loose.contains(item).then_some(child)
+    }
+
+    fn split(&mut self, node: usize) {
+        let b = self.nodes[node].bounds.clone();
+        let mid = (b.min.clone() + b.max.clone()) / 2_f32;
+        let first = self.nodes.len();
+        let quadrants = [
+            Aabb::new(b.min.clone(), mid.clone()),
+            Aabb::new(Vec2::new(mid.x, b.min.y), Vec2::new(b.max.x, mid.y)),
+            Aabb::new(Vec2::new(b.min.x, mid.y), Vec2::new(mid.x, b.max.y)),
+            Aabb::new(mid, b.max),
+        ];
+        self.nodes.extend(quadrants.into_iter().map(Node::new));
+        self.nodes[node].children = Some(first);
+    }
+}
+
+impl Broadphase for Quadtree {
+    fn name(&self) -> &'static str {
+        "quadtree"
+    }
+
+    fn update(&mut self, particles: &Particles) {
+        self.build(particles);
+    }
+
+    fn pairs(&mut self, particles: &Particles) -> Vec<(usize, usize)> {
+        self.build(particles);
+
+        let mut pairs = Vec::new();
+        let mut hits = Vec::new();
+        for i in 0..self.boxes.len() {
+            hits.clear();
+            self.query_boxes(&self.boxes[i], &mut hits);
+            hits.sort_unstable();
+            pairs.extend(
+                hits.iter()
+                    .filter(|&&k| k > i && particles.collides(i, k))
+                    .map(|&k| (i, k)),
+            );
+        }
+        pairs
+    }
+
+    fn query(&self, _particles: &Particles, aabb: &Aabb, out: &mut Vec<usize>) {
+        self.query_boxes(aabb, out);
+    }
+
+    fn clone_box(&self) -> Box<dyn Broadphase> {
+        Box::new(self.clone())
+    }
+
+    fn cells(&self) -> Vec<(Aabb, usize)> {
+        self.nodes
+            .iter()
+            .map(|node| (node.bounds.clone(), node.items.len()))
+            .collect()
+    }
+
+    fn kind(&self) -> Option<BroadphaseKind> {
+        Some(BroadphaseKind::Quadtree {
+            node_capacity: self.node_capacity,
+            max_depth: self.max_depth,
+        })
+    }
+}
@@ -0,0 +1,123 @@
+use crate::broadphase::{Aabb, Broadphase, BroadphaseKind};
+use crate::particle::Particles;
+
+/// Sweep and prune: particle boxes sorted by their lower edge along the longer side of the
+/// scene, so only neighbours in that order are tested. The order is kept between updates and
+/// re-sorted with a stable sort, which picks up the runs left from the last step and is close
+/// to linear while particles keep their order. Suits long, thin scenes where most grid cells
+/// would sit empty.
+#[derive(Clone, Debug, Default)]
+pub struct SweepAndPrune {
+    /// Particle indices, ordered by the lower edge of their box along `axis`.
+    order: Vec<usize>,
+    boxes: Vec<Aabb>,
+    axis: Axis,
+    /// Widest box along `axis`, bounding how far back a query has to look.
+    max_extent: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Axis {
+    #[default]
+    X,
+    Y,
+}
+
+impl Axis {
+    fn range(self, b: &Aabb) -> (f32, f32) {
+        match self {
+            Axis::X => (b.min.x, b.max.x),
+            Axis::Y => (b.min.y, b.max.y),
+        }
+    }
+}
+
+impl SweepAndPrune {
+    pub fn new() -> Self {
+        SweepAndPrune::default()
+    }
+
+    pub fn build(&mut self, particles: &Particles) {
+        self.boxes.clear();
+        self.boxes
+            .extend((0..particles.len()).map(|i| Aabb::of_particle(particles, i)));
+        let Some(first) = self.boxes.first() else {
+            self.order.clear();
+            return;
+        };
+        let bounds = self.boxes.iter().fold(first.clone(), |acc, b| acc.union(b));
+        self.axis = if bounds.max.x - bounds.min.x >= bounds.max.y - bounds.min.y {
+            Axis::X
+        } else {
+            Axis::Y
+        };
+
+        // Spawns and removals shift indices, so the old order no longer names the same
+        // particles; start over from index order.
+        if self.order.len() != particles.len() {
+            self.order.clear();
+            self.order.extend(0..particles.len());
+        }
+        let (axis, boxes) = (self.axis, &self.boxes);
+        self.order
+            .sort_by(|&a, &b| axis.range(&boxes[a]).0.total_cmp(&axis.range(&boxes[b]).0));
+        self.max_extent = boxes
+            .iter()
+            .map(|b| {
+                let (lo, hi) = axis.range(b);
+                hi - lo
+            })
+            .fold(0_f32, f32::max);
+    }
+}
+
+impl Broadphase for SweepAndPrune {
+    fn name(&self) -> &'static str {
+        "sweep and prune"
+    }
+
+    fn update(&mut self, particles: &Particles) {
+        self.build(particles);
+    }
+
+    fn pairs(&mut self, particles: &Particles) -> Vec<(usize, usize)> {
+        self.build(particles);
+
+        let mut pairs = Vec::new();
+        for (s, &i) in self.order.iter().enumerate() {
+            let (_, hi) = self.axis.range(&self.boxes[i]);
+            for &k in self.order[s + 1..].iter() {
+                if self.axis.range(&self.boxes[k]).0 > hi {
+                    break;
+                }
+                if self.boxes[i].overlaps(&self.boxes[k]) && particles.collides(i, k) {
+                    pairs.push((i.min(k), i.max(k)));
+                }
+            }
+        }
+        pairs
+    }
+
+    fn query(&self, _particles: &Particles, aabb: &Aabb, out: &mut Vec<usize>) {
+        let (lo, hi) = self.axis.range(aabb);
+        let lower = |i: &usize| self.axis.range(&self.boxes[*i]).0;
+        let start = self
+            .order
+            .partition_point(|i| lower(i) < lo - self.max_extent);
+        let end = self.order.partition_point(|i| lower(i) <= hi);
+        out.extend(
+            self.order[start..end]
+                .iter()
+                .filter(|&&i| self.boxes[i].overlaps(aabb))
+                .copied(),
+        );
+    }
+
+    fn clone_box(&self) -> Box<dyn Broadphase> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> Option<BroadphaseKind> {
+        Some(BroadphaseKind::SweepAndPrune)
+    }
+}
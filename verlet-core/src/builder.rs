@@ -0,0 +1,175 @@
+use utils::vec::Vec2;
+
+use crate::constraint::Constraint;
+use crate::emitter::Emitter;
+use crate::world::World;
+
+/// Fluent setup of a world, e.g. `WorldBuilder::new().gravity(g).substeps(4).build()`.
+/// Unlike `World::default`, a builder starts without any container.
+#[derive(Clone, Debug)]
+pub struct WorldBuilder {
+    config: WorldConfig,
+}
+
+impl Default for WorldBuilder {
+    fn default() -> Self {
+        WorldBuilder::new()
+    }
+}
+
+impl WorldBuilder {
+    pub fn new() -> Self {
+        let world = World::default();
+        WorldBuilder {
+            config: WorldConfig {
+                gravity: world.gravity,
+                constraints: Vec::new(),
+                emitters: Vec::new(),
+                substeps: world.substeps,
+                response_coef: world.solver.response_coef,
+                max_particles: world.max_particles,
+                max_displacement: world.max_displacement,
+                seed: None,
+                capacity: 0,
+                pool: false,
+            },
+        }
+    }
+
+    pub fn gravity(mut self, gravity: Vec2) -> Self {
+        self.config.gravity = gravity;
+        self
+    }
+
+    /// Adds a container; particles are confined to the union of all of them.
+    pub fn constraint(mut self, constraint: Constraint) -> Self {
+        self.config.constraints.push(constraint);
+        self
+    }
+
+    pub fn emitter(mut self, emitter: Emitter) -> Self {
+        self.config.emitters.push(emitter);
+        self
+    }
+
+    pub fn substeps(mut self, substeps: usize) -> Self {
+        self.config.substeps = substeps;
+        self
+    }
+
+    pub fn response_coef(mut self, response_coef: f32) -> Self {
+        self.config.response_coef = response_coef;
+        self
+    }
+
+    pub fn max_particles(mut self, max_particles: usize) -> Self {
+        self.config.max_particles = Some(max_particles);
+        self
+    }
+
+    pub fn max_displacement(mut self, max_displacement: f32) -> Self {
+        self.config.max_displacement = Some(max_displacement);
+        self
+    }
+
+    /// Seeds the world's random number generator, so every world built from the
+    /// configuration runs the same way.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Reserves room for `capacity` particles in every world built from the configuration,
+    /// see `World::reserve`.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.config.capacity = capacity;
+        self
+    }
+
+    /// Reuses the slots of removed particles, see `World::pool`. Together with `capacity`,
+    /// a world that never holds more than that many particles at once never reallocates.
+    pub fn pool(mut self, pool: bool) -> Self {
+        self.config.pool = pool;
+        self
+    }
+
+    /// The finished configuration and a fresh world built from it.
+    pub fn build(self) -> (WorldConfig, World) {
+        let world = self.config.world();
+        (self.config, world)
+    }
+}
+
+/// Settings produced by `WorldBuilder`. Read-only, so it can rebuild the starting world at any
+/// time, e.g. to reset a scene.
+#[derive(Clone, Debug)]
+pub struct WorldConfig {
+    gravity: Vec2,
+    constraints: Vec<Constraint>,
+    emitters: Vec<Emitter>,
+    substeps: usize,
+    response_coef: f32,
+    max_particles: Option<usize>,
+    max_displacement: Option<f32>,
+    seed: Option<u64>,
+    capacity: usize,
+    pool: bool,
+}
+
+impl WorldConfig {
+    pub fn gravity(&self) -> &Vec2 {
+        &self.gravity
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    pub fn emitters(&self) -> &[Emitter] {
+        &self.emitters
+    }
+
+    pub fn substeps(&self) -> usize {
+        self.substeps
+    }
+
+    pub fn response_coef(&self) -> f32 {
+        self.response_coef
+    }
+
+    pub fn max_particles(&self) -> Option<usize> {
+        self.max_particles
+    }
+
+    pub fn max_displacement(&self) -> Option<f32> {
+        self.max_displacement
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn pool(&self) -> bool {
+        self.pool
+    }
+
+    /// A fresh world with these settings and no particles.
+    pub fn world(&self) -> World {
+        let mut world = World::new(self.gravity.clone(), self.constraints.clone());
+        world.emitters = self.emitters.clone();
+        world.substeps = self.substeps;
+        world.solver.response_coef = self.response_coef;
+        world.max_particles = self.max_particles;
+        world.max_displacement = self.max_displacement;
+        if let Some(seed) = self.seed {
+            world.seed(seed);
+        }
+        world.reserve(self.capacity);
+        world.pool = self.pool;
+        world
+    }
+}
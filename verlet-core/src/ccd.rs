@@ -0,0 +1,89 @@
+use utils::vec::Vec2;
+
+use crate::obstacle::Obstacle;
+use crate::particle::Particles;
+use crate::query::ray_circle;
+
+/// Fraction of the move `from -> to` at which a circle of `radius` first touches `obstacle`,
+/// and the obstacle's normal there. Circles already touching at `from` are left to the
+/// discrete pass.
+pub fn sweep_obstacle(
+    obstacle: &Obstacle,
+    from: &Vec2,
+    to: &Vec2,
+    radius: f32,
+) -> Option<(f32, Vec2)> {
+    let (dir, len) = direction(to.clone() - from.clone())?;
+    if let Obstacle::Sdf { field } = obstacle {
+        let t = field.march(from, &dir, radius, len)?;
+        let n = field.gradient(&(from.clone() + dir.clone() * t))?;
+        return Some((t / len, n));
+    }
+    // A circle touching the obstacle is its center touching the obstacle grown by `radius`.
+    let (t, n) = match obstacle {
+        Obstacle::Circle { center, radius: r } => {
+            Obstacle::circle(center.clone(), r + radius).raycast(from, &dir)?
+        }
+        _ => {
+            let grown = radius + obstacle.thickness();
+            obstacle
+                .segments()
+                .into_iter()
+                .filter_map(|(a, b)| Obstacle::capsule(a, b, grown).raycast(from, &dir))
+                .min_by(|x, y| x.0.total_cmp(&y.0))?
+        }
+    };
+    (t <= len).then_some((t / len, n))
+}
+
+/// Fraction of a step at which circles moving `a0 -> a1` and `b0 -> b1` first come within
+/// `reach` of each other.
+pub fn sweep_circles(a0: &Vec2, a1: &Vec2, b0: &Vec2, b1: &Vec2, reach: f32) -> Option<f32> {
+    let start = a0.clone() - b0.clone();
+    let (dir, len) = direction(a1.clone() - b1.clone() - start.clone())?;
+    let t = ray_circle(&start, &dir, &Vec2::zero(), reach)?;
+    (t <= len).then_some(t / len)
+}
+
+fn direction(d: Vec2) -> Option<(Vec2, f32)> {
+    let len = d.len();
+    (len > f32::EPSILON).then(|| (d / len, len))
+}
+
+/// Moves particle `i` back to fraction `t` of its last step, where it touched a static
+/// surface with normal `n`, and reflects the normal part of its velocity by its restitution.
+pub(crate) fn rewind_wall(particles: &mut Particles, i: usize, t: f32, n: &Vec2) {
+    particles.wake(i);
+    let step = particles.pos[i].clone() - particles.pos_last[i].clone();
+    let v_in = step.dot(n);
+    let bounced = if v_in < 0_f32 {
+        step.clone() - n.clone() * ((1_f32 + particles.restitution[i]) * v_in)
+    } else {
+        step.clone()
+    };
+    particles.pos[i] = particles.pos_last[i].clone() + step * t;
+    particles.pos_last[i] = particles.pos[i].clone() - bounced;
+}
+
+/// Moves particles `a` and `b` back to fraction `t` of their last step, where they touched
+/// along `n` (from `b` to `a`), and exchanges a momentum-conserving impulse between them.
+pub(crate) fn rewind_pair(particles: &mut Particles, a: usize, b: usize, t: f32, n: &Vec2) {
+    particles.wake(a);
+    particles.wake(b);
+    let step_a = particles.pos[a].clone() - particles.pos_last[a].clone();
+    let step_b = particles.pos[b].clone() - particles.pos_last[b].clone();
+    particles.pos[a] = particles.pos_last[a].clone() + step_a.clone() * t;
+    particles.pos[b] = particles.pos_last[b].clone() + step_b.clone() * t;
+
+    let inv_mass = particles.inv_mass(a) + particles.inv_mass(b);
+    let vn = (step_a.clone() - step_b.clone()).dot(n);
+    let (mut va, mut vb) = (step_a, step_b);
+    if inv_mass > 0_f32 && vn < 0_f32 {
+        let impulse =
+            -(1_f32 + 0.5_f32 * (particles.restitution[a] + particles.restitution[b])) * vn;
+        va += n.clone() * (impulse * particles.inv_mass(a) / inv_mass);
+        vb -= n.clone() * (impulse * particles.inv_mass(b) / inv_mass);
+    }
+    particles.pos_last[a] = particles.pos[a].clone() - va;
+    particles.pos_last[b] = particles.pos[b].clone() - vb;
+}
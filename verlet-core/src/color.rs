@@ -0,0 +1,71 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b }
+    }
+}
+
+pub const STEELBLUE: Color = Color::rgb(70, 130, 180);
+
+impl Color {
+    /// Linear blend, `t = 0` is `self` and `t = 1` is `other`.
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        let t = t.clamp(0_f32, 1_f32);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Color::rgb(
+            mix(self.r, other.r),
+            mix(self.g, other.g),
+            mix(self.b, other.b),
+        )
+    }
+}
+
+/// Maps a value, e.g. a temperature, to a color by blending between stops sorted by value.
+/// Values outside the stops take the nearest end color.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Gradient {
+    pub stops: Vec<(f32, Color)>,
+}
+
+impl Gradient {
+    pub fn new(stops: Vec<(f32, Color)>) -> Self {
+        Gradient { stops }
+    }
+
+    /// Black through red and yellow to white, spread evenly from `cold` to `hot`.
+    pub fn thermal(cold: f32, hot: f32) -> Self {
+        let at = |f: f32| cold + f * (hot - cold);
+        Gradient::new(vec![
+            (at(0_f32), Color::rgb(20, 20, 40)),
+            (at(0.35_f32), Color::rgb(200, 30, 20)),
+            (at(0.7_f32), Color::rgb(250, 200, 40)),
+            (at(1_f32), Color::rgb(255, 255, 255)),
+        ])
+    }
+
+    /// The color at `value`; `STEELBLUE` if there are no stops.
+    pub fn sample(&self, value: f32) -> Color {
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return STEELBLUE;
+        };
+        if value <= first.0 {
+            return first.1;
+        }
+        for w in self.stops.windows(2) {
+            let ((a, ca), (b, cb)) = (w[0], w[1]);
+            if value <= b {
+                let t = if b > a { (value - a) / (b - a) } else { 1_f32 };
+                return ca.lerp(cb, t);
+            }
+        }
+        last.1
+    }
+}
@@ -0,0 +1,238 @@
+use utils::vec::Vec2;
+
+use crate::particle::Particles;
+use crate::sdf::SdfGrid;
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Constraint {
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+    Box {
+        min: Vec2,
+        max: Vec2,
+    },
+    /// Convex polygon with counter-clockwise winding.
+    Polygon {
+        points: Vec<Vec2>,
+    },
+    /// Arbitrary shape given by a sampled signed distance field, negative inside.
+    Sdf {
+        field: SdfGrid,
+    },
+}
+
+impl Constraint {
+    pub fn circle(center: Vec2, radius: f32) -> Self {
+        Constraint::Circle { center, radius }
+    }
+
+    pub fn rect(min: Vec2, max: Vec2) -> Self {
+        Constraint::Box { min, max }
+    }
+
+    /// Convex polygon container; the winding is normalized to counter-clockwise.
+    pub fn polygon(mut points: Vec<Vec2>) -> Self {
+        let n = points.len();
+        let area2: f32 = (0..n)
+            .map(|i| {
+                let (a, b) = (&points[i], &points[(i + 1) % n]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum();
+        if area2 < 0_f32 {
+            points.reverse();
+        }
+        Constraint::Polygon { points }
+    }
+
+    pub fn sdf(field: SdfGrid) -> Self {
+        Constraint::Sdf { field }
+    }
+
+    pub fn regular_polygon(center: Vec2, radius: f32, sides: usize) -> Self {
+        let points = (0..sides)
+            .map(|i| {
+                let a = std::f32::consts::TAU * i as f32 / sides as f32;
+                Vec2::new(center.x + radius * a.cos(), center.y + radius * a.sin())
+            })
+            .collect();
+        Constraint::Polygon { points }
+    }
+
+    pub fn center(&self) -> Vec2 {
+        match self {
+            Constraint::Circle { center, .. } => center.clone(),
+            Constraint::Box { min, max } => (min.clone() + max.clone()) / 2_f32,
+            Constraint::Polygon { points } => {
+                let sum = points.iter().fold(Vec2::zero(), |acc, p| acc + p.clone());
+                sum / points.len().max(1) as f32
+            }
+            Constraint::Sdf { field } => field.center(),
+        }
+    }
+
+    pub fn move_to(&mut self, pos: Vec2) {
+        let offset = pos - self.center();
+        match self {
+            Constraint::Circle { center, .. } => *center += offset,
+            Constraint::Box { min, max } => {
+                *min += offset.clone();
+                *max += offset;
+            }
+            Constraint::Polygon { points } => {
+                for p in points.iter_mut() {
+                    *p += offset.clone();
+                }
+            }
+            Constraint::Sdf { field } => field.origin += offset,
+        }
+    }
+
+    pub fn apply(&self, particles: &mut Particles) {
+        for i in 0..particles.len() {
+            if particles.alive[i] {
+                self.apply_to(particles, i);
+            }
+        }
+    }
+
+    /// How far the disc of `radius` at `pos` sticks out of the region, `0` when fully inside.
+    pub fn penetration(&self, pos: &Vec2, radius: f32) -> f32 {
+        match self {
+            Constraint::Circle { center, radius: r } => {
+                let dist = (pos.clone() - center.clone()).len();
+                f32::max(dist - (r - radius), 0_f32)
+            }
+            Constraint::Box { min, max } => {
+                let dx = f32::max(min.x + radius - pos.x, pos.x - (max.x - radius));
+                let dy = f32::max(min.y + radius - pos.y, pos.y - (max.y - radius));
+                Vec2::new(f32::max(dx, 0_f32), f32::max(dy, 0_f32)).len()
+            }
+            Constraint::Polygon { points } => match deepest_edge(points, pos) {
+                Some((depth, _)) => f32::max(radius - depth, 0_f32),
+                None => 0_f32,
+            },
+            Constraint::Sdf { field } => f32::max(field.distance(pos) + radius, 0_f32),
+        }
+    }
+
+    /// Pushes particle `i` back inside the region.
+    pub fn apply_to(&self, particles: &mut Particles, i: usize) {
+        let r = particles.radius[i];
+        match self {
+            Constraint::Circle { center, radius } => {
+                let v = center.clone() - particles.pos[i].clone();
+                let dist = v.len();
+                if dist > (radius - r) {
+                    let n = v / dist;
+                    let pos = center.clone() - n.clone() * (radius - r);
+                    resolve_wall(particles, i, pos, &n);
+                }
+            }
+            Constraint::Box { min, max } => {
+                let p = particles.pos[i].clone();
+                if p.x < min.x + r {
+                    let pos = Vec2::new(min.x + r, p.y);
+                    resolve_wall(particles, i, pos, &Vec2::new(1_f32, 0_f32));
+                } else if p.x > max.x - r {
+                    let pos = Vec2::new(max.x - r, p.y);
+                    resolve_wall(particles, i, pos, &Vec2::new(-1_f32, 0_f32));
+                }
+                let p = particles.pos[i].clone();
+                if p.y < min.y + r {
+                    let pos = Vec2::new(p.x, min.y + r);
+                    resolve_wall(particles, i, pos, &Vec2::new(0_f32, 1_f32));
+                } else if p.y > max.y - r {
+                    let pos = Vec2::new(p.x, max.y - r);
+                    resolve_wall(particles, i, pos, &Vec2::new(0_f32, -1_f32));
+                }
+            }
+            Constraint::Polygon { points } => {
+                // Push out of the deepest edge first; repeating handles corners where two
+                // edges are violated at once.
+                for _ in 0..points.len() {
+                    let Some((depth, n)) = deepest_edge(points, &particles.pos[i]) else {
+                        break;
+                    };
+                    if depth >= r {
+                        break;
+                    }
+                    let pos = particles.pos[i].clone() + n.clone() * (r - depth);
+                    resolve_wall(particles, i, pos, &n);
+                }
+            }
+            Constraint::Sdf { field } => {
+                // Interpolated gradients are only approximate near corners, so a second pass
+                // catches what the first one missed.
+                for _ in 0..2 {
+                    let depth = field.distance(&particles.pos[i]) + r;
+                    if depth <= 0_f32 {
+                        break;
+                    }
+                    let Some(out) = field.gradient(&particles.pos[i]) else {
+                        break;
+                    };
+                    let pos = particles.pos[i].clone() - out.clone() * depth;
+                    resolve_wall(particles, i, pos, &(out * -1_f32));
+                }
+            }
+        }
+    }
+}
+
+/// Smallest signed distance from `p` to the polygon's edges, with that edge's inward normal.
+fn deepest_edge(points: &[Vec2], p: &Vec2) -> Option<(f32, Vec2)> {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (&points[i], &points[(i + 1) % n]);
+            let mut normal = (b.clone() - a.clone()).perp();
+            normal.normalize();
+            ((p.clone() - a.clone()).dot(&normal), normal)
+        })
+        .min_by(|x, y| x.0.total_cmp(&y.0))
+}
+
+/// Moves particle `i` back onto the wall at `pos`; `n` is the wall normal pointing into the
+/// container. The implicit velocity is carried over rather than changed by the move, with its
+/// part into the wall mirrored and scaled by the restitution, so particles slide along the
+/// wall at full speed and inelastic ones simply stop going into it.
+pub(crate) fn resolve_wall(particles: &mut Particles, i: usize, pos: Vec2, n: &Vec2) {
+    particles.wake(i);
+    let mut vel = &particles.pos[i] - &particles.pos_last[i];
+    particles.pos[i] = pos;
+
+    let v_in = vel.dot(n);
+    if v_in < 0_f32 {
+        vel -= n.clone() * ((1_f32 + particles.restitution[i]) * v_in);
+    }
+    particles.pos_last[i] = &particles.pos[i] - &vel;
+}
+
+/// Confines each particle to the union of the regions selected by its `regions` mask: a
+/// particle inside any of them is left alone, otherwise it is pushed into the closest one.
+pub fn apply_all(constraints: &[Constraint], particles: &mut Particles) {
+    for m in 0..particles.len() {
+        if particles.pinned[m] || !particles.alive[m] {
+            continue;
+        }
+        let mut closest: Option<(f32, &Constraint)> = None;
+        for (i, c) in constraints.iter().enumerate() {
+            if i >= 32 || particles.regions[m] & (1 << i) == 0 {
+                continue;
+            }
+            let depth = c.penetration(&particles.pos[m], particles.radius[m]);
+            if closest.as_ref().is_none_or(|(d, _)| depth < *d) {
+                closest = Some((depth, c));
+            }
+        }
+        if let Some((depth, c)) = closest {
+            if depth > 0_f32 {
+                c.apply_to(particles, m);
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use crate::handle::ParticleHandle;
+use crate::link::Link;
+use crate::spring::Spring;
+use crate::user_data::UserData;
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event {
+    ConstraintBroken(BrokenConstraint),
+    /// Particles were removed or reordered; maps every old index to its new one, or `None`
+    /// if removed.
+    ParticlesRemapped(Vec<Option<usize>>),
+    /// Resting particle `absorbed` was merged into `kept`, which keeps its own user data;
+    /// `data` is what `absorbed` carried. Followed by the `ParticlesRemapped` that drops it.
+    ParticlesMerged {
+        kept: ParticleHandle,
+        absorbed: ParticleHandle,
+        #[cfg_attr(feature = "serde", serde(skip))]
+        data: Option<Box<dyn UserData>>,
+    },
+    /// User data of particles that were removed or despawned, right after the
+    /// `ParticlesRemapped` that removed them.
+    UserDataRemoved(
+        #[cfg_attr(feature = "serde", serde(skip))] Vec<(ParticleHandle, Box<dyn UserData>)>,
+    ),
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BrokenConstraint {
+    Link(Link),
+    Spring(Spring),
+}
@@ -0,0 +1,60 @@
+use std::f32::consts::PI;
+
+use utils::vec::Vec2;
+
+use crate::particle::Particles;
+
+/// Rectangular body of fluid. Particles in it are pushed against gravity by the weight of the
+/// fluid they displace, so particles less dense than `density` float, and are slowed by
+/// `drag` per second in proportion to how deep they are.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FluidZone {
+    pub min: Vec2,
+    pub max: Vec2,
+    /// Mass per unit area, comparable with `Particle::with_density`.
+    pub density: f32,
+    pub drag: f32,
+}
+
+impl FluidZone {
+    pub fn new(min: Vec2, max: Vec2, density: f32) -> Self {
+        FluidZone {
+            min,
+            max,
+            density,
+            drag: 2_f32,
+        }
+    }
+
+    /// Area of the disc of `radius` at `pos` inside the zone. Exact when only one pair of
+    /// opposite edges cuts the disc, a close estimate in the corners.
+    pub fn submerged_area(&self, pos: &Vec2, radius: f32) -> f32 {
+        let r = radius;
+        let area = PI * r * r;
+        if area <= 0_f32 {
+            return 0_f32;
+        }
+        let below = |c: f32| {
+            let c = c.clamp(-r, r);
+            c * (r * r - c * c).sqrt() + r * r * (c / r).asin() + 0.5_f32 * area
+        };
+        let height = below(self.max.y - pos.y) - below(self.min.y - pos.y);
+        let width = below(self.max.x - pos.x) - below(self.min.x - pos.x);
+        height * width / area
+    }
+
+    pub fn apply(&self, particles: &mut Particles, gravity: &Vec2, dt: f32) {
+        for i in 0..particles.len() {
+            let radius = particles.radius[i];
+            let submerged = self.submerged_area(&particles.pos[i], radius);
+            if submerged <= 0_f32 {
+                continue;
+            }
+            let fraction = submerged / (PI * radius * radius);
+            let drag = particles.velocity(i, dt) * (self.drag * fraction);
+            particles.apply_force(i, gravity.clone() * -(self.density * submerged));
+            particles.acc[i] -= drag;
+        }
+    }
+}
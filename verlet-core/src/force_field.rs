@@ -0,0 +1,192 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use utils::vec::Vec2;
+
+use crate::particle::Particles;
+
+/// A force evaluated for every particle each substep, e.g. wind or turbulence.
+pub trait ForceField: Debug + Send + Sync {
+    /// The force on particle `i`.
+    fn force(&self, particles: &Particles, i: usize) -> Vec2;
+
+    fn clone_box(&self) -> Box<dyn ForceField>;
+
+    /// Settings to rebuild this field from; custom fields return `None` and aren't saved.
+    fn kind(&self) -> Option<ForceFieldKind> {
+        None
+    }
+}
+
+impl Clone for Box<dyn ForceField> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// A built-in force field, used to save and restore a world's fields.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ForceFieldKind {
+    Wind(Wind),
+    Turbulence(Turbulence),
+}
+
+impl ForceFieldKind {
+    pub fn build(&self) -> Box<dyn ForceField> {
+        match self {
+            ForceFieldKind::Wind(wind) => Box::new(wind.clone()),
+            ForceFieldKind::Turbulence(turbulence) => Box::new(turbulence.clone()),
+        }
+    }
+}
+
+/// The same force on every particle, so light particles are blown further than heavy ones.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Wind {
+    pub force: Vec2,
+}
+
+impl Wind {
+    pub fn new(force: Vec2) -> Self {
+        Wind { force }
+    }
+}
+
+impl ForceField for Wind {
+    fn force(&self, _particles: &Particles, _i: usize) -> Vec2 {
+        self.force.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn ForceField> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> Option<ForceFieldKind> {
+        Some(ForceFieldKind::Wind(self.clone()))
+    }
+}
+
+/// Swirling, divergence-free force from the curl of gradient noise. `scale` is roughly the
+/// size of an eddy and `seed` picks the noise pattern.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Turbulence {
+    pub strength: f32,
+    pub scale: f32,
+    pub seed: u32,
+}
+
+impl Turbulence {
+    pub fn new(strength: f32, scale: f32) -> Self {
+        Turbulence {
+            strength,
+            scale,
+            seed: 0,
+        }
+    }
+}
+
+impl ForceField for Turbulence {
+    fn force(&self, particles: &Particles, i: usize) -> Vec2 {
+        // Central differences of the noise potential, in noise space.
+        const H: f32 = 0.01_f32;
+        let x = particles.pos[i].x / self.scale;
+        let y = particles.pos[i].y / self.scale;
+        let dx = noise(x + H, y, self.seed) - noise(x - H, y, self.seed);
+        let dy = noise(x, y + H, self.seed) - noise(x, y - H, self.seed);
+        Vec2::new(dy, -dx) * (self.strength / (2_f32 * H))
+    }
+
+    fn clone_box(&self) -> Box<dyn ForceField> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> Option<ForceFieldKind> {
+        Some(ForceFieldKind::Turbulence(self.clone()))
+    }
+}
+
+/// Returns the force on particle `i` of the given particles.
+pub type ForceClosure = dyn Fn(&Particles, usize) -> Vec2 + Send + Sync;
+
+/// A user closure as a force field.
+#[derive(Clone)]
+pub struct ForceFn(pub Arc<ForceClosure>);
+
+impl Debug for ForceFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ForceFn")
+    }
+}
+
+impl ForceField for ForceFn {
+    fn force(&self, particles: &Particles, i: usize) -> Vec2 {
+        (self.0)(particles, i)
+    }
+
+    fn clone_box(&self) -> Box<dyn ForceField> {
+        Box::new(self.clone())
+    }
+}
+
+fn hash(x: i32, y: i32, seed: u32) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (y as u32).wrapping_mul(0xd816_3841)
+        ^ seed.wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2c1b_3c6d);
+    h ^ (h >> 12)
+}
+
+/// Dot product of the lattice point's pseudo-random unit gradient with the offset `(x, y)`.
+fn grad(ix: i32, iy: i32, x: f32, y: f32, seed: u32) -> f32 {
+    let angle = hash(ix, iy, seed) as f32 * (std::f32::consts::TAU / u32::MAX as f32);
+    angle.cos() * x + angle.sin() * y
+}
+
+/// Smooth 2D gradient noise, roughly in `[-0.7, 0.7]`.
+fn noise(x: f32, y: f32, seed: u32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (ix, iy) = (x0 as i32, y0 as i32);
+    let fade = |t: f32| t * t * t * (t * (t * 6_f32 - 15_f32) + 10_f32);
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+
+    let (u, v) = (fade(fx), fade(fy));
+    let bottom = lerp(
+        grad(ix, iy, fx, fy, seed),
+        grad(ix + 1, iy, fx - 1_f32, fy, seed),
+        u,
+    );
+    let top = lerp(
+        grad(ix, iy + 1, fx, fy - 1_f32, seed),
+        grad(ix + 1, iy + 1, fx - 1_f32, fy - 1_f32, seed),
+        u,
+    );
+    lerp(bottom, top, v)
+}
+
+/// Saves the built-in fields of a world; custom ones are dropped.
+#[cfg(feature = "serde")]
+pub(crate) mod persist {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{ForceField, ForceFieldKind};
+
+    pub fn serialize<S: Serializer>(
+        fields: &[Box<dyn ForceField>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let kinds: Vec<ForceFieldKind> = fields.iter().filter_map(|f| f.kind()).collect();
+        kinds.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Box<dyn ForceField>>, D::Error> {
+        let kinds = Vec::<ForceFieldKind>::deserialize(deserializer)?;
+        Ok(kinds.iter().map(ForceFieldKind::build).collect())
+    }
+}
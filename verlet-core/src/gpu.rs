@@ -0,0 +1,427 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+use crate::particle::Particles;
+use crate::solver::COLLISION_MARGIN;
+
+const WORKGROUP_SIZE: u32 = 64;
+/// Particles stored per grid cell; further particles in a crowded cell are not seen as
+/// neighbours for that step.
+const CELL_CAPACITY: u32 = 16;
+/// Upper bound on grid cells; sparse worlds get coarser cells instead of more of them.
+const MAX_CELLS: usize = 1 << 20;
+
+#[derive(Debug)]
+pub enum GpuError {
+    NoAdapter,
+    Device(wgpu::RequestDeviceError),
+}
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuError::NoAdapter => write!(f, "no GPU adapter with compute shader support"),
+            GpuError::Device(e) => write!(f, "failed to open GPU device: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GpuError {}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct Params {
+    count: u32,
+    cells_x: u32,
+    cells_y: u32,
+    cell_capacity: u32,
+    origin: [f32; 2],
+    cell_size: f32,
+    response_coef: f32,
+    margin: f32,
+    _pad: [f32; 3],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct GpuParticle {
+    pos: [f32; 2],
+    radius: f32,
+    inv_mass: f32,
+    layer: u32,
+    mask: u32,
+    _pad: [u32; 2],
+}
+
+struct Buffers {
+    particles: wgpu::Buffer,
+    next_pos: wgpu::Buffer,
+    // Only used through the bind group, held so they live as long as it does.
+    _cell_counts: wgpu::Buffer,
+    _cell_items: wgpu::Buffer,
+    excluded_offsets: wgpu::Buffer,
+    excluded: wgpu::Buffer,
+    readback: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    particle_capacity: usize,
+    cell_capacity: usize,
+    excluded_capacity: usize,
+}
+
+/// Collision relaxation on the GPU. Each call uploads the particles, buckets them into a grid
+/// and runs Jacobi iterations in compute shaders, then reads the positions back. Only the
+/// positional correction runs on the GPU: restitution, friction and waking sleepers on impact
+/// are CPU-only.
+pub struct GpuSolver {
+    adapter: String,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    layout: wgpu::BindGroupLayout,
+    clear: wgpu::ComputePipeline,
+    insert: wgpu::ComputePipeline,
+    solve: wgpu::ComputePipeline,
+    apply: wgpu::ComputePipeline,
+    params: wgpu::Buffer,
+    buffers: Option<Buffers>,
+}
+
+impl fmt::Debug for GpuSolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GpuSolver")
+            .field("adapter", &self.adapter)
+            .finish()
+    }
+}
+
+impl GpuSolver {
+    /// Opens the default adapter, blocking until the device is ready.
+    pub fn new() -> Result<Self, GpuError> {
+        let instance = wgpu::Instance::default();
+        let adapter =
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+                .filter(|a| {
+                    a.get_downlevel_capabilities()
+                        .flags
+                        .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+                })
+                .ok_or(GpuError::NoAdapter)?;
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("verlet"),
+                required_features: wgpu::Features::empty(),
+                required_limits: adapter.limits(),
+                memory_hints: wgpu::MemoryHints::Performance,
+            },
+            None,
+        ))
+        .map_err(GpuError::Device)?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("verlet collisions"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("gpu.wgsl").into()),
+        });
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("verlet collisions"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1, false),
+                storage(2, false),
+                storage(3, false),
+                storage(4, false),
+                storage(5, true),
+                storage(6, true),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("verlet collisions"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point,
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let (clear, insert, solve, apply) = (
+            pipeline("clear"),
+            pipeline("insert"),
+            pipeline("solve"),
+            pipeline("apply"),
+        );
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("params"),
+            size: std::mem::size_of::<Params>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Ok(GpuSolver {
+            adapter: adapter.get_info().name,
+            device,
+            queue,
+            layout,
+            clear,
+            insert,
+            solve,
+            apply,
+            params,
+            buffers: None,
+        })
+    }
+
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter
+    }
+
+    /// Separates overlapping particles except the pairs in `excluded`, given as `(i, k)` with
+    /// `i < k`, running `iterations` relaxation passes over the same grid.
+    pub fn solve_collisions(
+        &mut self,
+        particles: &mut Particles,
+        excluded: &HashSet<(usize, usize)>,
+        response_coef: f32,
+        iterations: u32,
+    ) {
+        if particles.is_empty() || iterations == 0 {
+            return;
+        }
+        let upload: Vec<GpuParticle> = (0..particles.len())
+            .map(|i| GpuParticle {
+                pos: [particles.pos[i].x, particles.pos[i].y],
+                radius: particles.radius[i],
+                inv_mass: particles.inv_mass(i),
+                layer: particles.layer[i],
+                mask: if particles.alive[i] {
+                    particles.mask[i]
+                } else {
+                    0
+                },
+                _pad: [0; 2],
+            })
+            .collect();
+        let (offsets, neighbours) = adjacency(particles.len(), excluded);
+        let params = grid_params(particles, response_coef);
+        let cells = (params.cells_x * params.cells_y) as usize;
+
+        self.reserve(particles.len(), cells, neighbours.len());
+        let buffers = self.buffers.as_ref().unwrap();
+        self.queue
+            .write_buffer(&self.params, 0, bytemuck::bytes_of(&params));
+        self.queue
+            .write_buffer(&buffers.particles, 0, bytemuck::cast_slice(&upload));
+        self.queue
+            .write_buffer(&buffers.excluded_offsets, 0, bytemuck::cast_slice(&offsets));
+        if !neighbours.is_empty() {
+            self.queue
+                .write_buffer(&buffers.excluded, 0, bytemuck::cast_slice(&neighbours));
+        }
+
+        let groups = |n: usize| (n as u32).div_ceil(WORKGROUP_SIZE);
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_bind_group(0, &buffers.bind_group, &[]);
+            pass.set_pipeline(&self.clear);
+            pass.dispatch_workgroups(groups(cells), 1, 1);
+            pass.set_pipeline(&self.insert);
+            pass.dispatch_workgroups(groups(particles.len()), 1, 1);
+            for _ in 0..iterations {
+                pass.set_pipeline(&self.solve);
+                pass.dispatch_workgroups(groups(particles.len()), 1, 1);
+                pass.set_pipeline(&self.apply);
+                pass.dispatch_workgroups(groups(particles.len()), 1, 1);
+            }
+        }
+        let size = (particles.len() * std::mem::size_of::<[f32; 2]>()) as u64;
+        encoder.copy_buffer_to_buffer(&buffers.next_pos, 0, &buffers.readback, 0, size);
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = buffers.readback.slice(..size);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        self.device.poll(wgpu::Maintain::Wait);
+        {
+            let data = slice.get_mapped_range();
+            let positions: &[[f32; 2]] = bytemuck::cast_slice(&data);
+            for (p, pos) in particles.pos.iter_mut().zip(positions) {
+                p.x = pos[0];
+                p.y = pos[1];
+            }
+        }
+        buffers.readback.unmap();
+    }
+
+    /// Grows the buffers to fit, doubling so steady growth doesn't reallocate every step.
+    fn reserve(&mut self, particles: usize, cells: usize, excluded: usize) {
+        if let Some(b) = &self.buffers {
+            if b.particle_capacity >= particles
+                && b.cell_capacity >= cells
+                && b.excluded_capacity >= excluded
+            {
+                return;
+            }
+        }
+        let particle_capacity = particles.next_power_of_two();
+        let cell_capacity = cells.next_power_of_two();
+        // Bindings can't be empty, so keep room for at least one exclusion.
+        let excluded_capacity = excluded.max(1).next_power_of_two();
+
+        let storage = wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST;
+        let buffer = |label, size: usize, usage| {
+            self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: size as u64,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        let particles_buf = buffer(
+            "particles",
+            particle_capacity * std::mem::size_of::<GpuParticle>(),
+            storage,
+        );
+        let next_pos = buffer(
+            "next positions",
+            particle_capacity * 8,
+            storage | wgpu::BufferUsages::COPY_SRC,
+        );
+        let cell_counts = buffer("cell counts", cell_capacity * 4, storage);
+        let cell_items = buffer(
+            "cell items",
+            cell_capacity * CELL_CAPACITY as usize * 4,
+            storage,
+        );
+        let excluded_offsets = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("excluded offsets"),
+                contents: bytemuck::cast_slice(&vec![0_u32; particle_capacity + 1]),
+                usage: storage,
+            });
+        let excluded_buf = buffer("excluded", excluded_capacity * 4, storage);
+        let readback = buffer(
+            "readback",
+            particle_capacity * 8,
+            wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        );
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("verlet collisions"),
+            layout: &self.layout,
+            entries: &[
+                (0, &self.params),
+                (1, &particles_buf),
+                (2, &next_pos),
+                (3, &cell_counts),
+                (4, &cell_items),
+                (5, &excluded_offsets),
+                (6, &excluded_buf),
+            ]
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding,
+                resource: buffer.as_entire_binding(),
+            }),
+        });
+        self.buffers = Some(Buffers {
+            particles: particles_buf,
+            next_pos,
+            _cell_counts: cell_counts,
+            _cell_items: cell_items,
+            excluded_offsets,
+            excluded: excluded_buf,
+            readback,
+            bind_group,
+            particle_capacity,
+            cell_capacity,
+            excluded_capacity,
+        });
+    }
+}
+
+/// Exclusions as per-particle neighbour lists: particle `i`'s excluded partners are
+/// `neighbours[offsets[i]..offsets[i + 1]]`.
+fn adjacency(count: usize, excluded: &HashSet<(usize, usize)>) -> (Vec<u32>, Vec<u32>) {
+    let mut lists = vec![Vec::new(); count];
+    for &(i, k) in excluded.iter().filter(|&&(i, k)| i < count && k < count) {
+        lists[i].push(k as u32);
+        lists[k].push(i as u32);
+    }
+    let mut offsets = Vec::with_capacity(count + 1);
+    let mut neighbours = Vec::new();
+    offsets.push(0);
+    for list in lists {
+        neighbours.extend(list);
+        offsets.push(neighbours.len() as u32);
+    }
+    (offsets, neighbours)
+}
+
+/// Grid covering every finite particle, with cells one largest diameter wide.
+fn grid_params(particles: &Particles, response_coef: f32) -> Params {
+    let (mut min, mut max) = ([f32::MAX; 2], [f32::MIN; 2]);
+    let mut max_radius = 0_f32;
+    for (pos, &radius) in particles
+        .pos
+        .iter()
+        .zip(particles.radius.iter())
+        .filter(|(pos, _)| pos.x.is_finite() && pos.y.is_finite())
+    {
+        min = [f32::min(min[0], pos.x), f32::min(min[1], pos.y)];
+        max = [f32::max(max[0], pos.x), f32::max(max[1], pos.y)];
+        max_radius = f32::max(max_radius, radius);
+    }
+    if min[0] > max[0] {
+        (min, max) = ([0_f32; 2], [0_f32; 2]);
+    }
+    let mut cell_size = f32::max(2_f32 * max_radius + COLLISION_MARGIN, 1_f32);
+    let extent = [max[0] - min[0], max[1] - min[1]];
+    let cells = |size: f32| {
+        (
+            (extent[0] / size) as usize + 1,
+            (extent[1] / size) as usize + 1,
+        )
+    };
+    while cells(cell_size).0 * cells(cell_size).1 > MAX_CELLS {
+        cell_size *= 2_f32;
+    }
+    let (cells_x, cells_y) = cells(cell_size);
+    Params {
+        count: particles.len() as u32,
+        cells_x: cells_x as u32,
+        cells_y: cells_y as u32,
+        cell_capacity: CELL_CAPACITY,
+        origin: min,
+        cell_size,
+        response_coef,
+        margin: COLLISION_MARGIN,
+        _pad: [0_f32; 3],
+    }
+}
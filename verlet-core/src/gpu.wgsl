@@ -0,0 +1,122 @@
+// Jacobi collision relaxation over a uniform grid. `clear` and `insert` bucket particles into
+// cells, `solve` computes every particle's displacement from its neighbours in the 3x3 cells
+// around it, and `apply` writes the displaced positions back for the next iteration.
+
+struct Params {
+    count: u32,
+    cells_x: u32,
+    cells_y: u32,
+    cell_capacity: u32,
+    origin: vec2<f32>,
+    cell_size: f32,
+    response_coef: f32,
+    margin: f32,
+}
+
+struct Particle {
+    pos: vec2<f32>,
+    radius: f32,
+    inv_mass: f32,
+    layer: u32,
+    mask: u32,
+    _pad: vec2<u32>,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read_write> particles: array<Particle>;
+@group(0) @binding(2) var<storage, read_write> next_pos: array<vec2<f32>>;
+@group(0) @binding(3) var<storage, read_write> cell_counts: array<atomic<u32>>;
+@group(0) @binding(4) var<storage, read_write> cell_items: array<u32>;
+// Pairs excluded from collision, e.g. linked particles, as per-particle adjacency lists.
+@group(0) @binding(5) var<storage, read> excluded_offsets: array<u32>;
+@group(0) @binding(6) var<storage, read> excluded: array<u32>;
+
+fn cell_of(pos: vec2<f32>) -> vec2<i32> {
+    let c = vec2<i32>(floor((pos - params.origin) / params.cell_size));
+    return clamp(c, vec2<i32>(0, 0), vec2<i32>(i32(params.cells_x) - 1, i32(params.cells_y) - 1));
+}
+
+fn cell_index(c: vec2<i32>) -> u32 {
+    return u32(c.y) * params.cells_x + u32(c.x);
+}
+
+fn is_excluded(i: u32, j: u32) -> bool {
+    for (var e = excluded_offsets[i]; e < excluded_offsets[i + 1u]; e++) {
+        if excluded[e] == j {
+            return true;
+        }
+    }
+    return false;
+}
+
+@compute @workgroup_size(64)
+fn clear(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x < params.cells_x * params.cells_y {
+        atomicStore(&cell_counts[id.x], 0u);
+    }
+}
+
+@compute @workgroup_size(64)
+fn insert(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= params.count {
+        return;
+    }
+    let cell = cell_index(cell_of(particles[i].pos));
+    let slot = atomicAdd(&cell_counts[cell], 1u);
+    if slot < params.cell_capacity {
+        cell_items[cell * params.cell_capacity + slot] = i;
+    }
+}
+
+@compute @workgroup_size(64)
+fn solve(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= params.count {
+        return;
+    }
+    let p = particles[i];
+    var displacement = vec2<f32>(0.0, 0.0);
+    if p.inv_mass > 0.0 {
+        let home = cell_of(p.pos);
+        for (var dy = -1; dy <= 1; dy++) {
+            for (var dx = -1; dx <= 1; dx++) {
+                let c = home + vec2<i32>(dx, dy);
+                if c.x < 0 || c.y < 0 || c.x >= i32(params.cells_x) || c.y >= i32(params.cells_y) {
+                    continue;
+                }
+                let cell = cell_index(c);
+                let n = min(atomicLoad(&cell_counts[cell]), params.cell_capacity);
+                for (var s = 0u; s < n; s++) {
+                    let j = cell_items[cell * params.cell_capacity + s];
+                    let q = particles[j];
+                    if j == i || (p.layer & q.mask) == 0u || (q.layer & p.mask) == 0u {
+                        continue;
+                    }
+                    let min_dist = p.radius + q.radius + params.margin;
+                    let v = p.pos - q.pos;
+                    let dist2 = dot(v, v);
+                    if dist2 >= min_dist * min_dist || is_excluded(i, j) {
+                        continue;
+                    }
+                    let dist = sqrt(dist2);
+                    // Coincident particles separate vertically, the lower index upwards.
+                    var normal = vec2<f32>(0.0, select(-1.0, 1.0, i < j));
+                    if dist > 1.0e-7 {
+                        normal = v / dist;
+                    }
+                    let ratio = p.inv_mass / (p.inv_mass + q.inv_mass);
+                    displacement += normal * (ratio * 0.5 * params.response_coef * (min_dist - dist));
+                }
+            }
+        }
+    }
+    next_pos[i] = p.pos + displacement;
+}
+
+@compute @workgroup_size(64)
+fn apply(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x < params.count {
+        particles[id.x].pos = next_pos[id.x];
+    }
+}
@@ -0,0 +1,125 @@
+/// Stable reference to a particle. Unlike an index into `World::particles` it stays valid
+/// while other particles are removed, and stops resolving once its own particle is gone, even
+/// if a new particle reuses the storage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParticleHandle {
+    slot: u32,
+    generation: u32,
+}
+
+impl ParticleHandle {
+    /// Number unique to this handle, e.g. to label a particle in exported data. Particles that
+    /// reuse a removed particle's slot get a different one.
+    pub fn to_bits(self) -> u64 {
+        (self.generation as u64) << 32 | self.slot as u64
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Slot {
+    generation: u32,
+    index: Option<usize>,
+}
+
+/// Maps handles to particle indices and back. Slots of removed particles are reused with a
+/// bumped generation, so old handles to them turn stale.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Handles {
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+    /// Slot of each tracked particle, by index; `None` for the free slots of a pooled world.
+    by_index: Vec<Option<u32>>,
+}
+
+impl Handles {
+    /// Gives each of particles `0..count` without a slot one, and frees the slots of any
+    /// tracked particles past `count`.
+    pub(crate) fn track(&mut self, count: usize) {
+        if self.by_index.len() > count {
+            for slot in self.by_index.split_off(count).into_iter().flatten() {
+                self.release(slot);
+            }
+        }
+        while self.by_index.len() < count {
+            let slot = self.claim(self.by_index.len());
+            self.by_index.push(Some(slot));
+        }
+    }
+
+    /// Gives particle `index` a slot if it has none, e.g. after a remap left it dead in place.
+    pub(crate) fn retrack(&mut self, index: usize) {
+        if self.by_index[index].is_none() {
+            self.by_index[index] = Some(self.claim(index));
+        }
+    }
+
+    pub(crate) fn handle(&self, index: usize) -> Option<ParticleHandle> {
+        let slot = (*self.by_index.get(index)?)?;
+        Some(ParticleHandle {
+            slot,
+            generation: self.slots[slot as usize].generation,
+        })
+    }
+
+    pub(crate) fn index(&self, handle: ParticleHandle) -> Option<usize> {
+        let slot = self.slots.get(handle.slot as usize)?;
+        if slot.generation == handle.generation {
+            slot.index
+        } else {
+            None
+        }
+    }
+
+    /// Applies a compaction or reordering of the particles, after which `count` are left;
+    /// `remap` gives every old index its new one, or `None` if removed.
+    pub(crate) fn remap(&mut self, remap: &[Option<usize>], count: usize) {
+        self.track(remap.len());
+        let mut by_index = vec![None; count];
+        for (i, new) in remap.iter().enumerate() {
+            let Some(slot) = self.by_index[i] else {
+                continue;
+            };
+            match *new {
+                Some(n) => {
+                    self.slots[slot as usize].index = Some(n);
+                    by_index[n] = Some(slot);
+                }
+                None => self.release(slot),
+            }
+        }
+        self.by_index = by_index;
+    }
+
+    /// Makes room for `additional` more tracked particles.
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        self.slots.reserve(additional);
+        self.by_index.reserve(additional);
+    }
+
+    /// A free or new slot pointing at `index`.
+    fn claim(&mut self, index: usize) -> u32 {
+        match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot as usize].index = Some(index);
+                slot
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    index: Some(index),
+                });
+                self.slots.len() as u32 - 1
+            }
+        }
+    }
+
+    fn release(&mut self, slot: u32) {
+        let s = &mut self.slots[slot as usize];
+        s.generation = s.generation.wrapping_add(1);
+        s.index = None;
+        self.free.push(slot);
+    }
+}
@@ -0,0 +1,35 @@
+use utils::vec::Vec2;
+
+use crate::particle::Particles;
+
+/// Holds particles touching its disc at `temperature`, a source or a sink depending on
+/// whether it is hotter or colder than them. `rate` is how fast, per second, they approach it.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeatSource {
+    pub center: Vec2,
+    pub radius: f32,
+    pub temperature: f32,
+    pub rate: f32,
+}
+
+impl HeatSource {
+    pub fn new(center: Vec2, radius: f32, temperature: f32) -> Self {
+        HeatSource {
+            center,
+            radius,
+            temperature,
+            rate: 5_f32,
+        }
+    }
+
+    pub fn apply(&self, particles: &mut Particles, dt: f32) {
+        let blend = f32::min(self.rate * dt, 1_f32);
+        for i in 0..particles.len() {
+            if (&particles.pos[i] - &self.center).len() < self.radius + particles.radius[i] {
+                let t = &mut particles.temperature[i];
+                *t += blend * (self.temperature - *t);
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::VecDeque;
+
+use crate::world::World;
+
+/// Ring buffer of the most recent world snapshots, for rewinding a run. Once full, pushing a
+/// snapshot drops the oldest.
+#[derive(Clone, Debug)]
+pub struct History {
+    capacity: usize,
+    snapshots: VecDeque<World>,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        History {
+            capacity,
+            snapshots: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Enough room for `seconds` of steps of `dt`.
+    pub fn with_duration(seconds: f32, dt: f32) -> Self {
+        History::new(f32::ceil(seconds / dt) as usize)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn push(&mut self, world: &World) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(world.clone());
+    }
+
+    /// Takes the most recent snapshot, so repeated calls walk backwards in time. Stepping on
+    /// from a popped snapshot overwrites the discarded future.
+    pub fn pop(&mut self) -> Option<World> {
+        self.snapshots.pop_back()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
@@ -0,0 +1,246 @@
+use std::fmt::Debug;
+
+use utils::vec::Vec2;
+
+use crate::particle::Particles;
+
+/// Accumulates the forces acting on a trial copy of the particles into their `acc`.
+pub type Forces<'a> = dyn FnMut(&mut Particles) + 'a;
+
+/// Advances particles by one step from their accumulated acceleration, then clears it.
+/// Higher-order integrators can sample `forces` at intermediate states.
+pub trait Integrator: Debug + Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn integrate(
+        &self,
+        particles: &mut Particles,
+        dt: f32,
+        global_damping: f32,
+        forces: &mut Forces,
+    );
+
+    fn clone_box(&self) -> Box<dyn Integrator>;
+
+    /// Settings to rebuild this integrator from; custom implementations return `None`.
+    fn kind(&self) -> Option<IntegratorKind> {
+        None
+    }
+}
+
+impl Clone for Box<dyn Integrator> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// A built-in integrator, used to save and restore a world's choice.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IntegratorKind {
+    #[default]
+    Verlet,
+    SemiImplicitEuler,
+    Rk4,
+}
+
+impl IntegratorKind {
+    pub fn build(&self) -> Box<dyn Integrator> {
+        match self {
+            IntegratorKind::Verlet => Box::new(Verlet),
+            IntegratorKind::SemiImplicitEuler => Box::new(SemiImplicitEuler),
+            IntegratorKind::Rk4 => Box::new(Rk4),
+        }
+    }
+}
+
+/// Position Verlet; velocity is implicit in `pos - pos_last`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Verlet;
+
+impl Integrator for Verlet {
+    fn name(&self) -> &'static str {
+        "position verlet"
+    }
+
+    fn integrate(
+        &self,
+        particles: &mut Particles,
+        dt: f32,
+        global_damping: f32,
+        _forces: &mut Forces,
+    ) {
+        #[cfg(feature = "simd")]
+        crate::simd::integrate(particles, dt, global_damping);
+        #[cfg(not(feature = "simd"))]
+        for i in 0..particles.len() {
+            particles.update(i, dt, global_damping)
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Integrator> {
+        Box::new(*self)
+    }
+
+    fn kind(&self) -> Option<IntegratorKind> {
+        Some(IntegratorKind::Verlet)
+    }
+}
+
+/// Semi-implicit Euler with velocity stored in `Particle::vel`: the acceleration updates the
+/// velocity, which then moves the particle. Position corrections the solvers made since the
+/// last step are folded into the velocity first, as in position-based dynamics, so contacts
+/// and links still stop particles.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SemiImplicitEuler;
+
+impl Integrator for SemiImplicitEuler {
+    fn name(&self) -> &'static str {
+        "semi-implicit euler"
+    }
+
+    fn integrate(
+        &self,
+        particles: &mut Particles,
+        dt: f32,
+        global_damping: f32,
+        _forces: &mut Forces,
+    ) {
+        for i in 0..particles.len() {
+            particles.age[i] += dt;
+            if !particles.moves(i) {
+                hold(particles, i);
+                continue;
+            }
+            let drag = f32::max(1_f32 - (particles.damping[i] + global_damping) * dt, 0_f32);
+            particles.vel[i] =
+                (corrected_velocity(particles, i, dt) + &particles.acc[i] * dt) * drag;
+            particles.pos_last[i] = particles.pos[i].clone();
+            let step = &particles.vel[i] * dt;
+            particles.pos[i] += step;
+            particles.acc[i] = Vec2::zero();
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Integrator> {
+        Box::new(*self)
+    }
+
+    fn kind(&self) -> Option<IntegratorKind> {
+        Some(IntegratorKind::SemiImplicitEuler)
+    }
+}
+
+/// Classic fourth-order Runge-Kutta over position and velocity, sampling forces at three
+/// trial states per step on top of the accumulated one. Much lower energy drift than Verlet
+/// in smooth force-driven scenes such as orbits and springs, at four force evaluations per
+/// step; contacts and constraints still act between steps, as with `SemiImplicitEuler`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Rk4;
+
+impl Integrator for Rk4 {
+    fn name(&self) -> &'static str {
+        "rk4"
+    }
+
+    fn integrate(
+        &self,
+        particles: &mut Particles,
+        dt: f32,
+        global_damping: f32,
+        forces: &mut Forces,
+    ) {
+        let x0 = particles.pos.to_vec();
+        let v0: Vec<Vec2> = (0..particles.len())
+            .map(|i| corrected_velocity(particles, i, dt))
+            .collect();
+        let a0 = particles.acc.to_vec();
+
+        // Accelerations at x0 + v * h, velocity v0 + a * h.
+        let mut sample = |v: &[Vec2], a: &[Vec2], h: f32| -> (Vec<Vec2>, Vec<Vec2>) {
+            let mut trial = particles.clone();
+            let mut velocities = Vec::with_capacity(trial.len());
+            for i in 0..trial.len() {
+                let vel = v0[i].clone() + a[i].clone() * h;
+                trial.pos[i] = x0[i].clone() + v[i].clone() * h;
+                trial.pos_last[i] = &trial.pos[i] - &(&vel * dt);
+                trial.vel[i] = vel.clone();
+                trial.acc[i] = Vec2::zero();
+                velocities.push(vel);
+            }
+            forces(&mut trial);
+            (velocities, trial.acc.to_vec())
+        };
+        let half = 0.5_f32 * dt;
+        let (v1, a1) = sample(&v0, &a0, half);
+        let (v2, a2) = sample(&v1, &a1, half);
+        let (v3, a3) = sample(&v2, &a2, dt);
+
+        let sixth = dt / 6_f32;
+        for i in 0..particles.len() {
+            particles.age[i] += dt;
+            if !particles.moves(i) {
+                hold(particles, i);
+                continue;
+            }
+            let dx = v0[i].clone() + (v1[i].clone() + v2[i].clone()) * 2_f32 + v3[i].clone();
+            let dv = a0[i].clone() + (a1[i].clone() + a2[i].clone()) * 2_f32 + a3[i].clone();
+            let drag = f32::max(1_f32 - (particles.damping[i] + global_damping) * dt, 0_f32);
+            particles.vel[i] = (v0[i].clone() + dv * sixth) * drag;
+            particles.pos[i] = x0[i].clone() + dx * sixth;
+            // Keeps `pos - pos_last` equal to the end velocity, which is what the solvers and
+            // the next step's correction read.
+            particles.pos_last[i] = &particles.pos[i] - &(&particles.vel[i] * dt);
+            particles.acc[i] = Vec2::zero();
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Integrator> {
+        Box::new(*self)
+    }
+
+    fn kind(&self) -> Option<IntegratorKind> {
+        Some(IntegratorKind::Rk4)
+    }
+}
+
+/// The stored velocity of particle `i` plus whatever the solvers moved it off the position it
+/// was integrated to, as a velocity over `dt`.
+fn corrected_velocity(particles: &Particles, i: usize, dt: f32) -> Vec2 {
+    let predicted = &particles.pos_last[i] + &(&particles.vel[i] * dt);
+    particles.vel[i].clone() + (&particles.pos[i] - &predicted) / dt
+}
+
+/// Keeps a pinned, sleeping or dead particle where it is.
+fn hold(particles: &mut Particles, i: usize) {
+    particles.pos_last[i] = particles.pos[i].clone();
+    particles.vel[i] = Vec2::zero();
+    particles.acc[i] = Vec2::zero();
+}
+
+/// Saves a world's integrator as its kind; custom integrators load as `Verlet`.
+#[cfg(feature = "serde")]
+pub(crate) mod persist {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{Integrator, IntegratorKind};
+
+    // Serde hands over a reference to the field itself.
+    #[allow(clippy::borrowed_box)]
+    pub fn serialize<S: Serializer>(
+        integrator: &Box<dyn Integrator>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        integrator.kind().unwrap_or_default().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Box<dyn Integrator>, D::Error> {
+        Ok(IntegratorKind::deserialize(deserializer)?.build())
+    }
+
+    pub fn default() -> Box<dyn Integrator> {
+        IntegratorKind::default().build()
+    }
+}
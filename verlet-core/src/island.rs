@@ -0,0 +1,42 @@
+use std::collections::BTreeMap;
+
+use crate::particle::Particles;
+
+/// Connected groups of particles, joined through `pairs` such as contacts and links. Each
+/// island is sorted by index and the islands are ordered by their first particle. Pinned
+/// particles never move, so they don't connect what rests on them and belong to no island;
+/// neither do dead ones.
+pub fn islands(particles: &Particles, pairs: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let roots = roots(particles, pairs);
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, &root) in roots.iter().enumerate() {
+        if particles.alive[i] && !particles.pinned[i] {
+            groups.entry(root).or_default().push(i);
+        }
+    }
+    // Roots are the lowest index of their island, so this is already in order.
+    groups.into_values().collect()
+}
+
+/// The island of every particle, named by its lowest index. Pinned particles are alone.
+pub(crate) fn roots(particles: &Particles, pairs: &[(usize, usize)]) -> Vec<usize> {
+    let mut parent: Vec<usize> = (0..particles.len()).collect();
+    for &(i, k) in pairs {
+        if particles.pinned[i] || particles.pinned[k] {
+            continue;
+        }
+        let (ri, rk) = (find(&mut parent, i), find(&mut parent, k));
+        if ri != rk {
+            parent[ri.max(rk)] = ri.min(rk);
+        }
+    }
+    (0..particles.len()).map(|i| find(&mut parent, i)).collect()
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
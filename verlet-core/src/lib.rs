@@ -0,0 +1,89 @@
+pub mod attractor;
+pub mod bend;
+pub mod broadphase;
+pub mod builder;
+pub mod ccd;
+pub mod color;
+pub mod constraint;
+pub mod emitter;
+pub mod event;
+pub mod fluid;
+pub mod force_field;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod handle;
+pub mod heat;
+pub mod history;
+pub mod integrator;
+pub mod island;
+pub mod link;
+pub mod merge;
+pub mod morton;
+pub mod nbody;
+pub mod obstacle;
+#[cfg(feature = "parallel")]
+mod parallel;
+pub mod particle;
+pub mod periodic;
+pub mod pin;
+pub mod profile;
+pub mod query;
+pub mod replay;
+pub mod rigid;
+pub mod rotor;
+pub mod scene;
+pub mod schedule;
+pub mod sdf;
+#[cfg(feature = "simd")]
+mod simd;
+pub mod soft_body;
+pub mod solver;
+pub mod sph;
+pub mod spring;
+pub mod tether;
+pub mod timestep;
+pub mod trajectory;
+pub mod user_data;
+pub mod world;
+
+pub use attractor::Attractor;
+pub use bend::Bend;
+pub use broadphase::{Broadphase, BroadphaseKind};
+pub use builder::{WorldBuilder, WorldConfig};
+pub use color::{Color, Gradient};
+pub use constraint::Constraint;
+pub use emitter::Emitter;
+pub use event::Event;
+pub use fluid::FluidZone;
+pub use force_field::{ForceField, ForceFieldKind, ForceFn, Turbulence, Wind};
+#[cfg(feature = "gpu")]
+pub use gpu::GpuSolver;
+pub use handle::ParticleHandle;
+pub use heat::HeatSource;
+pub use history::History;
+pub use integrator::{Integrator, IntegratorKind, Rk4, SemiImplicitEuler, Verlet};
+pub use link::Link;
+pub use merge::Merging;
+pub use nbody::NBody;
+pub use obstacle::Obstacle;
+pub use particle::{Column, Particle, Particles};
+pub use periodic::Periodic;
+pub use pin::Pin;
+pub use profile::StepTimings;
+pub use query::{Hit, HitTarget};
+pub use replay::{Command, Playback, Recorder, Recording};
+pub use rigid::RigidCluster;
+pub use rotor::Rotor;
+pub use scene::{Scene, SceneParticle};
+pub use schedule::{SpawnEvent, SpawnShape};
+pub use sdf::SdfGrid;
+pub use soft_body::SoftBody;
+pub use solver::{Projection, Sleep, Solver};
+pub use sph::SphFluid;
+pub use spring::Spring;
+pub use tether::Tether;
+pub use timestep::{AdaptiveSubsteps, FixedTimestep};
+pub use trajectory::TrajectoryWriter;
+pub use user_data::UserData;
+pub use utils::vec::Vec2;
+pub use world::World;
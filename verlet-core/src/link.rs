@@ -0,0 +1,127 @@
+use utils::vec::Vec2;
+
+use crate::particle::Particles;
+
+/// Keeps particles `a` and `b` at `rest_length` from each other.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Link {
+    pub a: usize,
+    pub b: usize,
+    pub rest_length: f32,
+    /// Relative stretch, `(length - rest_length) / rest_length`, beyond which the link breaks.
+    pub break_threshold: Option<f32>,
+    /// Stretch per unit of force under `Projection::Xpbd`; zero is rigid. Relaxation ignores
+    /// it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub compliance: f32,
+}
+
+impl Link {
+    pub fn new(a: usize, b: usize, rest_length: f32) -> Self {
+        Link {
+            a,
+            b,
+            rest_length,
+            break_threshold: None,
+            compliance: 0_f32,
+        }
+    }
+
+    pub fn breakable(mut self, threshold: f32) -> Self {
+        self.break_threshold = Some(threshold);
+        self
+    }
+
+    pub fn compliant(mut self, compliance: f32) -> Self {
+        self.compliance = compliance;
+        self
+    }
+
+    pub fn is_broken(&self, particles: &Particles) -> bool {
+        overstretched(
+            particles,
+            self.a,
+            self.b,
+            self.rest_length,
+            self.break_threshold,
+        )
+    }
+
+    /// Link whose rest length is the current distance between the two particles.
+    pub fn between(particles: &Particles, a: usize, b: usize) -> Self {
+        let rest_length = (particles.pos[a].clone() - particles.pos[b].clone()).len();
+        Link::new(a, b, rest_length)
+    }
+
+    /// The linked pair as `(lo, hi)` indices.
+    pub fn key(&self) -> (usize, usize) {
+        (self.a.min(self.b), self.a.max(self.b))
+    }
+
+    pub fn solve(&self, particles: &mut Particles) {
+        let Some((n, delta)) = self.stretch(particles) else {
+            return;
+        };
+        let (inv_a, inv_b) = (particles.inv_mass(self.a), particles.inv_mass(self.b));
+        let inv_mass = inv_a + inv_b;
+        if inv_mass == 0_f32 {
+            return;
+        }
+        let ratio_a = inv_a / inv_mass;
+        let ratio_b = inv_b / inv_mass;
+
+        particles.pos[self.a] -= n.clone() * (ratio_a * delta);
+        particles.pos[self.b] += n * (ratio_b * delta);
+    }
+
+    /// XPBD projection in a substep of `dt`. `lambda` is the force impulse applied so far in
+    /// this substep and starts at zero.
+    pub fn solve_xpbd(&self, particles: &mut Particles, dt: f32, lambda: &mut f32) {
+        let Some((n, delta)) = self.stretch(particles) else {
+            return;
+        };
+        let compliance = self.compliance / (dt * dt);
+        let (inv_a, inv_b) = (particles.inv_mass(self.a), particles.inv_mass(self.b));
+        let inv_mass = inv_a + inv_b;
+        if inv_mass + compliance == 0_f32 {
+            return;
+        }
+        let d_lambda = -(delta + compliance * *lambda) / (inv_mass + compliance);
+        *lambda += d_lambda;
+
+        particles.pos[self.a] += n.clone() * (inv_a * d_lambda);
+        particles.pos[self.b] -= n * (inv_b * d_lambda);
+    }
+
+    /// The direction from `b` to `a` and how far the link is past its rest length; `None` if
+    /// the link is degenerate.
+    fn stretch(&self, particles: &Particles) -> Option<(Vec2, f32)> {
+        if self.a == self.b {
+            return None;
+        }
+        let v = &particles.pos[self.a] - &particles.pos[self.b];
+        let dist = v.len();
+        if dist <= f32::EPSILON {
+            return None;
+        }
+        let delta = dist - self.rest_length;
+        Some((v / dist, delta))
+    }
+}
+
+pub(crate) fn overstretched(
+    particles: &Particles,
+    a: usize,
+    b: usize,
+    rest_length: f32,
+    threshold: Option<f32>,
+) -> bool {
+    match threshold {
+        Some(t) if rest_length > 0_f32 => {
+            let dist = (particles.pos[a].clone() - particles.pos[b].clone()).len();
+            (dist - rest_length) / rest_length > t
+        }
+        _ => false,
+    }
+}
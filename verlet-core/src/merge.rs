@@ -0,0 +1,95 @@
+use std::f32::consts::{PI, TAU};
+
+use utils::vec::Vec2;
+
+use crate::particle::{Particle, Particles};
+use crate::solver::COLLISION_MARGIN;
+
+/// Merges pairs of touching particles that rest against each other long enough into one larger
+/// particle, like droplets running together. Pinned particles, SPH fluid members and particles
+/// held by links, springs, bends, pins, tethers or soft bodies never merge.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Merging {
+    /// Relative speed, in units per second, below which a touching pair counts as resting.
+    pub max_speed: f32,
+    /// Seconds a pair has to rest together before it merges.
+    pub contact_time: f32,
+    /// Pairs whose merged radius would exceed this stay apart.
+    pub max_radius: f32,
+}
+
+impl Default for Merging {
+    fn default() -> Self {
+        Merging {
+            max_speed: 30_f32,
+            contact_time: 0.5_f32,
+            max_radius: 60_f32,
+        }
+    }
+}
+
+/// Whether particles `a` and `b` are touching and moving slower than `max_speed` relative to
+/// each other, with velocities taken over the last substep of `dt`.
+pub(crate) fn resting(particles: &Particles, a: usize, b: usize, max_speed: f32, dt: f32) -> bool {
+    let reach = particles.radius[a] + particles.radius[b] + 2_f32 * COLLISION_MARGIN;
+    let offset = &particles.pos[a] - &particles.pos[b];
+    let v_rel = particles.velocity(a, dt) - particles.velocity(b, dt);
+    offset.dot(&offset) < reach * reach && v_rel.len() < max_speed
+}
+
+/// Radius of a disc with the combined area of particles `a` and `b`.
+pub(crate) fn merged_radius(particles: &Particles, a: usize, b: usize) -> f32 {
+    let (r_a, r_b) = (particles.radius[a], particles.radius[b]);
+    (r_a * r_a + r_b * r_b).sqrt()
+}
+
+/// One particle with the combined mass, area, momentum and heat of `a` and `b`, at their center
+/// of mass. Everything else is taken from the heavier one.
+pub fn merge(a: &Particle, b: &Particle) -> Particle {
+    let (heavy, light) = if a.mass >= b.mass { (a, b) } else { (b, a) };
+    let mass = a.mass + b.mass;
+    let weigh = |x: Vec2, y: Vec2| (x * a.mass + y * b.mass) / mass;
+    let pos = weigh(a.pos.clone(), b.pos.clone());
+    let step = weigh(
+        a.pos.clone() - a.pos_last.clone(),
+        b.pos.clone() - b.pos_last.clone(),
+    );
+
+    let mut merged = heavy.clone();
+    merged.pos_last = pos.clone() - step;
+    merged.pos = pos;
+    merged.vel = weigh(a.vel.clone(), b.vel.clone());
+    merged.acc = Vec2::zero();
+    merged.mass = mass;
+    merged.radius = (a.radius * a.radius + b.radius * b.radius).sqrt();
+    merged.temperature = (a.mass * a.temperature + b.mass * b.temperature) / mass;
+    merged.age = heavy.age.min(light.age);
+    merged.wake();
+    merged
+}
+
+/// Splits `p` into `count` particles of equal mass and area, on a ring around its position
+/// where they just touch, all moving with its velocity.
+pub fn split(p: &Particle, count: usize) -> Vec<Particle> {
+    if count <= 1 {
+        return vec![p.clone()];
+    }
+    let n = count as f32;
+    let radius = p.radius / n.sqrt();
+    let ring = (radius + 0.5_f32 * COLLISION_MARGIN) / (PI / n).sin();
+    let step = p.pos.clone() - p.pos_last.clone();
+    (0..count)
+        .map(|i| {
+            let angle = TAU * i as f32 / n;
+            let offset = Vec2::new(angle.cos(), angle.sin()) * ring;
+            let mut piece = p.clone();
+            piece.pos = p.pos.clone() + offset;
+            piece.pos_last = piece.pos.clone() - step.clone();
+            piece.radius = radius;
+            piece.mass = p.mass / n;
+            piece.wake();
+            piece
+        })
+        .collect()
+}
@@ -0,0 +1,34 @@
+use crate::particle::Particles;
+
+/// Position of cell `(x, y)` along the Z-order curve: the bits of both interleaved, so cells
+/// close in space mostly end up close in the order.
+pub fn code(x: u32, y: u32) -> u64 {
+    spread(x) | spread(y) << 1
+}
+
+/// Spaces out the bits of `v` to every other bit.
+fn spread(v: u32) -> u64 {
+    let mut v = v as u64;
+    v = (v | v << 16) & 0x0000_ffff_0000_ffff;
+    v = (v | v << 8) & 0x00ff_00ff_00ff_00ff;
+    v = (v | v << 4) & 0x0f0f_0f0f_0f0f_0f0f;
+    v = (v | v << 2) & 0x3333_3333_3333_3333;
+    (v | v << 1) & 0x5555_5555_5555_5555
+}
+
+/// Particle indices sorted along the Z-order curve over square cells of size `cell`.
+pub fn order(particles: &Particles, cell: f32) -> Vec<usize> {
+    let (min_x, min_y) = particles
+        .pos
+        .iter()
+        .fold((f32::MAX, f32::MAX), |(x, y), p| {
+            (f32::min(x, p.x), f32::min(y, p.y))
+        });
+    let mut order: Vec<usize> = (0..particles.len()).collect();
+    order.sort_by_cached_key(|&i| {
+        let p = &particles.pos[i];
+        // Casts saturate, so far outliers share the last cell instead of wrapping around.
+        code(((p.x - min_x) / cell) as u32, ((p.y - min_y) / cell) as u32)
+    });
+    order
+}
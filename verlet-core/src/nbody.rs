@@ -0,0 +1,187 @@
+use utils::vec::Vec2;
+
+use crate::particle::Particles;
+
+const NODE_CAPACITY: usize = 8;
+const MAX_DEPTH: usize = 16;
+
+/// Mutual gravitation: every particle accelerates towards every other with
+/// `strength * mass / r²`. Distant groups are lumped together with a Barnes-Hut quadtree, so a
+/// step costs `O(n log n)` instead of `O(n²)`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NBody {
+    pub strength: f32,
+    /// Opening angle: a node whose size is less than `theta` times its distance counts as a
+    /// single mass. `0` is exact, `0.5` is typically within a few percent.
+    pub theta: f32,
+    /// Softening length, added in quadrature to every distance so close encounters stay
+    /// finite.
+    pub softening: f32,
+}
+
+impl NBody {
+    pub fn new(strength: f32) -> Self {
+        NBody {
+            strength,
+            theta: 0.5_f32,
+            softening: 5_f32,
+        }
+    }
+
+    pub fn apply(&self, particles: &mut Particles) {
+        if particles.len() < 2 {
+            return;
+        }
+        let tree = MassTree::build(particles);
+        let acc: Vec<Vec2> = (0..particles.len())
+            .map(|i| tree.acceleration(particles, i, self))
+            .collect();
+        for (i, a) in acc.into_iter().enumerate() {
+            particles.accelerate(i, a);
+        }
+    }
+
+    /// Pull at `pos` from a point mass `mass` at `at`.
+    fn pull(&self, pos: &Vec2, at: &Vec2, mass: f32) -> Vec2 {
+        let offset = at.clone() - pos.clone();
+        let d2 = offset.dot(&offset) + self.softening * self.softening;
+        if d2 == 0_f32 {
+            return Vec2::zero();
+        }
+        offset * (self.strength * mass / (d2 * d2.sqrt()))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    center: Vec2,
+    half: f32,
+    mass: f32,
+    /// Mass-weighted mean position of everything below this node.
+    com: Vec2,
+    /// First of four consecutive children.
+    children: Option<usize>,
+    items: Vec<usize>,
+}
+
+impl Node {
+    fn new(center: Vec2, half: f32) -> Self {
+        Node {
+            center,
+            half,
+            mass: 0_f32,
+            com: Vec2::zero(),
+            children: None,
+            items: Vec::new(),
+        }
+    }
+
+    fn quadrant(&self, pos: &Vec2) -> usize {
+        usize::from(pos.x >= self.center.x) + 2 * usize::from(pos.y >= self.center.y)
+    }
+}
+
+/// Point quadtree over particle centers, each node carrying the total mass below it.
+struct MassTree {
+    nodes: Vec<Node>,
+}
+
+impl MassTree {
+    fn build(particles: &Particles) -> Self {
+        let (mut min, mut max) = (particles.pos[0].clone(), particles.pos[0].clone());
+        for p in particles.pos.iter() {
+            min = Vec2::new(min.x.min(p.x), min.y.min(p.y));
+            max = Vec2::new(max.x.max(p.x), max.y.max(p.y));
+        }
+        let half = 0.5_f32 * f32::max(max.x - min.x, max.y - min.y) + 1_f32;
+        let mut tree = MassTree {
+            nodes: vec![Node::new((min + max) / 2_f32, half)],
+        };
+        for i in (0..particles.len()).filter(|&i| particles.alive[i]) {
+            tree.insert(particles, 0, i, 0);
+        }
+
+        // Children always come after their parent, so a reverse pass sees them first.
+        for n in (0..tree.nodes.len()).rev() {
+            let (mass, weighted) = match tree.nodes[n].children {
+                Some(c) => tree.nodes[c..c + 4]
+                    .iter()
+                    .fold((0_f32, Vec2::zero()), |acc, n| {
+                        (acc.0 + n.mass, acc.1 + n.com.clone() * n.mass)
+                    }),
+                None => tree.nodes[n]
+                    .items
+                    .iter()
+                    .fold((0_f32, Vec2::zero()), |acc, &i| {
+                        let mass = particles.mass[i];
+                        (acc.0 + mass, acc.1 + particles.pos[i].clone() * mass)
+                    }),
+            };
+            let node = &mut tree.nodes[n];
+            node.mass = mass;
+            if mass > 0_f32 {
+                node.com = weighted / mass;
+            }
+        }
+        tree
+    }
+
+    fn insert(&mut self, particles: &Particles, node: usize, item: usize, depth: usize) {
+        if let Some(c) = self.nodes[node].children {
+            let child = c + self.nodes[node].quadrant(&particles.pos[item]);
+            self.insert(particles, child, item, depth + 1);
+            return;
+        }
+
+        self.nodes[node].items.push(item);
+        if self.nodes[node].items.len() > NODE_CAPACITY && depth < MAX_DEPTH {
+            self.split(node);
+            for i in std::mem::take(&mut self.nodes[node].items) {
+                self.insert(particles, node, i, depth);
+            }
+        }
+    }
+
+    fn split(&mut self, node: usize) {
+        let center = self.nodes[node].center.clone();
+        let half = 0.5_f32 * self.nodes[node].half;
+        let first = self.nodes.len();
+        for q in 0..4 {
+            let dx = if q % 2 == 1 { half } else { -half };
+            let dy = if q / 2 == 1 { half } else { -half };
+            let child = Vec2::new(center.x + dx, center.y + dy);
+            self.nodes.push(Node::new(child, half));
+        }
+        self.nodes[node].children = Some(first);
+    }
+
+    fn acceleration(&self, particles: &Particles, i: usize, nbody: &NBody) -> Vec2 {
+        let pos = &particles.pos[i];
+        let mut acc = Vec2::zero();
+        let mut stack = vec![0];
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if node.mass <= 0_f32 {
+                continue;
+            }
+            match node.children {
+                Some(c) => {
+                    let offset = node.com.clone() - pos.clone();
+                    let size = 2_f32 * node.half;
+                    if size * size < nbody.theta * nbody.theta * offset.dot(&offset) {
+                        acc += nbody.pull(pos, &node.com, node.mass);
+                    } else {
+                        stack.extend(c..c + 4);
+                    }
+                }
+                None => {
+                    for &j in node.items.iter().filter(|&&j| j != i) {
+                        acc += nbody.pull(pos, &particles.pos[j], particles.mass[j]);
+                    }
+                }
+            }
+        }
+        acc
+    }
+}
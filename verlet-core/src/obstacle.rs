@@ -0,0 +1,335 @@
+use utils::vec::Vec2;
+
+use crate::constraint::resolve_wall;
+use crate::particle::Particles;
+use crate::query::{ray_circle, ray_segment};
+use crate::sdf::SdfGrid;
+
+/// Static collider that particles bounce off but that never moves.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Obstacle {
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+    Segment {
+        a: Vec2,
+        b: Vec2,
+        /// See `Polyline::friction`.
+        #[cfg_attr(feature = "serde", serde(default))]
+        friction: f32,
+        /// Speed at which the surface runs from `a` towards `b`, like a conveyor belt. It
+        /// drags resting particles along through `friction`.
+        #[cfg_attr(feature = "serde", serde(default))]
+        surface_velocity: f32,
+    },
+    Capsule {
+        a: Vec2,
+        b: Vec2,
+        radius: f32,
+        /// See `Polyline::friction`.
+        #[cfg_attr(feature = "serde", serde(default))]
+        friction: f32,
+        /// See `Segment::surface_velocity`.
+        #[cfg_attr(feature = "serde", serde(default))]
+        surface_velocity: f32,
+    },
+    /// Arbitrary shape given by a sampled signed distance field, e.g. an image mask.
+    Sdf {
+        field: SdfGrid,
+    },
+    /// Open chain of segments, e.g. terrain. `friction` limits sliding along the surface the
+    /// way `Particle::kinetic_friction` does between particles.
+    Polyline {
+        points: Vec<Vec2>,
+        friction: f32,
+    },
+}
+
+impl Obstacle {
+    pub fn circle(center: Vec2, radius: f32) -> Self {
+        Obstacle::Circle { center, radius }
+    }
+
+    pub fn segment(a: Vec2, b: Vec2) -> Self {
+        Obstacle::Segment {
+            a,
+            b,
+            friction: 0_f32,
+            surface_velocity: 0_f32,
+        }
+    }
+
+    /// Segment whose surface runs from `a` towards `b` at `speed`, with enough friction to
+    /// carry particles.
+    pub fn conveyor(a: Vec2, b: Vec2, speed: f32) -> Self {
+        Obstacle::Segment {
+            a,
+            b,
+            friction: 1_f32,
+            surface_velocity: speed,
+        }
+    }
+
+    pub fn capsule(a: Vec2, b: Vec2, radius: f32) -> Self {
+        Obstacle::Capsule {
+            a,
+            b,
+            radius,
+            friction: 0_f32,
+            surface_velocity: 0_f32,
+        }
+    }
+
+    pub fn sdf(field: SdfGrid) -> Self {
+        Obstacle::Sdf { field }
+    }
+
+    pub fn polyline(points: Vec<Vec2>, friction: f32) -> Self {
+        Obstacle::Polyline { points, friction }
+    }
+
+    /// The polyline's segments in order, or the segment or capsule itself.
+    pub fn segments(&self) -> Vec<(Vec2, Vec2)> {
+        match self {
+            Obstacle::Segment { a, b, .. } | Obstacle::Capsule { a, b, .. } => {
+                vec![(a.clone(), b.clone())]
+            }
+            Obstacle::Polyline { points, .. } => points
+                .windows(2)
+                .map(|w| (w[0].clone(), w[1].clone()))
+                .collect(),
+            Obstacle::Circle { .. } | Obstacle::Sdf { .. } => Vec::new(),
+        }
+    }
+
+    /// Half the width of the segments' surface: the capsule radius, `0` for bare segments.
+    pub fn thickness(&self) -> f32 {
+        match self {
+            Obstacle::Capsule { radius, .. } => *radius,
+            _ => 0_f32,
+        }
+    }
+
+    /// Pushes particle `i` out of the obstacle; `dt` is the step length, for moving surfaces.
+    pub fn apply_to(&self, particles: &mut Particles, i: usize, dt: f32) {
+        match self {
+            Obstacle::Circle { center, radius } => {
+                let min_dist = particles.radius[i] + radius;
+                let v = particles.pos[i].clone() - center.clone();
+                let dist = v.len();
+                if dist < min_dist {
+                    let n = if dist > f32::EPSILON {
+                        v / dist
+                    } else {
+                        Vec2::new(0_f32, 1_f32)
+                    };
+                    let pos = center.clone() + n.clone() * min_dist;
+                    resolve_wall(particles, i, pos, &n);
+                }
+            }
+            Obstacle::Segment {
+                a,
+                b,
+                friction,
+                surface_velocity,
+            } => {
+                let surface = belt(a, b, *surface_velocity * dt);
+                collide_segments(
+                    particles,
+                    i,
+                    &[a.clone(), b.clone()],
+                    0_f32,
+                    *friction,
+                    &surface,
+                )
+            }
+            Obstacle::Capsule {
+                a,
+                b,
+                radius,
+                friction,
+                surface_velocity,
+            } => {
+                let surface = belt(a, b, *surface_velocity * dt);
+                collide_segments(
+                    particles,
+                    i,
+                    &[a.clone(), b.clone()],
+                    *radius,
+                    *friction,
+                    &surface,
+                )
+            }
+            Obstacle::Polyline { points, friction } => {
+                collide_segments(particles, i, points, 0_f32, *friction, &Vec2::zero())
+            }
+            Obstacle::Sdf { field } => {
+                let depth = particles.radius[i] - field.distance(&particles.pos[i]);
+                if depth > 0_f32 {
+                    if let Some(n) = field.gradient(&particles.pos[i]) {
+                        let pos = particles.pos[i].clone() + n.clone() * depth;
+                        resolve_wall(particles, i, pos, &n);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn apply(&self, particles: &mut Particles, dt: f32) {
+        for i in 0..particles.len() {
+            if particles.alive[i] && !particles.pinned[i] {
+                self.apply_to(particles, i, dt);
+            }
+        }
+    }
+
+    /// Distance along the unit ray to the obstacle's surface, and the surface normal there.
+    pub fn raycast(&self, origin: &Vec2, dir: &Vec2) -> Option<(f32, Vec2)> {
+        match self {
+            Obstacle::Circle { center, radius } => circle_hit(origin, dir, center, *radius),
+            Obstacle::Segment { a, b, .. } => {
+                let t = ray_segment(origin, dir, a, b)?;
+                let mut n = (b.clone() - a.clone()).perp();
+                n.normalize();
+                if n.dot(dir) > 0_f32 {
+                    n = n * -1_f32;
+                }
+                Some((t, n))
+            }
+            Obstacle::Capsule { a, b, radius, .. } => {
+                let mut offset = (b.clone() - a.clone()).perp();
+                offset.normalize();
+                let sides = [offset.clone() * *radius, offset * -*radius];
+                let side_hits = sides.into_iter().filter_map(|o| {
+                    let n = o.clone() / *radius;
+                    let t = ray_segment(origin, dir, &(a.clone() + o.clone()), &(b.clone() + o))?;
+                    (n.dot(dir) < 0_f32).then_some((t, n))
+                });
+                let cap_hits = [a, b]
+                    .into_iter()
+                    .filter_map(|c| circle_hit(origin, dir, c, *radius));
+                side_hits.chain(cap_hits).min_by(|x, y| x.0.total_cmp(&y.0))
+            }
+            Obstacle::Polyline { points, .. } => points
+                .windows(2)
+                .filter_map(|w| Obstacle::segment(w[0].clone(), w[1].clone()).raycast(origin, dir))
+                .min_by(|x, y| x.0.total_cmp(&y.0)),
+            Obstacle::Sdf { field } => {
+                let reach = (origin.clone() - field.center()).len() + field.extent();
+                let t = field.march(origin, dir, 0_f32, reach)?;
+                let n = field.gradient(&(origin.clone() + dir.clone() * t))?;
+                Some((t, n))
+            }
+        }
+    }
+}
+
+pub fn closest_on_segment(a: &Vec2, b: &Vec2, p: &Vec2) -> Vec2 {
+    let ab = b.clone() - a.clone();
+    let len2 = ab.dot(&ab);
+    if len2 <= f32::EPSILON {
+        return a.clone();
+    }
+    let t = f32::clamp((p.clone() - a.clone()).dot(&ab) / len2, 0_f32, 1_f32);
+    a.clone() + ab * t
+}
+
+/// Overlap between a circle of `radius` at `p` and the segment `a`-`b` thickened by
+/// `thickness`, with the unit normal pushing the circle out, or `None` if they don't touch.
+/// Near an end the normal points away from the end point, so chains of segments have
+/// rounded rather than sharp convex corners.
+pub fn segment_contact(
+    a: &Vec2,
+    b: &Vec2,
+    thickness: f32,
+    p: &Vec2,
+    radius: f32,
+) -> Option<(Vec2, f32)> {
+    let q = closest_on_segment(a, b, p);
+    let v = p.clone() - q;
+    let dist = v.len();
+    let depth = radius + thickness - dist;
+    if depth <= 0_f32 {
+        return None;
+    }
+    let n = if dist > f32::EPSILON {
+        v / dist
+    } else {
+        let mut n = (b.clone() - a.clone()).perp();
+        n.normalize();
+        n
+    };
+    Some((n, depth))
+}
+
+/// Pushes particle `i` out of the chain of segments through `points`, taking the deepest contact
+/// first. A particle in a valley can touch two segments at once; the second pass pushes it off
+/// whichever one the first left it overlapping. `surface` is how far the surface moves this step.
+fn collide_segments(
+    particles: &mut Particles,
+    i: usize,
+    points: &[Vec2],
+    thickness: f32,
+    friction: f32,
+    surface: &Vec2,
+) {
+    for _ in 0..2 {
+        let deepest = points
+            .windows(2)
+            .filter_map(|w| {
+                segment_contact(
+                    &w[0],
+                    &w[1],
+                    thickness,
+                    &particles.pos[i],
+                    particles.radius[i],
+                )
+            })
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        let Some((n, depth)) = deepest else {
+            return;
+        };
+        let pos = particles.pos[i].clone() + n.clone() * depth;
+        resolve_wall(particles, i, pos, &n);
+        apply_friction(particles, i, &n, depth, friction, surface);
+    }
+}
+
+/// Removes sliding along a surface with normal `n` that particle `i` overlapped by `depth`,
+/// relative to the surface's own motion `surface`, up to `friction * depth` per step.
+fn apply_friction(
+    particles: &mut Particles,
+    i: usize,
+    n: &Vec2,
+    depth: f32,
+    friction: f32,
+    surface: &Vec2,
+) {
+    if friction <= 0_f32 {
+        return;
+    }
+    let vel = particles.pos[i].clone() - particles.pos_last[i].clone() - surface.clone();
+    let v_t = vel.clone() - n.clone() * vel.dot(n);
+    let slip = v_t.len();
+    if slip > 0_f32 {
+        particles.pos_last[i] += v_t * f32::min(friction * depth / slip, 1_f32);
+    }
+}
+
+/// Displacement of `distance` along the segment from `a` towards `b`.
+fn belt(a: &Vec2, b: &Vec2, distance: f32) -> Vec2 {
+    let mut dir = b.clone() - a.clone();
+    if distance == 0_f32 || dir.len() <= f32::EPSILON {
+        return Vec2::zero();
+    }
+    dir.normalize();
+    dir * distance
+}
+
+fn circle_hit(origin: &Vec2, dir: &Vec2, center: &Vec2, radius: f32) -> Option<(f32, Vec2)> {
+    let t = ray_circle(origin, dir, center, radius)?;
+    let point = origin.clone() + dir.clone() * t;
+    Some((t, (point - center.clone()) / radius))
+}
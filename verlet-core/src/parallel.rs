@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+
+use rayon::prelude::*;
+
+use utils::vec::Vec2;
+
+use crate::particle::Particles;
+use crate::solver::{resolve_pair, Body};
+
+/// The columns a contact touches, taken once on the calling thread.
+struct SharedParticles {
+    pos: *mut Vec2,
+    pos_last: *mut Vec2,
+    radius: *const f32,
+    mass: *const f32,
+    static_friction: *const f32,
+    kinetic_friction: *const f32,
+    temperature: *mut f32,
+    pinned: *const bool,
+    asleep: *mut bool,
+    still_steps: *mut u32,
+}
+
+// SAFETY: only used to hand out disjoint particles to the stripe and island workers below.
+unsafe impl Send for SharedParticles {}
+unsafe impl Sync for SharedParticles {}
+
+impl SharedParticles {
+    fn new(particles: &mut Particles) -> Self {
+        SharedParticles {
+            pos: particles.pos.as_mut_ptr(),
+            pos_last: particles.pos_last.as_mut_ptr(),
+            radius: particles.radius.as_ptr(),
+            mass: particles.mass.as_ptr(),
+            static_friction: particles.static_friction.as_ptr(),
+            kinetic_friction: particles.kinetic_friction.as_ptr(),
+            temperature: particles.temperature.as_mut_ptr(),
+            pinned: particles.pinned.as_ptr(),
+            asleep: particles.asleep.as_mut_ptr(),
+            still_steps: particles.still_steps.as_mut_ptr(),
+        }
+    }
+
+    /// Same as `Body::load`.
+    ///
+    /// # Safety
+    ///
+    /// `i` is in bounds and no other worker touches particle `i` meanwhile.
+    unsafe fn load(&self, i: usize) -> Body {
+        Body {
+            pos: (*self.pos.add(i)).clone(),
+            pos_last: (*self.pos_last.add(i)).clone(),
+            radius: *self.radius.add(i),
+            mass: *self.mass.add(i),
+            static_friction: *self.static_friction.add(i),
+            kinetic_friction: *self.kinetic_friction.add(i),
+            temperature: *self.temperature.add(i),
+            pinned: *self.pinned.add(i),
+            asleep: *self.asleep.add(i),
+            still_steps: *self.still_steps.add(i),
+        }
+    }
+
+    /// Same as `Body::store`, with the requirements of `load`.
+    unsafe fn store(&self, i: usize, body: Body) {
+        *self.pos.add(i) = body.pos;
+        *self.pos_last.add(i) = body.pos_last;
+        *self.temperature.add(i) = body.temperature;
+        *self.asleep.add(i) = body.asleep;
+        *self.still_steps.add(i) = body.still_steps;
+    }
+
+    /// Runs `resolve` on pair `(i, k)`, with the requirements of `load` for both.
+    unsafe fn resolve<F>(&self, i: usize, k: usize, resolve: &F) -> f32
+    where
+        F: Fn(&mut Body, &mut Body) -> f32,
+    {
+        let (mut a, mut b) = (self.load(i), self.load(k));
+        let result = resolve(&mut a, &mut b);
+        self.store(i, a);
+        self.store(k, b);
+        result
+    }
+}
+
+/// Resolves contact pairs in vertical stripes at least `stripe_width` wide. A pair belongs to
+/// the lower of its two (adjacent) stripes; all even stripes run in parallel, then all odd ones,
+/// so no two workers ever touch the same particle. Pairs spanning more than two stripes are
+/// resolved serially afterwards. Returns the pairs for which `resolve` gave a non-zero result,
+/// with that result.
+pub(crate) fn solve_striped<F>(
+    particles: &mut Particles,
+    pairs: Vec<(usize, usize)>,
+    stripe_width: f32,
+    resolve: F,
+) -> Vec<(usize, usize, f32)>
+where
+    F: Fn(&mut Body, &mut Body) -> f32 + Sync,
+{
+    let stripes: Vec<i64> = particles
+        .pos
+        .iter()
+        .map(|pos| f32::floor(pos.x / stripe_width) as i64)
+        .collect();
+
+    let mut buckets: BTreeMap<i64, Vec<(usize, usize)>> = BTreeMap::new();
+    let mut leftover = Vec::new();
+    for (i, k) in pairs {
+        let (si, sk) = (stripes[i], stripes[k]);
+        if (si - sk).abs() <= 1 {
+            buckets.entry(si.min(sk)).or_default().push((i, k));
+        } else {
+            leftover.push((i, k));
+        }
+    }
+
+    let shared = SharedParticles::new(particles);
+    let mut results = Vec::new();
+    for parity in 0..2 {
+        let batch: Vec<&Vec<(usize, usize)>> = buckets
+            .iter()
+            .filter(|(s, _)| s.rem_euclid(2) == parity)
+            .map(|(_, v)| v)
+            .collect();
+
+        let found: Vec<Vec<(usize, usize, f32)>> = batch
+            .par_iter()
+            .map(|pairs| {
+                let mut found = Vec::new();
+                for &(i, k) in pairs.iter() {
+                    // SAFETY: `i != k`, both are in bounds, and every particle in this bucket
+                    // lies in stripe `s` or `s + 1`, which no other bucket of the same parity
+                    // touches.
+                    let result = unsafe { shared.resolve(i, k, &resolve) };
+                    push_result(&mut found, i, k, result);
+                }
+                found
+            })
+            .collect();
+        results.extend(found.into_iter().flatten());
+    }
+
+    for (i, k) in leftover {
+        let result = resolve_pair(particles, i, k, &resolve);
+        push_result(&mut results, i, k, result);
+    }
+    results
+}
+
+/// Resolves contact pairs one island at a time, with islands spread over the workers. Islands
+/// share no particles, so they need no ordering; pairs touching a pinned particle, which may
+/// hold up several islands, are resolved serially afterwards. Returns the same as
+/// `solve_striped`.
+pub(crate) fn solve_islands<F>(
+    particles: &mut Particles,
+    pairs: Vec<(usize, usize)>,
+    resolve: F,
+) -> Vec<(usize, usize, f32)>
+where
+    F: Fn(&mut Body, &mut Body) -> f32 + Sync,
+{
+    let roots = crate::island::roots(particles, &pairs);
+
+    let mut buckets: BTreeMap<usize, Vec<(usize, usize)>> = BTreeMap::new();
+    let mut leftover = Vec::new();
+    for (i, k) in pairs {
+        if particles.pinned[i] || particles.pinned[k] {
+            leftover.push((i, k));
+        } else {
+            buckets.entry(roots[i]).or_default().push((i, k));
+        }
+    }
+
+    let shared = SharedParticles::new(particles);
+    let batch: Vec<&Vec<(usize, usize)>> = buckets.values().collect();
+    let found: Vec<Vec<(usize, usize, f32)>> = batch
+        .par_iter()
+        .map(|pairs| {
+            let mut found = Vec::new();
+            for &(i, k) in pairs.iter() {
+                // SAFETY: `i != k`, both are in bounds, and both belong to this bucket's
+                // island, which no other bucket touches.
+                let result = unsafe { shared.resolve(i, k, &resolve) };
+                push_result(&mut found, i, k, result);
+            }
+            found
+        })
+        .collect();
+    let mut results: Vec<(usize, usize, f32)> = found.into_iter().flatten().collect();
+
+    for (i, k) in leftover {
+        let result = resolve_pair(particles, i, k, &resolve);
+        push_result(&mut results, i, k, result);
+    }
+    results
+}
+
+fn push_result(results: &mut Vec<(usize, usize, f32)>, i: usize, k: usize, result: f32) {
+    if result != 0_f32 {
+        results.push((i, k, result));
+    }
+}
@@ -0,0 +1,439 @@
+use std::ops::{Deref, DerefMut};
+
+use utils::vec::Vec2;
+
+use crate::color::{self, Color};
+
+/// One particle's fields, to build particles from and to copy them out of `Particles`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Particle {
+    pub pos: Vec2,
+    pub pos_last: Vec2,
+    pub acc: Vec2,
+    /// Velocity over the last step, kept by integrators that store it explicitly such as
+    /// `SemiImplicitEuler`; position Verlet leaves it implicit in `pos - pos_last`.
+    #[cfg_attr(feature = "serde", serde(default = "Vec2::zero"))]
+    pub vel: Vec2,
+    pub radius: f32,
+    pub mass: f32,
+    /// Multiplies the pull of the world's gravity or attractor on this particle: `0` floats
+    /// like dust, negative values rise like balloons.
+    #[cfg_attr(feature = "serde", serde(default = "default_gravity_scale"))]
+    pub gravity_scale: f32,
+    /// Fraction of normal velocity kept after a collision, `0` is dead and `1` fully elastic.
+    pub restitution: f32,
+    /// Friction coefficients; tangential slip below `static_friction * penetration` is cancelled
+    /// outright, above it `kinetic_friction` scales how much is removed.
+    pub static_friction: f32,
+    pub kinetic_friction: f32,
+    /// Linear drag per second, added to the world's global damping.
+    pub damping: f32,
+    /// Bitmask of the world constraints confining this particle; the particle may move
+    /// anywhere in the union of the selected regions.
+    pub regions: u32,
+    /// Collision layers this particle belongs to, and the layers it collides with. Two
+    /// particles collide only if each one's `layer` overlaps the other's `mask`.
+    pub layer: u32,
+    pub mask: u32,
+    /// Pinned particles never move: the integrator skips them and solvers treat them as
+    /// having infinite mass.
+    pub pinned: bool,
+    /// Sleeping particles are skipped by the integrator and act as immovable until something
+    /// hits them hard enough to wake them up.
+    pub asleep: bool,
+    /// Consecutive steps the particle moved less than the sleep threshold.
+    pub still_steps: u32,
+    /// Seconds since the particle was spawned.
+    pub age: f32,
+    /// Seconds after which the particle despawns; `None` lives forever.
+    pub lifetime: Option<f32>,
+    /// Exchanged with touching particles, see `Solver::conductivity`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub temperature: f32,
+    /// Index of the `World::sph_fluids` entry this particle belongs to; `None` is granular.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sph_fluid: Option<usize>,
+    pub color: Color,
+    /// Free for the application, e.g. an entity id or an index into its own table. The world
+    /// only copies it along: emitted particles take their emitter's `user_data`, and merged
+    /// and split particles keep that of the heavier or original one. Data of any other type
+    /// goes through `World::set_user_data`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub user_data: u64,
+    /// `false` for a free slot of a pooled world, see `World::pool`. Dead particles collide
+    /// with nothing, don't move and are skipped by queries until a new particle takes the slot.
+    #[cfg_attr(feature = "serde", serde(default = "default_alive"))]
+    pub alive: bool,
+}
+
+#[cfg(feature = "serde")]
+fn default_gravity_scale() -> f32 {
+    1_f32
+}
+
+#[cfg(feature = "serde")]
+fn default_alive() -> bool {
+    true
+}
+
+impl Particle {
+    pub fn new(pos: Vec2) -> Self {
+        Particle {
+            pos: pos.clone(),
+            pos_last: pos,
+            acc: Vec2::zero(),
+            vel: Vec2::zero(),
+            radius: 20_f32,
+            mass: 1_f32,
+            gravity_scale: 1_f32,
+            restitution: 0_f32,
+            static_friction: 0_f32,
+            kinetic_friction: 0_f32,
+            damping: 0_f32,
+            regions: u32::MAX,
+            layer: 1,
+            mask: u32::MAX,
+            pinned: false,
+            asleep: false,
+            still_steps: 0,
+            age: 0_f32,
+            lifetime: None,
+            temperature: 0_f32,
+            sph_fluid: None,
+            color: color::STEELBLUE,
+            user_data: 0,
+            alive: true,
+        }
+    }
+
+    pub fn with_mass(pos: Vec2, radius: f32, mass: f32) -> Self {
+        Particle {
+            radius,
+            mass,
+            ..Particle::new(pos)
+        }
+    }
+
+    /// Mass derived from the disc area, `density * pi * r^2`.
+    pub fn with_density(pos: Vec2, radius: f32, density: f32) -> Self {
+        Particle::with_mass(
+            pos,
+            radius,
+            density * std::f32::consts::PI * radius * radius,
+        )
+    }
+
+    pub fn collides_with(&self, other: &Particle) -> bool {
+        self.layer & other.mask != 0 && other.layer & self.mask != 0
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.lifetime.is_some_and(|l| self.age >= l)
+    }
+
+    /// Remaining fraction of the lifetime in `[0, 1]`, `1` for immortal particles.
+    pub fn life_fraction(&self) -> f32 {
+        match self.lifetime {
+            Some(l) if l > 0_f32 => f32::clamp(1_f32 - self.age / l, 0_f32, 1_f32),
+            Some(_) => 0_f32,
+            None => 1_f32,
+        }
+    }
+
+    pub fn wake(&mut self) {
+        self.asleep = false;
+        self.still_steps = 0;
+    }
+
+    pub fn inv_mass(&self) -> f32 {
+        if self.pinned || self.asleep {
+            0_f32
+        } else {
+            1_f32 / self.mass
+        }
+    }
+
+    pub fn set_velocity(&mut self, v: Vec2, dt: f32) {
+        self.pos_last = self.pos.clone() - (v.clone() * dt);
+        self.vel = v;
+    }
+
+    pub fn add_velocity(&mut self, v: Vec2, dt: f32) {
+        self.pos_last -= v.clone() * dt;
+        self.vel += v;
+    }
+
+    /// Position between the last two integration steps, `alpha` in `[0, 1]`.
+    pub fn interpolated(&self, alpha: f32) -> Vec2 {
+        self.pos_last.clone() + (self.pos.clone() - self.pos_last.clone()) * alpha
+    }
+
+    pub fn velocity(&self, dt: f32) -> Vec2 {
+        (self.pos.clone() - self.pos_last.clone()) / dt
+    }
+}
+
+/// One field of every particle, indexed by particle. Derefs to a slice, so values can be read
+/// and edited in place, while particles are only added and removed through `Particles`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Column<T>(Vec<T>);
+
+impl<T> Default for Column<T> {
+    fn default() -> Self {
+        Column(Vec::new())
+    }
+}
+
+impl<T> Deref for Column<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Column<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.0
+    }
+}
+
+/// Declares `Particles` with one column per `Particle` field, along with the methods that add,
+/// remove and move particles in every column at once.
+macro_rules! columns {
+    ($($field:ident: $ty:ty,)*) => {
+        /// The particles of a world, stored as one array per field, so the solver and the
+        /// integrator only stream through the fields they use. Particle `i` is index `i` of
+        /// every column.
+        #[derive(Clone, Debug, Default)]
+        #[cfg_attr(
+            feature = "serde",
+            derive(serde::Serialize, serde::Deserialize),
+            serde(from = "Vec<Particle>", into = "Vec<Particle>")
+        )]
+        pub struct Particles {
+            $(pub $field: Column<$ty>,)*
+        }
+
+        impl Particles {
+            pub fn push(&mut self, particle: Particle) {
+                $(self.$field.0.push(particle.$field);)*
+            }
+
+            /// A copy of particle `i`, or `None` past the end.
+            pub fn get(&self, i: usize) -> Option<Particle> {
+                (i < self.len()).then(|| Particle {
+                    $($field: self.$field[i].clone(),)*
+                })
+            }
+
+            /// Overwrites particle `i`.
+            pub fn set(&mut self, i: usize, particle: Particle) {
+                $(self.$field[i] = particle.$field;)*
+            }
+
+            pub fn swap(&mut self, a: usize, b: usize) {
+                $(self.$field.swap(a, b);)*
+            }
+
+            pub fn truncate(&mut self, len: usize) {
+                $(self.$field.0.truncate(len);)*
+            }
+
+            pub fn reserve(&mut self, additional: usize) {
+                $(self.$field.0.reserve(additional);)*
+            }
+
+            /// Keeps the particles whose entry in `keep` is set, in order.
+            pub fn retain_mask(&mut self, keep: &[bool]) {
+                $(
+                    let mut i = 0;
+                    self.$field.0.retain(|_| {
+                        i += 1;
+                        keep[i - 1]
+                    });
+                )*
+            }
+        }
+    };
+}
+
+columns! {
+    pos: Vec2,
+    pos_last: Vec2,
+    acc: Vec2,
+    vel: Vec2,
+    radius: f32,
+    mass: f32,
+    gravity_scale: f32,
+    restitution: f32,
+    static_friction: f32,
+    kinetic_friction: f32,
+    damping: f32,
+    regions: u32,
+    layer: u32,
+    mask: u32,
+    pinned: bool,
+    asleep: bool,
+    still_steps: u32,
+    age: f32,
+    lifetime: Option<f32>,
+    temperature: f32,
+    sph_fluid: Option<usize>,
+    color: Color,
+    user_data: u64,
+    alive: bool,
+}
+
+impl Particles {
+    pub fn new() -> Self {
+        Particles::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// A copy of particle `i`; panics past the end.
+    pub fn particle(&self, i: usize) -> Particle {
+        self.get(i).expect("particle index in bounds")
+    }
+
+    /// Copies of all particles, in index order.
+    pub fn iter(&self) -> impl Iterator<Item = Particle> + '_ {
+        (0..self.len()).map(|i| self.particle(i))
+    }
+
+    pub fn max_radius(&self) -> f32 {
+        self.radius.iter().copied().fold(0_f32, f32::max)
+    }
+
+    pub fn collides(&self, i: usize, k: usize) -> bool {
+        self.alive[i]
+            && self.alive[k]
+            && self.layer[i] & self.mask[k] != 0
+            && self.layer[k] & self.mask[i] != 0
+    }
+
+    /// Whether the integrators advance particle `i`: it is alive and neither pinned nor asleep.
+    pub fn moves(&self, i: usize) -> bool {
+        self.alive[i] && !self.pinned[i] && !self.asleep[i]
+    }
+
+    pub fn is_expired(&self, i: usize) -> bool {
+        self.lifetime[i].is_some_and(|l| self.age[i] >= l)
+    }
+
+    /// Remaining fraction of the lifetime of particle `i`, see `Particle::life_fraction`.
+    pub fn life_fraction(&self, i: usize) -> f32 {
+        match self.lifetime[i] {
+            Some(l) if l > 0_f32 => f32::clamp(1_f32 - self.age[i] / l, 0_f32, 1_f32),
+            Some(_) => 0_f32,
+            None => 1_f32,
+        }
+    }
+
+    pub fn wake(&mut self, i: usize) {
+        self.asleep[i] = false;
+        self.still_steps[i] = 0;
+    }
+
+    pub fn inv_mass(&self, i: usize) -> f32 {
+        if !self.moves(i) {
+            0_f32
+        } else {
+            1_f32 / self.mass[i]
+        }
+    }
+
+    /// Position Verlet step of particle `i`.
+    pub fn update(&mut self, i: usize, dt: f32, global_damping: f32) {
+        self.age[i] += dt;
+        if !self.moves(i) {
+            self.pos_last[i] = self.pos[i].clone();
+            self.acc[i] = Vec2::zero();
+            return;
+        }
+        let drag = f32::max(1_f32 - (self.damping[i] + global_damping) * dt, 0_f32);
+        let delta = (&self.pos[i] - &self.pos_last[i]) * drag;
+        self.pos_last[i] = self.pos[i].clone();
+        self.pos[i] += delta + &self.acc[i] * (dt * dt);
+        self.acc[i] = Vec2::zero();
+    }
+
+    /// Shortens the last integration step of particle `i` to at most `max` units, scaling its
+    /// velocity with it.
+    pub fn limit_step(&mut self, i: usize, max: f32) {
+        let step = &self.pos[i] - &self.pos_last[i];
+        let len = step.len();
+        if len > max {
+            let scale = max / len;
+            self.pos[i] = &self.pos_last[i] + &(step * scale);
+            self.vel[i] = &self.vel[i] * scale;
+        }
+    }
+
+    pub fn accelerate(&mut self, i: usize, acc: Vec2) {
+        self.acc[i] += acc;
+    }
+
+    pub fn apply_force(&mut self, i: usize, force: Vec2) {
+        self.acc[i] += force / self.mass[i];
+    }
+
+    pub fn set_velocity(&mut self, i: usize, v: Vec2, dt: f32) {
+        self.pos_last[i] = &self.pos[i] - &(&v * dt);
+        self.vel[i] = v;
+    }
+
+    pub fn add_velocity(&mut self, i: usize, v: Vec2, dt: f32) {
+        self.pos_last[i] -= &v * dt;
+        self.vel[i] += v;
+    }
+
+    /// Position of particle `i` between the last two integration steps, `alpha` in `[0, 1]`.
+    pub fn interpolated(&self, i: usize, alpha: f32) -> Vec2 {
+        &self.pos_last[i] + &((&self.pos[i] - &self.pos_last[i]) * alpha)
+    }
+
+    pub fn velocity(&self, i: usize, dt: f32) -> Vec2 {
+        (&self.pos[i] - &self.pos_last[i]) / dt
+    }
+}
+
+impl Extend<Particle> for Particles {
+    fn extend<I: IntoIterator<Item = Particle>>(&mut self, iter: I) {
+        for particle in iter {
+            self.push(particle);
+        }
+    }
+}
+
+impl FromIterator<Particle> for Particles {
+    fn from_iter<I: IntoIterator<Item = Particle>>(iter: I) -> Self {
+        let mut particles = Particles::new();
+        particles.extend(iter);
+        particles
+    }
+}
+
+impl From<Vec<Particle>> for Particles {
+    fn from(particles: Vec<Particle>) -> Self {
+        particles.into_iter().collect()
+    }
+}
+
+impl From<Particles> for Vec<Particle> {
+    fn from(particles: Particles) -> Self {
+        particles.iter().collect()
+    }
+}
@@ -0,0 +1,72 @@
+use utils::vec::Vec2;
+
+use crate::particle::Particles;
+
+/// Toroidal boundary: particles leaving the box through one side come back in through the
+/// opposite one, and particles near opposite sides collide across the seam. Links, bodies and
+/// obstacles don't see the wrap.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Periodic {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Periodic {
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Periodic { min, max }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max.clone() - self.min.clone()
+    }
+
+    /// Moves `p` back into the box by whole box sizes, keeping its velocity.
+    pub fn wrap(&self, particles: &mut Particles, i: usize) {
+        let size = self.size();
+        let shift = Vec2::new(
+            wrap_shift(particles.pos[i].x, self.min.x, size.x),
+            wrap_shift(particles.pos[i].y, self.min.y, size.y),
+        );
+        if shift.x != 0_f32 || shift.y != 0_f32 {
+            particles.pos[i] += shift.clone();
+            particles.pos_last[i] += shift;
+        }
+    }
+
+    /// Shifts that move a point within `reach` of an edge to its images just beyond the
+    /// opposite edges, including the diagonal one near a corner.
+    pub fn image_shifts(&self, p: &Vec2, reach: f32) -> Vec<Vec2> {
+        let size = self.size();
+        let side = |v: f32, min: f32, max: f32, size: f32| {
+            if v - min < reach {
+                size
+            } else if max - v < reach {
+                -size
+            } else {
+                0_f32
+            }
+        };
+        let dx = side(p.x, self.min.x, self.max.x, size.x);
+        let dy = side(p.y, self.min.y, self.max.y, size.y);
+        let mut shifts = Vec::new();
+        if dx != 0_f32 {
+            shifts.push(Vec2::new(dx, 0_f32));
+        }
+        if dy != 0_f32 {
+            shifts.push(Vec2::new(0_f32, dy));
+        }
+        if dx != 0_f32 && dy != 0_f32 {
+            shifts.push(Vec2::new(dx, dy));
+        }
+        shifts
+    }
+}
+
+/// Whole multiples of `size` that bring `v` into `min..min + size`.
+fn wrap_shift(v: f32, min: f32, size: f32) -> f32 {
+    if size <= 0_f32 {
+        return 0_f32;
+    }
+    -f32::floor((v - min) / size) * size
+}
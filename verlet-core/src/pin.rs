@@ -0,0 +1,21 @@
+use utils::vec::Vec2;
+
+use crate::particle::Particles;
+
+/// Nails a particle to a world position. Moving `point` drags the particle along.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pin {
+    pub particle: usize,
+    pub point: Vec2,
+}
+
+impl Pin {
+    pub fn new(particle: usize, point: Vec2) -> Self {
+        Pin { particle, point }
+    }
+
+    pub fn apply(&self, particles: &mut Particles) {
+        particles.pos[self.particle] = self.point.clone();
+    }
+}
@@ -0,0 +1,43 @@
+use std::time::{Duration, Instant};
+
+/// Wall-clock time spent in each phase of the last `World::step`, measured while
+/// `World::profile` is set. Phases sum over all substeps and solver iterations; `total` also
+/// covers emitters, merging and despawning.
+#[derive(Clone, Debug, Default)]
+pub struct StepTimings {
+    /// Finding candidate pairs and rebuilding the index for queries.
+    pub broadphase: Duration,
+    /// Resolving contacts, their velocities and the continuous collision pass.
+    pub narrowphase: Duration,
+    /// Links, bends, obstacles, containers and pins.
+    pub constraints: Duration,
+    /// Forces, heat and the integrator.
+    pub integrate: Duration,
+    pub total: Duration,
+}
+
+/// Measures consecutive laps of a step; a disabled clock never reads the time, which isn't
+/// available on every target.
+pub(crate) struct Clock(Option<Instant>);
+
+impl Clock {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Clock(enabled.then(Instant::now))
+    }
+
+    /// Starts a new lap without counting the last one towards any phase.
+    pub(crate) fn skip(&mut self) {
+        if self.0.is_some() {
+            self.0 = Some(Instant::now());
+        }
+    }
+
+    /// Adds the time since the previous lap to `phase`.
+    pub(crate) fn lap(&mut self, phase: &mut Duration) {
+        if let Some(last) = self.0 {
+            let now = Instant::now();
+            *phase += now - last;
+            self.0 = Some(now);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use utils::vec::Vec2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitTarget {
+    Particle(usize),
+    Obstacle(usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct Hit {
+    pub target: HitTarget,
+    pub point: Vec2,
+    /// Surface normal at `point`, facing the ray origin.
+    pub normal: Vec2,
+    pub distance: f32,
+}
+
+/// Distance along the unit ray `origin + t * dir` to where it enters the circle. Rays starting
+/// inside the circle don't hit it.
+pub fn ray_circle(origin: &Vec2, dir: &Vec2, center: &Vec2, radius: f32) -> Option<f32> {
+    let f = origin.clone() - center.clone();
+    let b = f.dot(dir);
+    let c = f.dot(&f) - radius * radius;
+    if c < 0_f32 {
+        return None;
+    }
+    let disc = b * b - c;
+    if disc < 0_f32 {
+        return None;
+    }
+    let t = -b - disc.sqrt();
+    (t >= 0_f32).then_some(t)
+}
+
+/// Distance along the unit ray `origin + t * dir` to segment `a`-`b`.
+pub fn ray_segment(origin: &Vec2, dir: &Vec2, a: &Vec2, b: &Vec2) -> Option<f32> {
+    let ab = b.clone() - a.clone();
+    let denom = cross(dir, &ab);
+    if denom.abs() <= f32::EPSILON {
+        return None;
+    }
+    let ao = a.clone() - origin.clone();
+    let t = cross(&ao, &ab) / denom;
+    let u = cross(&ao, dir) / denom;
+    (t >= 0_f32 && (0_f32..=1_f32).contains(&u)).then_some(t)
+}
+
+fn cross(a: &Vec2, b: &Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
@@ -0,0 +1,286 @@
+use std::mem;
+
+use utils::vec::Vec2;
+
+use crate::attractor::Attractor;
+use crate::broadphase::BroadphaseKind;
+use crate::constraint::Constraint;
+use crate::force_field::ForceFieldKind;
+use crate::integrator::IntegratorKind;
+use crate::merge::Merging;
+use crate::nbody::NBody;
+use crate::obstacle::Obstacle;
+use crate::particle::Particle;
+use crate::solver::{Projection, Sleep};
+use crate::tether::Tether;
+use crate::timestep::AdaptiveSubsteps;
+use crate::world::World;
+
+/// An outside change to the world between steps. Since stepping is deterministic for a fixed
+/// `dt`, the initial state plus the commands issued before each step reproduce a whole run.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Command {
+    MoveConstraint {
+        index: usize,
+        center: Vec2,
+    },
+    SetConstraint {
+        index: usize,
+        constraint: Constraint,
+    },
+    MoveEmitter {
+        index: usize,
+        position: Vec2,
+    },
+    SetEmitterRate {
+        index: usize,
+        rate: f32,
+    },
+    SetGravity(Vec2),
+    SetSubsteps(usize),
+    SetAdaptiveSubsteps(Option<AdaptiveSubsteps>),
+    SetTimeScale(f32),
+    SetResponseCoef(f32),
+    AddTether(Tether),
+    MoveTether {
+        index: usize,
+        point: Vec2,
+    },
+    RemoveTether(usize),
+    AddObstacle(Obstacle),
+    RemoveObstacle(usize),
+    SetBroadphase(BroadphaseKind),
+    SetIntegrator(IntegratorKind),
+    /// `None` switches back to uniform gravity.
+    SetAttractor(Option<Attractor>),
+    SetNBody(Option<NBody>),
+    ImpulseRadial {
+        center: Vec2,
+        strength: f32,
+        falloff: f32,
+        dt: f32,
+    },
+    ForceRadial {
+        center: Vec2,
+        strength: f32,
+        radius: f32,
+        dt: f32,
+    },
+    AddForceField(ForceFieldKind),
+    RemoveForceField(usize),
+    SetProjection(Projection),
+    SetMerging(Option<Merging>),
+    SplitParticle {
+        index: usize,
+        count: usize,
+    },
+    AddParticle(Particle),
+    RemoveParticle(usize),
+    /// Removes all the listed particles, given by their indices before any is removed.
+    RemoveParticles(Vec<usize>),
+    /// Turning sleep off wakes every particle.
+    SetSleep(Option<Sleep>),
+    /// Replaces the whole world, e.g. when a snapshot is loaded.
+    Restore(Box<World>),
+}
+
+impl Command {
+    /// Commands naming an index that doesn't exist do nothing, since a replay log may not
+    /// match the world it's played on.
+    pub fn apply(&self, world: &mut World) {
+        match self {
+            Command::MoveConstraint { index, center } => {
+                if let Some(c) = world.constraints.get_mut(*index) {
+                    c.move_to(center.clone());
+                }
+            }
+            Command::SetConstraint { index, constraint } => {
+                if let Some(c) = world.constraints.get_mut(*index) {
+                    *c = constraint.clone();
+                }
+            }
+            Command::MoveEmitter { index, position } => {
+                if let Some(e) = world.emitters.get_mut(*index) {
+                    e.position = position.clone();
+                }
+            }
+            Command::SetEmitterRate { index, rate } => {
+                if let Some(e) = world.emitters.get_mut(*index) {
+                    e.rate = *rate;
+                }
+            }
+            Command::SetGravity(gravity) => world.gravity = gravity.clone(),
+            Command::SetSubsteps(substeps) => world.set_substeps(*substeps),
+            Command::SetAdaptiveSubsteps(adaptive) => world.adaptive_substeps = adaptive.clone(),
+            Command::SetTimeScale(scale) => world.set_time_scale(*scale),
+            Command::SetResponseCoef(coef) => world.solver.response_coef = *coef,
+            Command::AddTether(tether) => {
+                if tether.particle < world.particles.len() {
+                    world.add_tether(tether.clone());
+                }
+            }
+            Command::MoveTether { index, point } => {
+                if let Some(t) = world.tethers.get_mut(*index) {
+                    t.point = point.clone();
+                }
+            }
+            Command::RemoveTether(index) => {
+                if *index < world.tethers.len() {
+                    world.tethers.remove(*index);
+                }
+            }
+            Command::AddObstacle(obstacle) => {
+                world.add_obstacle(obstacle.clone());
+            }
+            Command::RemoveObstacle(index) => {
+                if *index < world.obstacles.len() {
+                    world.obstacles.remove(*index);
+                }
+            }
+            Command::SetBroadphase(kind) => world.solver.set_broadphase(kind.build()),
+            Command::SetIntegrator(kind) => world.integrator = kind.build(),
+            Command::SetAttractor(attractor) => world.attractor = attractor.clone(),
+            Command::SetNBody(n_body) => world.n_body = n_body.clone(),
+            Command::ImpulseRadial {
+                center,
+                strength,
+                falloff,
+                dt,
+            } => world.apply_impulse_radial(center.clone(), *strength, *falloff, *dt),
+            Command::ForceRadial {
+                center,
+                strength,
+                radius,
+                dt,
+            } => world.apply_force_radial(center.clone(), *strength, *radius, *dt),
+            Command::AddForceField(kind) => world.force_fields.push(kind.build()),
+            Command::RemoveForceField(index) => {
+                if *index < world.force_fields.len() {
+                    world.force_fields.remove(*index);
+                }
+            }
+            Command::SetProjection(projection) => world.solver.projection = projection.clone(),
+            Command::SetMerging(merging) => world.merging = merging.clone(),
+            Command::SplitParticle { index, count } => {
+                if *index < world.particles.len() && world.particles.alive[*index] {
+                    world.split_particle(*index, *count);
+                }
+            }
+            Command::AddParticle(particle) => {
+                world.add_particle(particle.clone());
+            }
+            Command::RemoveParticle(index) => {
+                if let Some(handle) = world.handle(*index) {
+                    world.remove(handle);
+                }
+            }
+            Command::RemoveParticles(indices) => {
+                let handles: Vec<_> = indices.iter().filter_map(|&i| world.handle(i)).collect();
+                for handle in handles {
+                    world.remove(handle);
+                }
+            }
+            Command::SetSleep(sleep) => {
+                world.solver.sleep = sleep.clone();
+                if sleep.is_none() {
+                    (0..world.particles.len()).for_each(|i| world.particles.wake(i));
+                }
+            }
+            Command::Restore(snapshot) => *world = (**snapshot).clone(),
+        }
+    }
+}
+
+/// A recorded run: the starting world and, for every step, the commands applied before it.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Recording {
+    pub dt: f32,
+    pub initial: World,
+    pub steps: Vec<Vec<Command>>,
+}
+
+impl Recording {
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+/// Routes commands and steps through to the world while recording them.
+#[derive(Clone, Debug)]
+pub struct Recorder {
+    recording: Recording,
+    pending: Vec<Command>,
+}
+
+impl Recorder {
+    pub fn new(world: &World, dt: f32) -> Self {
+        Recorder {
+            recording: Recording {
+                dt,
+                initial: world.clone(),
+                steps: Vec::new(),
+            },
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn apply(&mut self, world: &mut World, command: Command) {
+        command.apply(world);
+        self.pending.push(command);
+    }
+
+    pub fn step(&mut self, world: &mut World) {
+        world.step(self.recording.dt);
+        self.recording.steps.push(mem::take(&mut self.pending));
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+}
+
+/// Re-runs a recording step by step.
+#[derive(Clone, Debug)]
+pub struct Playback {
+    recording: Recording,
+    next: usize,
+}
+
+impl Playback {
+    pub fn new(recording: Recording) -> Self {
+        Playback { recording, next: 0 }
+    }
+
+    /// A fresh copy of the recorded starting state.
+    pub fn world(&self) -> World {
+        self.recording.initial.clone()
+    }
+
+    pub fn position(&self) -> usize {
+        self.next
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.recording.len()
+    }
+
+    /// Applies the next step's commands and steps `world`; returns `false` once the recording
+    /// is exhausted.
+    pub fn step(&mut self, world: &mut World) -> bool {
+        let Some(commands) = self.recording.steps.get(self.next) else {
+            return false;
+        };
+        for command in commands {
+            command.apply(world);
+        }
+        world.step(self.recording.dt);
+        self.next += 1;
+        true
+    }
+}
@@ -0,0 +1,71 @@
+use utils::vec::Vec2;
+
+use crate::particle::Particles;
+
+/// Group of particles held in a fixed arrangement by shape matching: every solve finds the
+/// rotation and translation that best fit the rest shape onto the current positions and pulls
+/// each particle towards its matched spot, with `stiffness` in `[0, 1]` scaling the pull.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RigidCluster {
+    pub particles: Vec<usize>,
+    /// Rest offset of each particle from the cluster's center of mass.
+    pub rest: Vec<Vec2>,
+    pub stiffness: f32,
+}
+
+impl RigidCluster {
+    /// Cluster holding `ids` in their current arrangement.
+    pub fn new(particles: &Particles, ids: Vec<usize>, stiffness: f32) -> Self {
+        let center = center_of_mass(particles, &ids);
+        let rest = ids
+            .iter()
+            .map(|&i| particles.pos[i].clone() - center.clone())
+            .collect();
+        RigidCluster {
+            particles: ids,
+            rest,
+            stiffness,
+        }
+    }
+
+    /// Angle by which the rest shape is currently turned.
+    pub fn angle(&self, particles: &Particles) -> f32 {
+        let center = center_of_mass(particles, &self.particles);
+        let (mut dot, mut cross) = (0_f32, 0_f32);
+        for (&i, r) in self.particles.iter().zip(self.rest.iter()) {
+            let q = particles.pos[i].clone() - center.clone();
+            let mass = particles.mass[i];
+            dot += mass * r.dot(&q);
+            cross += mass * (r.x * q.y - r.y * q.x);
+        }
+        f32::atan2(cross, dot)
+    }
+
+    pub fn solve(&self, particles: &mut Particles) {
+        if self.particles.len() < 2 {
+            return;
+        }
+        let center = center_of_mass(particles, &self.particles);
+        let (sin, cos) = self.angle(particles).sin_cos();
+        for (&i, r) in self.particles.iter().zip(self.rest.iter()) {
+            if particles.pinned[i] {
+                continue;
+            }
+            let goal = center.clone() + Vec2::new(cos * r.x - sin * r.y, sin * r.x + cos * r.y);
+            let correction = (goal - particles.pos[i].clone()) * self.stiffness;
+            particles.pos[i] += correction;
+        }
+    }
+}
+
+fn center_of_mass(particles: &Particles, ids: &[usize]) -> Vec2 {
+    let mass: f32 = ids.iter().map(|&i| particles.mass[i]).sum();
+    if mass <= 0_f32 {
+        return Vec2::zero();
+    }
+    let sum = ids.iter().fold(Vec2::zero(), |acc, &i| {
+        acc + particles.pos[i].clone() * particles.mass[i]
+    });
+    sum / mass
+}
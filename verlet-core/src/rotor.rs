@@ -0,0 +1,141 @@
+use utils::vec::Vec2;
+
+use crate::obstacle::segment_contact;
+use crate::particle::Particles;
+
+/// Kinematic obstacle that spins about `pivot` at a set `angular_velocity` no matter what it
+/// hits: a set of thick arms, like mixer paddles or the blades of a water wheel. Particles
+/// it touches are pushed out and carried along with the surface's velocity.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rotor {
+    pub pivot: Vec2,
+    /// Arm segments relative to `pivot` at angle `0`.
+    pub arms: Vec<(Vec2, Vec2)>,
+    /// Half the width of each arm.
+    pub thickness: f32,
+    /// Current angle in radians.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub angle: f32,
+    /// Radians per second, counter-clockwise.
+    pub angular_velocity: f32,
+    /// See `Obstacle::Polyline::friction`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub friction: f32,
+}
+
+impl Rotor {
+    pub fn new(
+        pivot: Vec2,
+        arms: Vec<(Vec2, Vec2)>,
+        thickness: f32,
+        angular_velocity: f32,
+    ) -> Self {
+        Rotor {
+            pivot,
+            arms,
+            thickness,
+            angle: 0_f32,
+            angular_velocity,
+            friction: 0_f32,
+        }
+    }
+
+    /// `count` evenly spaced paddles reaching `length` out from the pivot.
+    pub fn paddles(
+        pivot: Vec2,
+        count: usize,
+        length: f32,
+        thickness: f32,
+        angular_velocity: f32,
+    ) -> Self {
+        Rotor::wheel(pivot, 0_f32, length, count, thickness, angular_velocity)
+    }
+
+    /// `count` blades running from `inner` to `outer` radius, like a water wheel.
+    pub fn wheel(
+        pivot: Vec2,
+        inner: f32,
+        outer: f32,
+        count: usize,
+        thickness: f32,
+        angular_velocity: f32,
+    ) -> Self {
+        let arms = (0..count)
+            .map(|i| {
+                let (sin, cos) = (std::f32::consts::TAU * i as f32 / count as f32).sin_cos();
+                (
+                    Vec2::new(inner * cos, inner * sin),
+                    Vec2::new(outer * cos, outer * sin),
+                )
+            })
+            .collect();
+        Rotor::new(pivot, arms, thickness, angular_velocity)
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        self.angle = (self.angle + self.angular_velocity * dt) % std::f32::consts::TAU;
+    }
+
+    /// The arms in world coordinates at the current angle.
+    pub fn segments(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        let (sin, cos) = self.angle.sin_cos();
+        let place = move |v: &Vec2| {
+            Vec2::new(
+                self.pivot.x + cos * v.x - sin * v.y,
+                self.pivot.y + sin * v.x + cos * v.y,
+            )
+        };
+        self.arms.iter().map(move |(a, b)| (place(a), place(b)))
+    }
+
+    /// Velocity of the rotor's surface at world point `p`.
+    pub fn velocity_at(&self, p: &Vec2) -> Vec2 {
+        let r = p.clone() - self.pivot.clone();
+        Vec2::new(-r.y, r.x) * self.angular_velocity
+    }
+
+    /// Pushes particle `i` out of the arms and gives it the arm's velocity along the contact
+    /// normal, plus as much of its tangential velocity as `friction` allows, over a step of
+    /// `dt`.
+    pub fn apply_to(&self, particles: &mut Particles, i: usize, dt: f32) {
+        let deepest = self
+            .segments()
+            .filter_map(|(a, b)| {
+                segment_contact(
+                    &a,
+                    &b,
+                    self.thickness,
+                    &particles.pos[i],
+                    particles.radius[i],
+                )
+            })
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        let Some((n, depth)) = deepest else {
+            return;
+        };
+        particles.wake(i);
+        particles.pos[i] += n.clone() * depth;
+
+        let surface = self.velocity_at(&particles.pos[i]) * dt;
+        let mut rel = particles.pos[i].clone() - particles.pos_last[i].clone() - surface.clone();
+        let v_in = rel.dot(&n);
+        if v_in < 0_f32 {
+            rel -= n.clone() * ((1_f32 + particles.restitution[i]) * v_in);
+        }
+        let v_t = rel.clone() - n.clone() * rel.dot(&n);
+        let slip = v_t.len();
+        if slip > 0_f32 && self.friction > 0_f32 {
+            rel -= v_t * f32::min(self.friction * depth / slip, 1_f32);
+        }
+        particles.pos_last[i] = particles.pos[i].clone() - (rel + surface);
+    }
+
+    pub fn apply(&self, particles: &mut Particles, dt: f32) {
+        for i in 0..particles.len() {
+            if particles.alive[i] && !particles.pinned[i] {
+                self.apply_to(particles, i, dt);
+            }
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use utils::vec::Vec2;
+
+use crate::attractor::Attractor;
+use crate::color::{self, Color};
+use crate::constraint::Constraint;
+use crate::emitter::Emitter;
+use crate::fluid::FluidZone;
+use crate::force_field::ForceFieldKind;
+use crate::heat::HeatSource;
+use crate::integrator::IntegratorKind;
+use crate::merge::Merging;
+use crate::nbody::NBody;
+use crate::obstacle::Obstacle;
+use crate::particle::Particle;
+use crate::periodic::Periodic;
+use crate::rotor::Rotor;
+use crate::schedule::SpawnEvent;
+use crate::solver::Projection;
+use crate::sph::SphFluid;
+use crate::timestep::AdaptiveSubsteps;
+use crate::world::World;
+
+/// Declarative setup of an experiment: containers, static and spinning obstacles, fluids, heat sources,
+/// SPH fluids, emitters, timed spawns, force fields, initial particles and gravity. Missing fields take the
+/// `World::default` values.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Scene {
+    pub gravity: Vec2,
+    pub attractor: Option<Attractor>,
+    pub n_body: Option<NBody>,
+    pub force_fields: Vec<ForceFieldKind>,
+    pub constraints: Vec<Constraint>,
+    /// See `World::periodic`.
+    pub periodic: Option<Periodic>,
+    pub obstacles: Vec<Obstacle>,
+    pub rotors: Vec<Rotor>,
+    pub fluids: Vec<FluidZone>,
+    pub sph_fluids: Vec<SphFluid>,
+    pub heat_sources: Vec<HeatSource>,
+    /// See `Solver::conductivity`.
+    pub conductivity: f32,
+    /// See `Solver::projection`.
+    pub projection: Projection,
+    /// See `World::substeps`.
+    pub substeps: usize,
+    /// See `World::adaptive_substeps`.
+    pub adaptive_substeps: Option<AdaptiveSubsteps>,
+    /// See `World::solver_iterations`.
+    pub solver_iterations: usize,
+    pub integrator: IntegratorKind,
+    pub merging: Option<Merging>,
+    /// See `World::max_particles`.
+    pub max_particles: Option<usize>,
+    /// See `World::max_displacement`.
+    pub max_displacement: Option<f32>,
+    /// See `World::ccd`.
+    pub ccd: bool,
+    pub emitters: Vec<Emitter>,
+    /// See `World::schedule`.
+    pub schedule: Vec<SpawnEvent>,
+    /// Seeds the world's random number generator, see `World::seed`.
+    pub seed: Option<u64>,
+    pub particles: Vec<SceneParticle>,
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        let world = World::default();
+        Scene {
+            gravity: world.gravity,
+            attractor: None,
+            n_body: None,
+            force_fields: Vec::new(),
+            constraints: world.constraints,
+            periodic: None,
+            obstacles: Vec::new(),
+            rotors: Vec::new(),
+            fluids: Vec::new(),
+            sph_fluids: Vec::new(),
+            heat_sources: Vec::new(),
+            conductivity: world.solver.conductivity,
+            projection: world.solver.projection,
+            substeps: world.substeps,
+            adaptive_substeps: None,
+            solver_iterations: world.solver_iterations,
+            integrator: IntegratorKind::default(),
+            merging: None,
+            max_particles: world.max_particles,
+            max_displacement: world.max_displacement,
+            ccd: world.ccd,
+            emitters: Vec::new(),
+            schedule: Vec::new(),
+            seed: None,
+            particles: Vec::new(),
+        }
+    }
+}
+
+/// Initial state of a particle; `velocity` is in units per second.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct SceneParticle {
+    pub pos: Vec2,
+    pub velocity: Vec2,
+    pub radius: f32,
+    pub mass: f32,
+    /// See `Particle::gravity_scale`.
+    pub gravity_scale: f32,
+    pub restitution: f32,
+    pub pinned: bool,
+    pub temperature: f32,
+    pub sph_fluid: Option<usize>,
+    pub color: Color,
+    /// See `Particle::user_data`.
+    pub user_data: u64,
+}
+
+impl Default for SceneParticle {
+    fn default() -> Self {
+        SceneParticle {
+            pos: Vec2::zero(),
+            velocity: Vec2::zero(),
+            radius: 20_f32,
+            mass: 1_f32,
+            gravity_scale: 1_f32,
+            restitution: 0_f32,
+            pinned: false,
+            temperature: 0_f32,
+            sph_fluid: None,
+            color: color::STEELBLUE,
+            user_data: 0,
+        }
+    }
+}
+
+impl Scene {
+    /// Builds a world from the scene, with initial velocities set for steps of `dt`.
+    pub fn build(&self, dt: f32) -> World {
+        let mut world = World::new(self.gravity.clone(), self.constraints.clone());
+        world.attractor = self.attractor.clone();
+        world.n_body = self.n_body.clone();
+        world.force_fields = self
+            .force_fields
+            .iter()
+            .map(ForceFieldKind::build)
+            .collect();
+        world.periodic = self.periodic.clone();
+        world.obstacles = self.obstacles.clone();
+        world.rotors = self.rotors.clone();
+        world.fluids = self.fluids.clone();
+        world.sph_fluids = self.sph_fluids.clone();
+        world.heat_sources = self.heat_sources.clone();
+        world.solver.conductivity = self.conductivity;
+        world.solver.projection = self.projection.clone();
+        world.substeps = self.substeps;
+        world.adaptive_substeps = self.adaptive_substeps.clone();
+        world.solver_iterations = self.solver_iterations;
+        world.integrator = self.integrator.build();
+        world.merging = self.merging.clone();
+        world.max_particles = self.max_particles;
+        world.max_displacement = self.max_displacement;
+        world.ccd = self.ccd;
+        world.emitters = self.emitters.clone();
+        world.schedule = self.schedule.clone();
+        if let Some(seed) = self.seed {
+            world.seed(seed);
+        }
+        for desc in self.particles.iter() {
+            world.add_particle(desc.build(dt));
+        }
+        world
+    }
+}
+
+impl SceneParticle {
+    /// The particle this describes, moving at `velocity` for steps of `dt`.
+    pub fn build(&self, dt: f32) -> Particle {
+        let mut p = Particle::with_mass(self.pos.clone(), self.radius, self.mass);
+        p.gravity_scale = self.gravity_scale;
+        p.restitution = self.restitution;
+        p.pinned = self.pinned;
+        p.temperature = self.temperature;
+        p.sph_fluid = self.sph_fluid;
+        p.color = self.color;
+        p.user_data = self.user_data;
+        p.set_velocity(self.velocity.clone(), dt);
+        p
+    }
+}
@@ -0,0 +1,246 @@
+use utils::vec::Vec2;
+
+/// Signed distance field sampled on a regular grid: negative inside the shape, positive
+/// outside. Values between samples are interpolated bilinearly.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SdfGrid {
+    /// World position of sample `(0, 0)`.
+    pub origin: Vec2,
+    /// Distance between neighbouring samples.
+    pub cell: f32,
+    pub width: usize,
+    pub height: usize,
+    /// Row-major samples, `width * height` of them.
+    pub values: Vec<f32>,
+}
+
+impl SdfGrid {
+    /// Samples `distance` over `min..max` every `cell` units. Returns `None` unless `cell` is
+    /// finite and positive and the bounds are finite.
+    pub fn from_fn(
+        min: Vec2,
+        max: Vec2,
+        cell: f32,
+        distance: impl Fn(&Vec2) -> f32,
+    ) -> Option<Self> {
+        let span = max.clone() - min.clone();
+        if !(cell > 0_f32 && cell.is_finite() && span.x.is_finite() && span.y.is_finite()) {
+            return None;
+        }
+        let width = f32::ceil(span.x.max(0_f32) / cell) as usize + 1;
+        let height = f32::ceil(span.y.max(0_f32) / cell) as usize + 1;
+        let mut values = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let p = Vec2::new(min.x + x as f32 * cell, min.y + y as f32 * cell);
+                values.push(distance(&p));
+            }
+        }
+        Some(SdfGrid {
+            origin: min,
+            cell,
+            width,
+            height,
+            values,
+        })
+    }
+
+    /// Field of a `width` by `height` mask of cells `cell` units apart, row-major with row 0
+    /// at `origin.y`, where `inside[i]` marks cells in the shape. The shape's edge lies halfway
+    /// between inside and outside cells.
+    pub fn from_mask(
+        origin: Vec2,
+        cell: f32,
+        width: usize,
+        height: usize,
+        inside: &[bool],
+    ) -> Self {
+        let to_inside = squared_distances(width, height, |i| inside[i]);
+        let to_outside = squared_distances(width, height, |i| !inside[i]);
+        let values = (0..width * height)
+            .map(|i| {
+                let d = if inside[i] {
+                    0.5_f32 - to_outside[i].sqrt() as f32
+                } else {
+                    to_inside[i].sqrt() as f32 - 0.5_f32
+                };
+                d * cell
+            })
+            .collect();
+        SdfGrid {
+            origin,
+            cell,
+            width,
+            height,
+            values,
+        }
+    }
+
+    pub fn min(&self) -> Vec2 {
+        self.origin.clone()
+    }
+
+    pub fn max(&self) -> Vec2 {
+        let size = Vec2::new(
+            (self.width.max(1) - 1) as f32 * self.cell,
+            (self.height.max(1) - 1) as f32 * self.cell,
+        );
+        self.origin.clone() + size
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min() + self.max()) / 2_f32
+    }
+
+    /// Distance from the center to a corner of the grid.
+    pub fn extent(&self) -> f32 {
+        (self.max() - self.center()).len()
+    }
+
+    fn value(&self, x: usize, y: usize) -> f32 {
+        self.values[y * self.width + x]
+    }
+
+    /// Signed distance at `p`. Outside the grid it grows with the distance to the grid's edge.
+    pub fn distance(&self, p: &Vec2) -> f32 {
+        if self.values.is_empty() {
+            return f32::INFINITY;
+        }
+        let (min, max) = (self.min(), self.max());
+        let q = Vec2::new(p.x.clamp(min.x, max.x), p.y.clamp(min.y, max.y));
+        let outside = (p.clone() - q.clone()).len();
+
+        let fx = (q.x - min.x) / self.cell;
+        let fy = (q.y - min.y) / self.cell;
+        let x0 = (fx.floor() as usize).min(self.width - 1);
+        let y0 = (fy.floor() as usize).min(self.height - 1);
+        let x1 = (x0 + 1).min(self.width - 1);
+        let y1 = (y0 + 1).min(self.height - 1);
+        let (tx, ty) = (fx - x0 as f32, fy - y0 as f32);
+        let bottom = self.value(x0, y0) * (1_f32 - tx) + self.value(x1, y0) * tx;
+        let top = self.value(x0, y1) * (1_f32 - tx) + self.value(x1, y1) * tx;
+        bottom * (1_f32 - ty) + top * ty + outside
+    }
+
+    /// Unit direction of steepest increase at `p`, i.e. pointing out of the shape, or `None`
+    /// where the field is flat.
+    pub fn gradient(&self, p: &Vec2) -> Option<Vec2> {
+        let h = 0.5_f32 * self.cell;
+        let dx = self.distance(&Vec2::new(p.x + h, p.y)) - self.distance(&Vec2::new(p.x - h, p.y));
+        let dy = self.distance(&Vec2::new(p.x, p.y + h)) - self.distance(&Vec2::new(p.x, p.y - h));
+        let mut g = Vec2::new(dx, dy);
+        if g.len() <= f32::EPSILON {
+            return None;
+        }
+        g.normalize();
+        Some(g)
+    }
+
+    /// Distance along the unit ray at which it comes within `offset` of the shape, found by
+    /// sphere tracing. `None` if the ray starts that close already or misses within `max_dist`.
+    pub fn march(&self, origin: &Vec2, dir: &Vec2, offset: f32, max_dist: f32) -> Option<f32> {
+        const MAX_STEPS: usize = 64;
+        let tolerance = 0.01_f32 * self.cell;
+        if self.distance(origin) - offset <= tolerance {
+            return None;
+        }
+        let mut t = 0_f32;
+        for _ in 0..MAX_STEPS {
+            let d = self.distance(&(origin.clone() + dir.clone() * t)) - offset;
+            if d <= tolerance {
+                return Some(t);
+            }
+            t += d;
+            if t > max_dist {
+                return None;
+            }
+        }
+        None
+    }
+
+    /// Centers of the cells whose samples are all inside the shape.
+    pub fn inside_cells(&self) -> impl Iterator<Item = Vec2> + '_ {
+        let (w, h) = (self.width.saturating_sub(1), self.height.saturating_sub(1));
+        (0..h).flat_map(move |y| {
+            (0..w).filter_map(move |x| {
+                let inside = self.value(x, y) < 0_f32
+                    && self.value(x + 1, y) < 0_f32
+                    && self.value(x, y + 1) < 0_f32
+                    && self.value(x + 1, y + 1) < 0_f32;
+                inside.then(|| {
+                    Vec2::new(
+                        self.origin.x + (x as f32 + 0.5_f32) * self.cell,
+                        self.origin.y + (y as f32 + 0.5_f32) * self.cell,
+                    )
+                })
+            })
+        })
+    }
+}
+
+/// Squared distance, in cells, from every cell of the grid to the nearest cell where `target`
+/// holds, by separable exact distance transforms over columns and then rows.
+fn squared_distances(width: usize, height: usize, target: impl Fn(usize) -> bool) -> Vec<f64> {
+    let mut grid: Vec<f64> = (0..width * height)
+        .map(|i| if target(i) { 0_f64 } else { FAR })
+        .collect();
+    let mut line = Vec::new();
+    for x in 0..width {
+        line.clear();
+        line.extend((0..height).map(|y| grid[y * width + x]));
+        for (y, d) in transform_1d(&line).into_iter().enumerate() {
+            grid[y * width + x] = d;
+        }
+    }
+    for y in 0..height {
+        let row = &mut grid[y * width..(y + 1) * width];
+        let transformed = transform_1d(row);
+        row.copy_from_slice(&transformed);
+    }
+    grid
+}
+
+/// Stands in for infinity, finite so the parabola intersections below stay well defined.
+const FAR: f64 = 1e20_f64;
+
+/// Lower envelope of the parabolas `(q - p)^2 + f[p]`, i.e. the 1D squared distance transform
+/// (Felzenszwalb and Huttenlocher).
+fn transform_1d(f: &[f64]) -> Vec<f64> {
+    let n = f.len();
+    let mut d = vec![0_f64; n];
+    if n == 0 {
+        return d;
+    }
+    let mut v = vec![0_usize; n];
+    let mut z = vec![0_f64; n + 1];
+    let mut k = 0;
+    z[0] = f64::NEG_INFINITY;
+    z[1] = f64::INFINITY;
+    for q in 1..n {
+        loop {
+            let r = v[k];
+            let (qf, rf) = (q as f64, r as f64);
+            let s = ((f[q] + qf * qf) - (f[r] + rf * rf)) / (2_f64 * (qf - rf));
+            if s <= z[k] {
+                k -= 1;
+                continue;
+            }
+            k += 1;
+            v[k] = q;
+            z[k] = s;
+            z[k + 1] = f64::INFINITY;
+            break;
+        }
+    }
+    k = 0;
+    for (q, out) in d.iter_mut().enumerate() {
+        while z[k + 1] < q as f64 {
+            k += 1;
+        }
+        let r = v[k];
+        let offset = q as f64 - r as f64;
+        *out = offset * offset + f[r];
+    }
+    d
+}
@@ -0,0 +1,160 @@
+use utils::vec::Vec2;
+use wide::{f32x8, CmpGt, CmpLt};
+
+use crate::particle::Particles;
+use crate::solver::{closing_speed, overlaps, COLLISION_MARGIN};
+
+const LANES: usize = 8;
+
+/// Same result as `Particles::update` on every particle, with the arithmetic done eight
+/// particles at a time.
+pub(crate) fn integrate(particles: &mut Particles, dt: f32, global_damping: f32) {
+    let (dt_v, one, zero) = (f32x8::splat(dt), f32x8::splat(1_f32), f32x8::splat(0_f32));
+    let damping_v = f32x8::splat(global_damping);
+    let whole = particles.len() - particles.len() % LANES;
+    for start in (0..whole).step_by(LANES) {
+        let chunk = start..start + LANES;
+        let mut lanes = [[0_f32; LANES]; 7];
+        let columns = particles.pos[chunk.clone()]
+            .iter()
+            .zip(&particles.pos_last[chunk.clone()])
+            .zip(&particles.acc[chunk.clone()])
+            .zip(&particles.damping[chunk]);
+        for (i, (((pos, last), acc), &damping)) in columns.enumerate() {
+            lanes[0][i] = pos.x;
+            lanes[1][i] = pos.y;
+            lanes[2][i] = last.x;
+            lanes[3][i] = last.y;
+            lanes[4][i] = acc.x;
+            lanes[5][i] = acc.y;
+            lanes[6][i] = damping;
+        }
+        let [x, y, last_x, last_y, acc_x, acc_y, damping] = lanes.map(f32x8::new);
+
+        let drag = (one - (damping + damping_v) * dt_v).max(zero);
+        let next_x = x + ((x - last_x) * drag + acc_x * dt_v * dt_v);
+        let next_y = y + ((y - last_y) * drag + acc_y * dt_v * dt_v);
+        let (next_x, next_y) = (next_x.to_array(), next_y.to_array());
+
+        for i in 0..LANES {
+            let k = start + i;
+            particles.age[k] += dt;
+            particles.pos_last[k] = particles.pos[k].clone();
+            if particles.moves(k) {
+                particles.pos[k].x = next_x[i];
+                particles.pos[k].y = next_y[i];
+            }
+            particles.acc[k].x = 0_f32;
+            particles.acc[k].y = 0_f32;
+        }
+    }
+    for i in whole..particles.len() {
+        particles.update(i, dt, global_damping);
+    }
+}
+
+/// Same result as keeping the pairs of `pairs` for which `solver::overlaps` holds, with the
+/// distances worked out eight pairs at a time.
+pub(crate) fn retain_overlapping(particles: &Particles, pairs: &mut Vec<(usize, usize)>) {
+    let margin = f32x8::splat(COLLISION_MARGIN);
+    let (pos, radius) = (&particles.pos[..], &particles.radius[..]);
+    let whole = pairs.len() - pairs.len() % LANES;
+    let mut kept = 0;
+    for start in (0..whole).step_by(LANES) {
+        let mut chunk = [(0, 0); LANES];
+        chunk.copy_from_slice(&pairs[start..start + LANES]);
+        let (x_1, y_1) = (gather(|l| pos[chunk[l].0].x), gather(|l| pos[chunk[l].0].y));
+        let (x_2, y_2) = (gather(|l| pos[chunk[l].1].x), gather(|l| pos[chunk[l].1].y));
+        let (r_1, r_2) = (
+            gather(|l| radius[chunk[l].0]),
+            gather(|l| radius[chunk[l].1]),
+        );
+
+        let (dx, dy) = (x_1 - x_2, y_1 - y_2);
+        let min_dist = r_1 + r_2 + margin;
+        let overlap = (dx * dx + dy * dy).cmp_lt(min_dist * min_dist).move_mask();
+
+        // Every pair is written and only the overlapping ones are kept, with no branch to
+        // mispredict.
+        for (l, &pair) in chunk.iter().enumerate() {
+            pairs[kept] = pair;
+            kept += (overlap >> l & 1) as usize;
+        }
+    }
+    for start in whole..pairs.len() {
+        let (i, k) = pairs[start];
+        if overlaps(particles, i, k) {
+            pairs[kept] = (i, k);
+            kept += 1;
+        }
+    }
+    pairs.truncate(kept);
+}
+
+/// Same result as keeping the pairs of `pairs` for which `solver::overlaps` holds and
+/// pushing the closing speed of each onto `contacts`, with the distances and speeds worked
+/// out eight pairs at a time.
+pub(crate) fn retain_contacts(
+    particles: &Particles,
+    pairs: &mut Vec<(usize, usize)>,
+    contacts: &mut Vec<(usize, usize, f32, Vec2)>,
+) {
+    let (margin, epsilon) = (f32x8::splat(COLLISION_MARGIN), f32x8::splat(f32::EPSILON));
+    let (pos, pos_last, radius) = (
+        &particles.pos[..],
+        &particles.pos_last[..],
+        &particles.radius[..],
+    );
+    let whole = pairs.len() - pairs.len() % LANES;
+    let mut kept = 0;
+    for start in (0..whole).step_by(LANES) {
+        let mut chunk = [(0, 0); LANES];
+        chunk.copy_from_slice(&pairs[start..start + LANES]);
+        let (x_1, y_1) = (gather(|l| pos[chunk[l].0].x), gather(|l| pos[chunk[l].0].y));
+        let (x_2, y_2) = (gather(|l| pos[chunk[l].1].x), gather(|l| pos[chunk[l].1].y));
+        let last_x_1 = gather(|l| pos_last[chunk[l].0].x);
+        let last_y_1 = gather(|l| pos_last[chunk[l].0].y);
+        let last_x_2 = gather(|l| pos_last[chunk[l].1].x);
+        let last_y_2 = gather(|l| pos_last[chunk[l].1].y);
+        let (r_1, r_2) = (
+            gather(|l| radius[chunk[l].0]),
+            gather(|l| radius[chunk[l].1]),
+        );
+
+        let (dx, dy) = (x_1 - x_2, y_1 - y_2);
+        let dist2 = dx * dx + dy * dy;
+        let dist = dist2.sqrt();
+        let min_dist = r_1 + r_2 + margin;
+        let overlap = dist2.cmp_lt(min_dist * min_dist).move_mask();
+        let touching = (dist.cmp_lt(min_dist) & dist.cmp_gt(epsilon)).move_mask();
+        let v_rel_x = (x_1 - last_x_1) - (x_2 - last_x_2);
+        let v_rel_y = (y_1 - last_y_1) - (y_2 - last_y_2);
+        let vn = ((v_rel_x * dx + v_rel_y * dy) / dist).to_array();
+
+        for (l, &(i, k)) in chunk.iter().enumerate() {
+            if overlap & (1 << l) != 0 {
+                pairs[kept] = (i, k);
+                kept += 1;
+                if touching & (1 << l) != 0 {
+                    contacts.push((i, k, vn[l], Vec2::zero()));
+                }
+            }
+        }
+    }
+    for start in whole..pairs.len() {
+        let (i, k) = pairs[start];
+        if overlaps(particles, i, k) {
+            pairs[kept] = (i, k);
+            kept += 1;
+            if let Some(vn) = closing_speed(particles, i, k, &Vec2::zero()) {
+                contacts.push((i, k, vn, Vec2::zero()));
+            }
+        }
+    }
+    pairs.truncate(kept);
+}
+
+/// Lane `l` set to `value(l)`.
+fn gather(value: impl Fn(usize) -> f32) -> f32x8 {
+    f32x8::new(std::array::from_fn(value))
+}
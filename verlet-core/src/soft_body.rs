@@ -0,0 +1,56 @@
+use utils::vec::Vec2;
+
+use crate::color::{self, Color};
+use crate::particle::Particles;
+
+/// Closed ring of linked particles with an internal pressure that pushes back towards the
+/// ring's rest area, so the blob deforms on impact and springs back.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SoftBody {
+    /// Ring particles in counter-clockwise order.
+    pub particles: Vec<usize>,
+    pub rest_area: f32,
+    /// Outward force per unit of edge length when the area is fully collapsed.
+    pub pressure: f32,
+    pub color: Color,
+}
+
+impl SoftBody {
+    pub fn new(particles: Vec<usize>, rest_area: f32, pressure: f32) -> Self {
+        SoftBody {
+            particles,
+            rest_area,
+            pressure,
+            color: color::STEELBLUE,
+        }
+    }
+
+    pub fn area(&self, particles: &Particles) -> f32 {
+        let n = self.particles.len();
+        let twice: f32 = (0..n)
+            .map(|i| {
+                let a = &particles.pos[self.particles[i]];
+                let b = &particles.pos[self.particles[(i + 1) % n]];
+                a.x * b.y - b.x * a.y
+            })
+            .sum();
+        0.5_f32 * twice
+    }
+
+    pub fn apply_pressure(&self, particles: &mut Particles) {
+        let n = self.particles.len();
+        if n < 3 || self.rest_area <= 0_f32 {
+            return;
+        }
+        let p = self.pressure * (self.rest_area - self.area(particles)) / self.rest_area;
+        for i in 0..n {
+            let (ia, ib) = (self.particles[i], self.particles[(i + 1) % n]);
+            let edge = particles.pos[ib].clone() - particles.pos[ia].clone();
+            // Outward normal of a counter-clockwise edge, scaled by the edge length.
+            let force = Vec2::new(edge.y, -edge.x) * (0.5_f32 * p);
+            particles.apply_force(ia, force.clone());
+            particles.apply_force(ib, force);
+        }
+    }
+}
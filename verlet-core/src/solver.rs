@@ -0,0 +1,755 @@
+use std::collections::{BTreeMap, HashSet};
+#[cfg(any(feature = "parallel", feature = "gpu"))]
+use std::sync::Arc;
+#[cfg(feature = "gpu")]
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use utils::vec::Vec2;
+
+#[cfg(feature = "serde")]
+use crate::broadphase::BroadphaseKind;
+use crate::broadphase::{Aabb, Broadphase, SpatialGrid};
+#[cfg(feature = "gpu")]
+use crate::gpu::GpuSolver;
+use crate::particle::Particles;
+use crate::periodic::Periodic;
+
+/// Extra separation kept between touching particles.
+pub const COLLISION_MARGIN: f32 = 2_f32;
+
+/// Particles moving less than `threshold` per step for `steps` consecutive steps fall asleep;
+/// a sleeper is woken when hit by a particle moving more than `wake_threshold` per step
+/// relative to it. The wake threshold has to exceed what gravity adds in one step, or resting
+/// contacts would keep waking each other.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sleep {
+    pub threshold: f32,
+    pub wake_threshold: f32,
+    pub steps: u32,
+    /// Puts whole islands of touching or linked particles to sleep at once, when every member
+    /// has been still for `steps`, instead of each particle on its own. Stops a settling pile
+    /// from freezing bottom first while the top still slides.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub islands: bool,
+}
+
+impl Default for Sleep {
+    fn default() -> Self {
+        Sleep {
+            threshold: 0.05_f32,
+            wake_threshold: 1_f32,
+            steps: 60,
+            islands: false,
+        }
+    }
+}
+
+/// How contacts and links are pushed back to valid positions.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Projection {
+    /// Contacts move `response_coef` of the way apart and links snap to their length each
+    /// pass, so how stiff they end up depends on the step size and substep count.
+    #[default]
+    Relaxation,
+    /// Extended position-based dynamics. Contacts and links have a compliance, the inverse of
+    /// their stiffness, and behave the same at any step size; zero compliance is rigid.
+    /// Contacts are then always solved on the CPU.
+    Xpbd { contact_compliance: f32 },
+}
+
+impl Projection {
+    /// Fraction of a contact's overlap to correct per pass, given both particles' inverse
+    /// masses.
+    fn contact_response(&self, response_coef: f32, inv_mass: f32, dt: f32) -> f32 {
+        match self {
+            Projection::Relaxation => 0.5_f32 * response_coef,
+            Projection::Xpbd { contact_compliance } => {
+                inv_mass / (inv_mass + contact_compliance / (dt * dt))
+            }
+        }
+    }
+}
+
+/// Serializes as its settings. The thread pool is rebuilt on load, the broadphase index on the
+/// next step.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "SolverSettings", into = "SolverSettings")
+)]
+pub struct Solver {
+    /// Only used by `Projection::Relaxation`.
+    pub response_coef: f32,
+    pub projection: Projection,
+    /// Sleeping is off unless set.
+    pub sleep: Option<Sleep>,
+    /// Fraction of the way touching particles move towards their common temperature per
+    /// contact, in `[0, 1]`. Heat doesn't flow on the GPU path.
+    pub conductivity: f32,
+    /// With a thread pool, hands whole islands of touching particles to the workers instead
+    /// of stripes. Faster for many separate clumps, slower for one big pile, which ends up on
+    /// a single thread.
+    pub split_islands: bool,
+    /// Fraction of each contact's correction from the previous substep applied up front by
+    /// `warm_start_contacts`, in `[0, 1]`. Resting stacks then start close to their solution,
+    /// so they settle with fewer solver iterations and jitter less. Off at `0`.
+    pub warm_start: f32,
+    broadphase: Box<dyn Broadphase>,
+    /// Correction each pair received along its normal during the current substep, and during
+    /// the previous one for warm starting. Seam contacts of a periodic world aren't cached.
+    /// Ordered, so warm starting applies the pushes in the same order on every run.
+    contact_cache: BTreeMap<(usize, usize), f32>,
+    warm_cache: BTreeMap<(usize, usize), f32>,
+    /// Pairs that touched this substep with their closing speed before any correction and
+    /// the periodic image shift of the second particle, for `solve_velocities`.
+    contacts: Vec<(usize, usize, f32, Vec2)>,
+    /// Time spent finding candidate pairs, accumulated while set.
+    pub(crate) broadphase_time: Option<Duration>,
+    #[cfg(feature = "parallel")]
+    thread_pool: Option<Arc<rayon::ThreadPool>>,
+    /// When set, replaces the CPU contact solve where `gpu_applies`.
+    #[cfg(feature = "gpu")]
+    gpu: Option<Arc<Mutex<GpuSolver>>>,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Solver {
+            response_coef: 0.8_f32,
+            projection: Projection::Relaxation,
+            sleep: None,
+            conductivity: 0_f32,
+            split_islands: false,
+            warm_start: 0_f32,
+            broadphase: Box::new(SpatialGrid::auto()),
+            contact_cache: BTreeMap::new(),
+            warm_cache: BTreeMap::new(),
+            contacts: Vec::new(),
+            broadphase_time: None,
+            #[cfg(feature = "parallel")]
+            thread_pool: None,
+            #[cfg(feature = "gpu")]
+            gpu: None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SolverSettings {
+    response_coef: f32,
+    #[serde(default)]
+    projection: Projection,
+    sleep: Option<Sleep>,
+    #[serde(default)]
+    conductivity: f32,
+    #[serde(default)]
+    split_islands: bool,
+    #[serde(default)]
+    warm_start: f32,
+    /// `None` for custom broadphases, which load as the default grid.
+    broadphase: Option<BroadphaseKind>,
+    threads: usize,
+}
+
+#[cfg(feature = "serde")]
+impl From<Solver> for SolverSettings {
+    fn from(solver: Solver) -> Self {
+        #[cfg(feature = "parallel")]
+        let threads = solver.thread_count();
+        #[cfg(not(feature = "parallel"))]
+        let threads = 1;
+        SolverSettings {
+            response_coef: solver.response_coef,
+            projection: solver.projection,
+            broadphase: solver.broadphase.kind(),
+            sleep: solver.sleep,
+            conductivity: solver.conductivity,
+            split_islands: solver.split_islands,
+            warm_start: solver.warm_start,
+            threads,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<SolverSettings> for Solver {
+    fn from(settings: SolverSettings) -> Self {
+        let mut solver = Solver {
+            response_coef: settings.response_coef,
+            projection: settings.projection,
+            sleep: settings.sleep,
+            conductivity: settings.conductivity,
+            split_islands: settings.split_islands,
+            warm_start: settings.warm_start,
+            ..Solver::default()
+        };
+        if let Some(kind) = settings.broadphase {
+            solver.broadphase = kind.build();
+        }
+        #[cfg(feature = "parallel")]
+        solver.set_thread_count(settings.threads);
+        solver
+    }
+}
+
+impl Solver {
+    pub fn broadphase(&self) -> &dyn Broadphase {
+        self.broadphase.as_ref()
+    }
+
+    pub fn set_broadphase(&mut self, broadphase: Box<dyn Broadphase>) {
+        self.broadphase = broadphase;
+    }
+
+    /// Candidate contact pairs `(i, k)` with `i < k` from the broadphase.
+    pub fn pairs(&mut self, particles: &Particles) -> Vec<(usize, usize)> {
+        self.broadphase.pairs(particles)
+    }
+
+    /// Rebuilds the broadphase index so spatial queries see the latest positions.
+    pub fn refresh_index(&mut self, particles: &Particles) {
+        self.broadphase.update(particles);
+    }
+
+    /// Number of threads used to resolve collisions; `0` or `1` keeps the serial path.
+    #[cfg(feature = "parallel")]
+    pub fn thread_count(&self) -> usize {
+        self.thread_pool
+            .as_ref()
+            .map(|p| p.current_num_threads())
+            .unwrap_or(1)
+    }
+
+    #[cfg(feature = "parallel")]
+    pub fn set_thread_count(&mut self, threads: usize) {
+        self.thread_pool = if threads > 1 {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .ok()
+                .map(Arc::new)
+        } else {
+            None
+        };
+    }
+
+    /// Moves collision resolution to the GPU, or back to the CPU with `None`. Clones of the
+    /// solver share the device.
+    ///
+    /// The GPU only pushes overlapping pairs apart by `response_coef`, skipping `excluded`
+    /// pairs and honouring layers and masks. Substeps needing more than that are solved on
+    /// the CPU instead, see `gpu_applies`. Even where it applies, the GPU records no contacts,
+    /// so `solve_velocities` doesn't stop slow pairs against each other, and it doesn't add to
+    /// the broadphase time in step timings.
+    #[cfg(feature = "gpu")]
+    pub fn set_gpu(&mut self, gpu: Option<GpuSolver>) {
+        self.gpu = gpu.map(|g| Arc::new(Mutex::new(g)));
+    }
+
+    #[cfg(feature = "gpu")]
+    pub fn uses_gpu(&self) -> bool {
+        self.gpu.is_some()
+    }
+
+    /// Whether a GPU solve of `particles` would match the CPU one. It doesn't with
+    /// restitution or friction on any particle, heat conduction, sleep, SPH fluids, XPBD
+    /// projection or warm starting, none of which the GPU implements.
+    #[cfg(feature = "gpu")]
+    pub fn gpu_applies(&self, particles: &Particles) -> bool {
+        self.projection == Projection::Relaxation
+            && self.conductivity <= 0_f32
+            && self.sleep.is_none()
+            && self.warm_start <= 0_f32
+            && particles.restitution.iter().all(|&r| r == 0_f32)
+            && particles.static_friction.iter().all(|&f| f == 0_f32)
+            && particles.kinetic_friction.iter().all(|&f| f == 0_f32)
+            && particles.sph_fluid.iter().all(Option::is_none)
+    }
+
+    /// Resolves contacts between all candidate pairs except those in `excluded`, given as
+    /// `(i, k)` with `i < k`, in a substep of `dt`.
+    pub fn solve_collisions(
+        &mut self,
+        particles: &mut Particles,
+        excluded: &HashSet<(usize, usize)>,
+        dt: f32,
+    ) {
+        #[cfg(feature = "gpu")]
+        if let Some(gpu) = self.gpu.as_ref().filter(|_| self.gpu_applies(particles)) {
+            let mut gpu = gpu.lock().unwrap_or_else(|e| e.into_inner());
+            gpu.solve_collisions(particles, excluded, self.response_coef, 1);
+            return;
+        }
+        let start = self.broadphase_time.map(|_| Instant::now());
+        let mut pairs = self.broadphase.pairs(particles);
+        if let (Some(time), Some(start)) = (&mut self.broadphase_time, start) {
+            *time += start.elapsed();
+        }
+        pairs.retain(|&(i, k)| needs_contact(particles, i, k) && !excluded.contains(&(i, k)));
+        // Most candidates are only neighbours, so they're dropped before any is resolved.
+        #[cfg(feature = "simd")]
+        crate::simd::retain_contacts(particles, &mut pairs, &mut self.contacts);
+        #[cfg(not(feature = "simd"))]
+        {
+            retain_overlapping(particles, &mut pairs);
+            for &(i, k) in pairs.iter() {
+                if let Some(vn) = closing_speed(particles, i, k, &Vec2::zero()) {
+                    self.contacts.push((i, k, vn, Vec2::zero()));
+                }
+            }
+        }
+        let response = Response {
+            projection: &self.projection,
+            response_coef: self.response_coef,
+            dt,
+        };
+        let wake_threshold = self.sleep.as_ref().map_or(0_f32, |s| s.wake_threshold);
+        let conductivity = self.conductivity;
+        let warm = self.warm_start > 0_f32;
+        let resolve = |a: &mut Body, b: &mut Body| {
+            let delta = resolve_contact(&response, wake_threshold, conductivity, a, b);
+            if warm {
+                delta
+            } else {
+                0_f32
+            }
+        };
+
+        #[cfg(feature = "parallel")]
+        if let Some(pool) = &self.thread_pool {
+            let corrections = if self.split_islands {
+                pool.install(|| crate::parallel::solve_islands(particles, pairs, resolve))
+            } else {
+                let stripe_width = 2_f32 * particles.max_radius() + COLLISION_MARGIN;
+                pool.install(|| {
+                    crate::parallel::solve_striped(particles, pairs, stripe_width, resolve)
+                })
+            };
+            for (i, k, delta) in corrections {
+                cache_correction(&mut self.contact_cache, i, k, delta);
+            }
+            return;
+        }
+
+        for (i, k) in pairs {
+            let delta = resolve_pair(particles, i, k, resolve);
+            cache_correction(&mut self.contact_cache, i, k, delta);
+        }
+    }
+
+    /// Starts a substep by pushing still overlapping pairs apart by `warm_start` of the
+    /// correction they received over the previous substep, at most until they just touch.
+    /// The solver passes that follow then only have to fix what changed.
+    pub fn warm_start_contacts(&mut self, particles: &mut Particles) {
+        std::mem::swap(&mut self.contact_cache, &mut self.warm_cache);
+        self.contact_cache.clear();
+        if self.warm_start <= 0_f32 {
+            self.warm_cache.clear();
+            return;
+        }
+        for (&(i, k), &delta) in self.warm_cache.iter() {
+            if k >= particles.len() {
+                continue;
+            }
+            let v = &particles.pos[i] - &particles.pos[k];
+            let dist = v.len();
+            let min_dist = particles.radius[i] + particles.radius[k] + COLLISION_MARGIN;
+            if dist >= min_dist || dist <= f32::EPSILON {
+                continue;
+            }
+            // Both are negative; the larger one is the smaller push.
+            let delta = f32::max(self.warm_start * delta, dist - min_dist);
+            let (mut o_1, mut o_2) = (Body::load(particles, i), Body::load(particles, k));
+            separate(&mut o_1, &mut o_2, &(v / dist), delta);
+            o_1.store(particles, i);
+            o_2.store(particles, k);
+        }
+    }
+
+    /// Forgets cached contacts, e.g. after particles were removed or reordered and the
+    /// cached indices point at different particles.
+    pub fn clear_contact_cache(&mut self) {
+        self.contact_cache.clear();
+        self.warm_cache.clear();
+    }
+
+    /// Resolves contacts reaching across the seams of `periodic`, skipping `excluded` pairs as
+    /// `solve_collisions` does. Each particle near an edge is looked up at its images beyond
+    /// the opposite edges in the broadphase index, so this has to run after `solve_collisions`
+    /// rebuilt it.
+    pub fn solve_periodic_collisions(
+        &mut self,
+        particles: &mut Particles,
+        periodic: &Periodic,
+        excluded: &HashSet<(usize, usize)>,
+        dt: f32,
+    ) {
+        let reach = 2_f32 * particles.max_radius() + COLLISION_MARGIN;
+        // `(i, k, shift)` with `i < k`, where `shift` moves `k` to its image next to `i`.
+        let mut pairs = Vec::new();
+        let mut near = Vec::new();
+        for (i, pos) in particles.pos.iter().enumerate() {
+            for shift in periodic.image_shifts(pos, reach) {
+                let image = pos + &shift;
+                near.clear();
+                self.broadphase
+                    .query(particles, &Aabb::around(&image, reach), &mut near);
+                for &k in near.iter().filter(|&&k| k != i) {
+                    if !particles.collides(i, k) {
+                        continue;
+                    }
+                    pairs.push(if i < k {
+                        (i, k, &shift * -1_f32)
+                    } else {
+                        (k, i, shift.clone())
+                    });
+                }
+            }
+        }
+        pairs.sort_by_key(|&(i, k, _)| (i, k));
+        pairs.dedup_by_key(|&mut (i, k, _)| (i, k));
+        pairs.retain(|&(i, k, _)| needs_contact(particles, i, k) && !excluded.contains(&(i, k)));
+
+        let response = Response {
+            projection: &self.projection,
+            response_coef: self.response_coef,
+            dt,
+        };
+        let wake_threshold = self.sleep.as_ref().map_or(0_f32, |s| s.wake_threshold);
+        for (i, k, shift) in pairs {
+            if let Some(vn) = closing_speed(particles, i, k, &shift) {
+                self.contacts.push((i, k, vn, shift.clone()));
+            }
+            let (mut o_1, mut o_2) = (Body::load(particles, i), Body::load(particles, k));
+            o_2.pos += &shift;
+            o_2.pos_last += &shift;
+            resolve_contact(
+                &response,
+                wake_threshold,
+                self.conductivity,
+                &mut o_1,
+                &mut o_2,
+            );
+            o_2.pos -= &shift;
+            o_2.pos_last -= shift;
+            o_1.store(particles, i);
+            o_2.store(particles, k);
+        }
+    }
+
+    /// Sets the normal velocity of every pair that touched since the last call, after the
+    /// position passes: pairs that hit faster per step than the larger `rest_speed` of the two
+    /// rebound at their restitution times the closing speed, slower ones stop against each
+    /// other. The change is split by inverse mass, so momentum is conserved and heavy particles
+    /// plow through light ones. Contacts resolved on the GPU aren't recorded.
+    pub fn solve_velocities(
+        &mut self,
+        particles: &mut Particles,
+        rest_speed: impl Fn(&Particles, usize) -> f32,
+    ) {
+        // A pair can be recorded once per iteration; its first closing speed is the real one.
+        self.contacts.sort_by_key(|&(i, k, ..)| (i, k));
+        self.contacts.dedup_by_key(|&mut (i, k, ..)| (i, k));
+        for (i, k, closing, shift) in self.contacts.drain(..) {
+            let (inv_mass_1, inv_mass_2) = (particles.inv_mass(i), particles.inv_mass(k));
+            let inv_mass = inv_mass_1 + inv_mass_2;
+            let v = &particles.pos[i] - &(&particles.pos[k] + &shift);
+            let dist = v.len();
+            if inv_mass == 0_f32 || dist <= f32::EPSILON {
+                continue;
+            }
+            let n = v / dist;
+            let v_rel = step(particles, i) - step(particles, k);
+            let rest_speed = f32::max(rest_speed(particles, i), rest_speed(particles, k));
+            let target = if closing < -rest_speed {
+                -0.5_f32 * (particles.restitution[i] + particles.restitution[k]) * closing
+            } else {
+                0_f32
+            };
+            let change = target - v_rel.dot(&n);
+            particles.pos_last[i] -= &n * (change * inv_mass_1 / inv_mass);
+            particles.pos_last[k] += n * (change * inv_mass_2 / inv_mass);
+        }
+    }
+
+    /// Puts particles that stayed still long enough to sleep. Runs after the position solvers
+    /// and before integration, so `pos - pos_last` is the net motion over the last step.
+    pub fn update_sleep(&self, particles: &mut Particles) {
+        let Some(sleep) = &self.sleep else {
+            return;
+        };
+        for i in 0..particles.len() {
+            if !particles.moves(i) {
+                continue;
+            }
+            if step(particles, i).len() < sleep.threshold {
+                particles.still_steps[i] += 1;
+                if particles.still_steps[i] >= sleep.steps {
+                    particles.asleep[i] = true;
+                    particles.pos_last[i] = particles.pos[i].clone();
+                }
+            } else {
+                particles.still_steps[i] = 0;
+            }
+        }
+    }
+
+    /// Like `update_sleep`, but an island only falls asleep once all its members have been
+    /// still for `steps`, and then all at once.
+    pub fn update_island_sleep(&self, particles: &mut Particles, islands: &[Vec<usize>]) {
+        let Some(sleep) = &self.sleep else {
+            return;
+        };
+        for island in islands {
+            let mut settled = true;
+            for &i in island {
+                if particles.asleep[i] {
+                    continue;
+                }
+                if step(particles, i).len() < sleep.threshold {
+                    particles.still_steps[i] += 1;
+                } else {
+                    particles.still_steps[i] = 0;
+                }
+                settled &= particles.still_steps[i] >= sleep.steps;
+            }
+            if settled {
+                for &i in island {
+                    if !particles.asleep[i] {
+                        particles.asleep[i] = true;
+                        particles.pos_last[i] = particles.pos[i].clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// What `resolve_contact` needs to size its correction.
+struct Response<'a> {
+    projection: &'a Projection,
+    response_coef: f32,
+    dt: f32,
+}
+
+/// Adds a pair's correction to the warm-start cache. Both the serial and the threaded paths
+/// go through here, so they leave the same entries behind.
+fn cache_correction(cache: &mut BTreeMap<(usize, usize), f32>, i: usize, k: usize, delta: f32) {
+    if delta != 0_f32 {
+        *cache.entry((i, k)).or_default() += delta;
+    }
+}
+
+/// The fields of one particle the contact solve reads and writes. Each pair is copied out of
+/// the columns and back, so the serial and threaded paths share `resolve_contact`.
+pub(crate) struct Body {
+    pub(crate) pos: Vec2,
+    pub(crate) pos_last: Vec2,
+    pub(crate) radius: f32,
+    pub(crate) mass: f32,
+    pub(crate) static_friction: f32,
+    pub(crate) kinetic_friction: f32,
+    pub(crate) temperature: f32,
+    pub(crate) pinned: bool,
+    pub(crate) asleep: bool,
+    pub(crate) still_steps: u32,
+}
+
+impl Body {
+    pub(crate) fn load(particles: &Particles, i: usize) -> Self {
+        Body {
+            pos: particles.pos[i].clone(),
+            pos_last: particles.pos_last[i].clone(),
+            radius: particles.radius[i],
+            mass: particles.mass[i],
+            static_friction: particles.static_friction[i],
+            kinetic_friction: particles.kinetic_friction[i],
+            temperature: particles.temperature[i],
+            pinned: particles.pinned[i],
+            asleep: particles.asleep[i],
+            still_steps: particles.still_steps[i],
+        }
+    }
+
+    /// Writes back the fields a contact changes.
+    pub(crate) fn store(self, particles: &mut Particles, i: usize) {
+        particles.pos[i] = self.pos;
+        particles.pos_last[i] = self.pos_last;
+        particles.temperature[i] = self.temperature;
+        particles.asleep[i] = self.asleep;
+        particles.still_steps[i] = self.still_steps;
+    }
+
+    fn inv_mass(&self) -> f32 {
+        if self.pinned || self.asleep {
+            0_f32
+        } else {
+            1_f32 / self.mass
+        }
+    }
+
+    fn wake(&mut self) {
+        self.asleep = false;
+        self.still_steps = 0;
+    }
+}
+
+/// Runs `resolve` on pair `(i, k)` of `particles`.
+pub(crate) fn resolve_pair(
+    particles: &mut Particles,
+    i: usize,
+    k: usize,
+    resolve: impl Fn(&mut Body, &mut Body) -> f32,
+) -> f32 {
+    let (mut o_1, mut o_2) = (Body::load(particles, i), Body::load(particles, k));
+    let result = resolve(&mut o_1, &mut o_2);
+    o_1.store(particles, i);
+    o_2.store(particles, k);
+    result
+}
+
+/// Returns the correction applied along the contact normal, negative while the pair
+/// overlapped and `0` if it didn't.
+fn resolve_contact(
+    response: &Response,
+    wake_threshold: f32,
+    conductivity: f32,
+    o_1: &mut Body,
+    o_2: &mut Body,
+) -> f32 {
+    let v = &o_1.pos - &o_2.pos;
+    let dist2 = v.x * v.x + v.y * v.y;
+    let min_dist = o_1.radius + o_2.radius + COLLISION_MARGIN;
+    if dist2 < min_dist * min_dist {
+        let dist = f32::sqrt(dist2);
+        // Coincident particles have no contact normal; separate them vertically.
+        let n = if dist > f32::EPSILON {
+            v / dist
+        } else {
+            Vec2::new(0_f32, 1_f32)
+        };
+        let v_rel = (&o_1.pos - &o_1.pos_last) - (&o_2.pos - &o_2.pos_last);
+        if o_1.asleep != o_2.asleep && v_rel.len() > wake_threshold {
+            o_1.wake();
+            o_2.wake();
+        }
+        if conductivity > 0_f32 {
+            exchange_heat(conductivity, o_1, o_2);
+        }
+
+        let inv_mass = o_1.inv_mass() + o_2.inv_mass();
+        let Some((mass_ratio_1, mass_ratio_2)) = mass_ratios(o_1, o_2) else {
+            return 0_f32;
+        };
+        let delta =
+            response
+                .projection
+                .contact_response(response.response_coef, inv_mass, response.dt)
+                * (dist - min_dist);
+        separate(o_1, o_2, &n, delta);
+
+        let vn = v_rel.dot(&n);
+
+        let static_friction = 0.5_f32 * (o_1.static_friction + o_2.static_friction);
+        let kinetic_friction = 0.5_f32 * (o_1.kinetic_friction + o_2.kinetic_friction);
+        let v_t = v_rel - n * vn;
+        let slip = v_t.len();
+        let depth = min_dist - dist;
+        if slip > 0_f32 && (static_friction > 0_f32 || kinetic_friction > 0_f32) {
+            let correction = if slip < static_friction * depth {
+                v_t
+            } else {
+                v_t * f32::min(kinetic_friction * depth / slip, 1_f32)
+            };
+            o_1.pos_last += &correction * mass_ratio_2;
+            o_2.pos_last -= correction * mass_ratio_1;
+        }
+        return delta;
+    }
+    0_f32
+}
+
+/// Shares of a correction taken by `o_2` and `o_1`, by inverse mass; `None` if neither can
+/// move.
+fn mass_ratios(o_1: &Body, o_2: &Body) -> Option<(f32, f32)> {
+    let inv_mass = o_1.inv_mass() + o_2.inv_mass();
+    if inv_mass == 0_f32 {
+        return None;
+    }
+    Some((o_2.inv_mass() / inv_mass, o_1.inv_mass() / inv_mass))
+}
+
+/// Shifts the pair by `delta` along the normal `n` from `o_2` to `o_1`, split by inverse mass;
+/// negative values push them apart.
+fn separate(o_1: &mut Body, o_2: &mut Body, n: &Vec2, delta: f32) {
+    let Some((mass_ratio_1, mass_ratio_2)) = mass_ratios(o_1, o_2) else {
+        return;
+    };
+    o_1.pos -= n * (mass_ratio_2 * delta);
+    o_2.pos += n * (mass_ratio_1 * delta);
+}
+
+/// Whether particles `i` and `k` are closer than their radii plus `COLLISION_MARGIN`, the
+/// test `resolve_contact` starts with.
+pub(crate) fn overlaps(particles: &Particles, i: usize, k: usize) -> bool {
+    let v = &particles.pos[i] - &particles.pos[k];
+    let min_dist = particles.radius[i] + particles.radius[k] + COLLISION_MARGIN;
+    v.x * v.x + v.y * v.y < min_dist * min_dist
+}
+
+/// Keeps the pairs of `pairs` that `overlaps`, in order.
+pub(crate) fn retain_overlapping(particles: &Particles, pairs: &mut Vec<(usize, usize)>) {
+    #[cfg(feature = "simd")]
+    crate::simd::retain_overlapping(particles, pairs);
+    #[cfg(not(feature = "simd"))]
+    pairs.retain(|&(i, k)| overlaps(particles, i, k));
+}
+
+/// Whether a candidate pair is left to the contact solve at all.
+fn needs_contact(particles: &Particles, i: usize, k: usize) -> bool {
+    let sleeping = particles.asleep[i] && particles.asleep[k];
+    // Members of one SPH fluid interact through pressure instead.
+    let fluid =
+        particles.sph_fluid[i].is_some() && particles.sph_fluid[i] == particles.sph_fluid[k];
+    !sleeping && !fluid
+}
+
+/// Relative velocity of two overlapping particles along their contact normal, per step, with
+/// `k` moved by `shift`; negative when they approach.
+pub(crate) fn closing_speed(
+    particles: &Particles,
+    i: usize,
+    k: usize,
+    shift: &Vec2,
+) -> Option<f32> {
+    let v = &particles.pos[i] - &(&particles.pos[k] + shift);
+    let dist = v.len();
+    let min_dist = particles.radius[i] + particles.radius[k] + COLLISION_MARGIN;
+    if dist >= min_dist || dist <= f32::EPSILON {
+        return None;
+    }
+    let v_rel = step(particles, i) - step(particles, k);
+    Some(v_rel.dot(&v) / dist)
+}
+
+/// How far particle `i` moved over the last step.
+fn step(particles: &Particles, i: usize) -> Vec2 {
+    &particles.pos[i] - &particles.pos_last[i]
+}
+
+/// Moves both temperatures `conductivity` of the way to their mass-weighted mean, which
+/// conserves the total heat.
+fn exchange_heat(conductivity: f32, o_1: &mut Body, o_2: &mut Body) {
+    let total = o_1.mass + o_2.mass;
+    if total <= 0_f32 {
+        return;
+    }
+    let mean = (o_1.mass * o_1.temperature + o_2.mass * o_2.temperature) / total;
+    o_1.temperature += conductivity * (mean - o_1.temperature);
+    o_2.temperature += conductivity * (mean - o_2.temperature);
+}
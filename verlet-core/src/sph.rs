@@ -0,0 +1,129 @@
+use std::f32::consts::PI;
+
+use utils::vec::Vec2;
+
+use crate::broadphase::SpatialGrid;
+use crate::particle::Particles;
+
+/// Smoothed-particle hydrodynamics settings for a group of particles. Members push apart when
+/// packed denser than `rest_density` and drag each other's velocities together, which makes
+/// them flow like a liquid; they don't take part in the rigid contact solver among themselves.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SphFluid {
+    /// Reach of the kernels, typically three to four particle radii.
+    pub smoothing_radius: f32,
+    /// Mass per unit area the fluid settles at.
+    pub rest_density: f32,
+    /// Pressure per unit of density above rest, the square of the speed of sound. Higher is
+    /// less compressible but needs smaller steps; the default wants at least two substeps at
+    /// 60 Hz.
+    pub stiffness: f32,
+    /// Kinematic viscosity, in area per second.
+    pub viscosity: f32,
+}
+
+impl SphFluid {
+    pub fn new(smoothing_radius: f32, rest_density: f32) -> Self {
+        SphFluid {
+            smoothing_radius,
+            rest_density,
+            stiffness: 1_000_000_f32,
+            viscosity: 500_f32,
+        }
+    }
+
+    /// Accelerates the members of fluid `index` by their pressure and viscosity forces, with
+    /// velocities taken over a step of `dt`.
+    pub fn apply(&self, index: usize, particles: &mut Particles, dt: f32) {
+        let members: Vec<usize> = (0..particles.len())
+            .filter(|&i| particles.alive[i] && particles.sph_fluid[i] == Some(index))
+            .collect();
+        if members.is_empty() {
+            return;
+        }
+        let h = self.smoothing_radius;
+        let mut grid = SpatialGrid::new(h);
+        for (m, &i) in members.iter().enumerate() {
+            grid.insert(m, &particles.pos[i]);
+        }
+        let neighbours = |m: usize, out: &mut Vec<usize>| {
+            out.clear();
+            let (cx, cy) = grid.cell_of(&particles.pos[members[m]]);
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    out.extend_from_slice(grid.cell((cx + dx, cy + dy)));
+                }
+            }
+        };
+
+        // Müller et al.'s kernels, normalised for two dimensions.
+        let h2 = h * h;
+        let poly6 = 4_f32 / (PI * h2 * h2 * h2 * h2);
+        let spiky_grad = -30_f32 / (PI * h2 * h2 * h);
+        let visc_lap = 40_f32 / (PI * h2 * h2 * h);
+
+        let mut near = Vec::new();
+        let density: Vec<f32> = (0..members.len())
+            .map(|m| {
+                neighbours(m, &mut near);
+                let p = &particles.pos[members[m]];
+                near.iter()
+                    .map(|&n| {
+                        let q = members[n];
+                        let offset = p - &particles.pos[q];
+                        let r2 = offset.dot(&offset);
+                        if r2 < h2 {
+                            let d = h2 - r2;
+                            particles.mass[q] * poly6 * d * d * d
+                        } else {
+                            0_f32
+                        }
+                    })
+                    .sum()
+            })
+            .collect();
+        // No tension: sparse regions don't pull together, which keeps the surface stable.
+        let pressure: Vec<f32> = density
+            .iter()
+            .map(|rho| f32::max(self.stiffness * (rho - self.rest_density), 0_f32))
+            .collect();
+
+        let acc: Vec<Vec2> = (0..members.len())
+            .map(|m| {
+                neighbours(m, &mut near);
+                let p = members[m];
+                let v = particles.velocity(p, dt);
+                let (mut push, mut blend) = (Vec2::zero(), Vec2::zero());
+                for &n in near.iter().filter(|&&n| n != m) {
+                    let q = members[n];
+                    let offset = &particles.pos[p] - &particles.pos[q];
+                    let r = offset.len();
+                    if r >= h || density[n] <= 0_f32 {
+                        continue;
+                    }
+                    let dir = if r > f32::EPSILON {
+                        offset / r
+                    } else {
+                        Vec2::new(0_f32, 1_f32)
+                    };
+                    let shared = 0.5_f32 * (pressure[m] + pressure[n]) / density[n];
+                    let mass = particles.mass[q];
+                    push -= dir * (mass * shared * spiky_grad * (h - r) * (h - r));
+                    let dv = particles.velocity(q, dt) - v.clone();
+                    blend += dv * (mass / density[n] * visc_lap * (h - r));
+                }
+                let push = if density[m] > 0_f32 {
+                    push / density[m]
+                } else {
+                    Vec2::zero()
+                };
+                push + blend * self.viscosity
+            })
+            .collect();
+
+        for (&i, a) in members.iter().zip(acc) {
+            particles.accelerate(i, a);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::link::overstretched;
+use crate::particle::Particles;
+
+/// Hooke spring between particles `a` and `b`, applied as forces rather than positional
+/// corrections, so it stretches and oscillates instead of holding a hard distance.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spring {
+    pub a: usize,
+    pub b: usize,
+    pub rest_length: f32,
+    /// Relative stretch, `(length - rest_length) / rest_length`, beyond which the spring breaks.
+    pub break_threshold: Option<f32>,
+    pub stiffness: f32,
+    pub damping: f32,
+}
+
+impl Spring {
+    pub fn new(a: usize, b: usize, rest_length: f32, stiffness: f32, damping: f32) -> Self {
+        Spring {
+            a,
+            b,
+            rest_length,
+            stiffness,
+            damping,
+            break_threshold: None,
+        }
+    }
+
+    pub fn breakable(mut self, threshold: f32) -> Self {
+        self.break_threshold = Some(threshold);
+        self
+    }
+
+    pub fn is_broken(&self, particles: &Particles) -> bool {
+        overstretched(
+            particles,
+            self.a,
+            self.b,
+            self.rest_length,
+            self.break_threshold,
+        )
+    }
+
+    /// The connected pair as `(lo, hi)` indices.
+    pub fn key(&self) -> (usize, usize) {
+        (self.a.min(self.b), self.a.max(self.b))
+    }
+
+    pub fn apply(&self, particles: &mut Particles, dt: f32) {
+        if self.a == self.b {
+            return;
+        }
+        let v = particles.pos[self.a].clone() - particles.pos[self.b].clone();
+        let dist = v.len();
+        if dist <= f32::EPSILON {
+            return;
+        }
+        let n = v / dist;
+        let v_rel = (particles.velocity(self.a, dt) - particles.velocity(self.b, dt)).dot(&n);
+        let force = n * (-self.stiffness * (dist - self.rest_length) - self.damping * v_rel);
+
+        particles.apply_force(self.a, force.clone());
+        particles.apply_force(self.b, force * -1_f32);
+    }
+}
@@ -0,0 +1,34 @@
+use utils::vec::Vec2;
+
+use crate::particle::Particles;
+
+/// Zero-length spring pulling a particle towards a world point, e.g. the mouse cursor.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tether {
+    pub particle: usize,
+    pub point: Vec2,
+    pub stiffness: f32,
+    pub damping: f32,
+}
+
+impl Tether {
+    pub fn new(particle: usize, point: Vec2, stiffness: f32, damping: f32) -> Self {
+        Tether {
+            particle,
+            point,
+            stiffness,
+            damping,
+        }
+    }
+
+    pub fn apply(&self, particles: &mut Particles, dt: f32) {
+        let i = self.particle;
+        if i >= particles.len() {
+            return;
+        }
+        let stretch = self.point.clone() - particles.pos[i].clone();
+        let force = stretch * self.stiffness - particles.velocity(i, dt) * self.damping;
+        particles.apply_force(i, force);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::particle::Particles;
+
+/// Accumulates frame time and hands it out in fixed `dt` slices, so the simulation behaves the
+/// same regardless of the render frame rate.
+#[derive(Clone, Debug)]
+pub struct FixedTimestep {
+    pub dt: f32,
+    /// Upper bound on steps per frame, so a long stall doesn't snowball into ever longer frames.
+    pub max_steps: usize,
+    /// Runs exactly one step per frame and ignores the frame time, so the simulation never
+    /// depends on the wall clock.
+    pub lockstep: bool,
+    /// Paused timesteps only run the steps requested through `single_step`.
+    pub paused: bool,
+    /// Multiplies frame time before it is accumulated, e.g. `0.1` for slow motion. The step
+    /// `dt` stays fixed, so only the number of steps per frame changes. Ignored in lockstep.
+    pub time_scale: f32,
+    accumulator: f32,
+    pending: usize,
+}
+
+impl FixedTimestep {
+    pub fn new(dt: f32) -> Self {
+        FixedTimestep {
+            dt,
+            max_steps: 8,
+            lockstep: false,
+            paused: false,
+            time_scale: 1_f32,
+            accumulator: 0_f32,
+            pending: 0,
+        }
+    }
+
+    pub fn lockstep(dt: f32) -> Self {
+        FixedTimestep {
+            lockstep: true,
+            ..FixedTimestep::new(dt)
+        }
+    }
+
+    /// Runs one more step on the next `advance` while paused.
+    pub fn single_step(&mut self) {
+        self.pending += 1;
+    }
+
+    /// Adds `frame_dt` to the accumulator and returns how many fixed steps to run.
+    pub fn advance(&mut self, frame_dt: f32) -> usize {
+        if self.paused {
+            return std::mem::take(&mut self.pending);
+        }
+        self.pending = 0;
+        if self.lockstep {
+            return 1;
+        }
+        self.accumulator += frame_dt * self.time_scale;
+        let mut steps = 0;
+        while self.accumulator >= self.dt && steps < self.max_steps {
+            self.accumulator -= self.dt;
+            steps += 1;
+        }
+        if steps == self.max_steps {
+            self.accumulator = f32::min(self.accumulator, self.dt);
+        }
+        steps
+    }
+
+    /// Fraction of a step left in the accumulator, used to interpolate rendered positions.
+    pub fn alpha(&self) -> f32 {
+        if self.lockstep {
+            return 1_f32;
+        }
+        self.accumulator / self.dt
+    }
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        FixedTimestep::new(1_f32 / 60_f32)
+    }
+}
+
+/// Picks the substep count of every step from the fastest particle: enough substeps that no
+/// particle moves more than `max_travel` of its radius in one, within `min..=max`. The count
+/// rises as soon as a step needs it and falls back by one per step as the scene calms down,
+/// so a single fast particle doesn't make it flap. Assumes steps of similar length.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdaptiveSubsteps {
+    pub max_travel: f32,
+    pub min: usize,
+    pub max: usize,
+}
+
+impl Default for AdaptiveSubsteps {
+    fn default() -> Self {
+        AdaptiveSubsteps {
+            max_travel: 0.5_f32,
+            min: 1,
+            max: 16,
+        }
+    }
+}
+
+impl AdaptiveSubsteps {
+    /// Substeps for the next step, given the `current` count the particles last moved at.
+    pub fn substeps(&self, particles: &Particles, current: usize) -> usize {
+        let current = current.max(1);
+        let needed = (0..particles.len())
+            .filter(|&i| particles.moves(i) && particles.radius[i] > 0_f32)
+            .map(|i| {
+                // Distance covered over a whole step at the current substep count.
+                let travel = (&particles.pos[i] - &particles.pos_last[i]).len() * current as f32;
+                f32::ceil(travel / (self.max_travel * particles.radius[i])) as usize
+            })
+            .max()
+            .unwrap_or(0);
+        let target = needed.clamp(self.min.max(1), self.max.max(self.min).max(1));
+        if target < current {
+            current - 1
+        } else {
+            target
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::io::{self, Write};
+
+use crate::world::World;
+
+/// Writes particle trajectories as CSV, one `id,time,x,y,vx,vy` row per particle per step, for
+/// analysis outside the simulation. `id` is `ParticleHandle::to_bits`, so it follows a particle
+/// across removals of others; `time` is `World::time` and velocities are `World::velocity`,
+/// in units per second.
+pub struct TrajectoryWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> TrajectoryWriter<W> {
+    /// Starts the CSV with its header row.
+    pub fn new(mut out: W) -> io::Result<Self> {
+        writeln!(out, "id,time,x,y,vx,vy")?;
+        Ok(TrajectoryWriter { out })
+    }
+
+    /// Appends a row for every particle of `world`, as of its last step.
+    pub fn record(&mut self, world: &World) -> io::Result<()> {
+        for (i, p) in world.particles.pos.iter().enumerate() {
+            let Some(handle) = world.handle(i) else {
+                continue;
+            };
+            let v = world.velocity(i);
+            writeln!(
+                self.out,
+                "{},{},{},{},{},{}",
+                handle.to_bits(),
+                world.time,
+                p.x,
+                p.y,
+                v.x,
+                v.y
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
@@ -0,0 +1,41 @@
+use std::any::Any;
+use std::fmt::Debug;
+
+/// Application data attached to a particle with `World::set_user_data`, such as a game
+/// entity, a label or a score. Any `Clone + Debug + Send + Sync` type qualifies.
+pub trait UserData: Any + Debug + Send + Sync {
+    fn clone_box(&self) -> Box<dyn UserData>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any + Clone + Debug + Send + Sync> UserData for T {
+    fn clone_box(&self) -> Box<dyn UserData> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl Clone for Box<dyn UserData> {
+    fn clone(&self) -> Self {
+        (**self).clone_box()
+    }
+}
+
+impl dyn UserData {
+    /// The data as a `T`, if that's what it is.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+
+    pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut()
+    }
+}
@@ -0,0 +1,1452 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use utils::vec::Vec2;
+
+use crate::attractor::Attractor;
+use crate::bend::Bend;
+use crate::broadphase::Aabb;
+use crate::ccd;
+use crate::constraint::{self, Constraint};
+use crate::emitter::Emitter;
+use crate::event::{BrokenConstraint, Event};
+use crate::fluid::FluidZone;
+use crate::force_field::{ForceField, ForceFn};
+use crate::handle::{Handles, ParticleHandle};
+use crate::heat::HeatSource;
+use crate::integrator::{Integrator, Verlet};
+use crate::island::islands;
+use crate::link::Link;
+use crate::merge::{self, Merging};
+use crate::morton;
+use crate::nbody::NBody;
+use crate::obstacle::Obstacle;
+use crate::particle::{Particle, Particles};
+use crate::periodic::Periodic;
+use crate::pin::Pin;
+use crate::profile::{Clock, StepTimings};
+use crate::query::{self, Hit, HitTarget};
+use crate::rigid::RigidCluster;
+use crate::rotor::Rotor;
+use crate::schedule::SpawnEvent;
+use crate::soft_body::SoftBody;
+use crate::solver::{retain_overlapping, Projection, Solver, COLLISION_MARGIN};
+use crate::sph::SphFluid;
+use crate::spring::Spring;
+use crate::tether::Tether;
+use crate::timestep::AdaptiveSubsteps;
+use crate::user_data::UserData;
+
+/// Smallest scale `World::set_time_scale` accepts.
+const MIN_TIME_SCALE: f32 = 0.01_f32;
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct World {
+    pub particles: Particles,
+    pub gravity: Vec2,
+    /// When set, particles fall towards the attractor instead of along `gravity`.
+    pub attractor: Option<Attractor>,
+    /// Mutual attraction between all particles, on top of the gravity above.
+    pub n_body: Option<NBody>,
+    /// Snapshots keep only the built-in fields, closures are dropped.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::force_field::persist")
+    )]
+    pub force_fields: Vec<Box<dyn ForceField>>,
+    pub constraints: Vec<Constraint>,
+    /// Wrap-around boundary; usually in place of a container constraint.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub periodic: Option<Periodic>,
+    pub obstacles: Vec<Obstacle>,
+    /// Spinning obstacles, advanced every substep.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rotors: Vec<Rotor>,
+    pub fluids: Vec<FluidZone>,
+    /// SPH fluids, which particles join through `Particle::sph_fluid`.
+    pub sph_fluids: Vec<SphFluid>,
+    pub heat_sources: Vec<HeatSource>,
+    pub links: Vec<Link>,
+    pub bends: Vec<Bend>,
+    pub soft_bodies: Vec<SoftBody>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rigid_clusters: Vec<RigidCluster>,
+    pub springs: Vec<Spring>,
+    pub pins: Vec<Pin>,
+    pub tethers: Vec<Tether>,
+    pub emitters: Vec<Emitter>,
+    /// Timed spawns still to come, fired and removed once `time` reaches them.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub schedule: Vec<SpawnEvent>,
+    /// Simulated seconds `step` has run, including the time scale.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub time: f32,
+    /// Length of the last substep, which `pos - pos_last` spans; see `velocity`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    last_sub_dt: f32,
+    /// Merges slow touching particles when set.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub merging: Option<Merging>,
+    /// Seconds each touching pair has been resting together, for `merging`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    resting: BTreeMap<(usize, usize), f32>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    events: Vec<Event>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    handles: Handles,
+    /// Indices of the dead particles, reused by `add_particle` last freed first.
+    #[cfg_attr(feature = "serde", serde(default))]
+    free: Vec<usize>,
+    /// Per-link XPBD multipliers, reset every substep.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    link_lambdas: Vec<f32>,
+    /// Source of all randomness in the simulation; seed it for reproducible runs. This is the
+    /// generator behind `StdRng`, named directly so snapshots can carry its state.
+    pub rng: ChaCha12Rng,
+    pub solver: Solver,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default = "crate::integrator::persist::default",
+            with = "crate::integrator::persist"
+        )
+    )]
+    pub integrator: Box<dyn Integrator>,
+    /// Solver passes per `step`, each integrating `dt / substeps`. Change it with
+    /// `set_substeps` to keep the particles' speed.
+    pub substeps: usize,
+    /// Sets `substeps` at the start of every step from how fast the particles move. Fixed
+    /// when `None`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub adaptive_substeps: Option<AdaptiveSubsteps>,
+    /// Gauss-Seidel passes over contacts and constraints per substep. More passes stiffen tall
+    /// stacks and long chains without shrinking the step.
+    #[cfg_attr(feature = "serde", serde(default = "default_solver_iterations"))]
+    pub solver_iterations: usize,
+    /// Linear drag per second applied to every particle.
+    pub damping: f32,
+    /// Multiplies the `dt` of every `step`, see `set_time_scale`.
+    #[cfg_attr(feature = "serde", serde(default = "default_time_scale"))]
+    time_scale: f32,
+    /// Emitters stop spawning while the world holds this many particles.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_particles: Option<usize>,
+    /// Runs `sort_spatially` every this many steps, which speeds up contact solving in large
+    /// scenes whose particles mix over time. Off when `None`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub spatial_sort_interval: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    steps_since_sort: u32,
+    /// Keeps the particles that are removed, despawned or merged away in place as dead slots,
+    /// see `Particle::alive`, and fills those slots with the next added particles before
+    /// growing `particles`. Nothing gets compacted, so the other particles keep their indices
+    /// and spawning and despawning in bulk doesn't reallocate or shift the columns. For the
+    /// same reason `spatial_sort_interval` is ignored.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pool: bool,
+    /// Longest distance a particle may travel in one substep, so one bad frame or a deep
+    /// penetration can't fling it across the scene. Unlimited when `None`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_displacement: Option<f32>,
+    /// Runs `solve_ccd` after every substep.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ccd: bool,
+    /// Measures `timings` on every step when set.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub profile: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    timings: StepTimings,
+    /// Application data by particle, see `set_user_data`. Snapshots don't keep it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    user_data: HashMap<ParticleHandle, Box<dyn UserData>>,
+}
+
+#[cfg(feature = "serde")]
+fn default_solver_iterations() -> usize {
+    1
+}
+
+#[cfg(feature = "serde")]
+fn default_time_scale() -> f32 {
+    1_f32
+}
+
+impl Default for World {
+    fn default() -> Self {
+        World {
+            particles: Particles::new(),
+            gravity: Vec2::new(0_f32, -1000_f32),
+            attractor: None,
+            n_body: None,
+            force_fields: Vec::new(),
+            constraints: vec![Constraint::circle(Vec2::zero(), 300_f32)],
+            periodic: None,
+            obstacles: Vec::new(),
+            rotors: Vec::new(),
+            fluids: Vec::new(),
+            sph_fluids: Vec::new(),
+            heat_sources: Vec::new(),
+            links: Vec::new(),
+            bends: Vec::new(),
+            soft_bodies: Vec::new(),
+            rigid_clusters: Vec::new(),
+            springs: Vec::new(),
+            pins: Vec::new(),
+            tethers: Vec::new(),
+            emitters: Vec::new(),
+            schedule: Vec::new(),
+            time: 0_f32,
+            last_sub_dt: 0_f32,
+            merging: None,
+            resting: BTreeMap::new(),
+            events: Vec::new(),
+            handles: Handles::default(),
+            free: Vec::new(),
+            link_lambdas: Vec::new(),
+            rng: ChaCha12Rng::from_entropy(),
+            solver: Solver::default(),
+            integrator: Box::new(Verlet),
+            substeps: 1,
+            adaptive_substeps: None,
+            solver_iterations: 1,
+            damping: 0_f32,
+            max_particles: None,
+            spatial_sort_interval: None,
+            steps_since_sort: 0,
+            pool: false,
+            max_displacement: None,
+            ccd: false,
+            profile: false,
+            timings: StepTimings::default(),
+            time_scale: 1_f32,
+            user_data: HashMap::new(),
+        }
+    }
+}
+
+impl World {
+    pub fn new(gravity: Vec2, constraints: Vec<Constraint>) -> Self {
+        World {
+            gravity,
+            constraints,
+            ..World::default()
+        }
+    }
+
+    /// Phase timings of the last step, measured while `profile` is set.
+    pub fn timings(&self) -> &StepTimings {
+        &self.timings
+    }
+
+    /// Reseeds the world's random number generator. Together with a fixed `dt` passed to
+    /// `step`, this makes runs bit-identical for the same inputs.
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = ChaCha12Rng::seed_from_u64(seed);
+    }
+
+    /// Reserves capacity for `additional` more particles and their handles.
+    pub fn reserve(&mut self, additional: usize) {
+        self.particles.reserve(additional);
+        self.handles.reserve(additional);
+    }
+
+    /// Adds a particle and returns its index, which is a dead slot's if there is one.
+    pub fn add_particle(&mut self, mut particle: Particle) -> usize {
+        particle.alive = true;
+        if let Some(index) = self.free.pop() {
+            self.particles.set(index, particle);
+            self.handles.retrack(index);
+            return index;
+        }
+        self.particles.push(particle);
+        self.handles.track(self.particles.len());
+        self.particles.len() - 1
+    }
+
+    /// Number of live particles, which is `particles.len()` minus the dead slots.
+    pub fn particle_count(&self) -> usize {
+        self.particles.len() - self.free.len()
+    }
+
+    /// Adds a particle and returns a handle to it.
+    pub fn insert(&mut self, particle: Particle) -> ParticleHandle {
+        let index = self.add_particle(particle);
+        self.handles
+            .handle(index)
+            .expect("added particles are tracked")
+    }
+
+    /// Handle of particle `index`. Particles pushed onto `particles` directly get one from the
+    /// next `add_particle` or `step`.
+    pub fn handle(&self, index: usize) -> Option<ParticleHandle> {
+        self.handles.handle(index)
+    }
+
+    /// Current index of the particle behind `handle`, or `None` if it was removed.
+    pub fn index(&self, handle: ParticleHandle) -> Option<usize> {
+        self.handles.index(handle)
+    }
+
+    /// Removes the particle behind `handle` along with every link, spring, bend, pin, tether
+    /// and soft body attached to it, and updates the broadphase index. Later particles move
+    /// down one index unless the world is `pool`ed, see `Event::ParticlesRemapped`. Returns
+    /// `None` for a stale handle.
+    pub fn remove(&mut self, handle: ParticleHandle) -> Option<Particle> {
+        let index = self.index(handle)?;
+        let removed = self.particles.particle(index);
+        self.retain_particles(|_, i| i != index);
+        self.solver.refresh_index(&self.particles);
+        Some(removed)
+    }
+
+    /// A copy of the particle behind `handle`.
+    pub fn get(&self, handle: ParticleHandle) -> Option<Particle> {
+        self.particles.get(self.index(handle)?)
+    }
+
+    /// Overwrites the particle behind `handle`; returns `false` for a stale handle.
+    pub fn set(&mut self, handle: ParticleHandle, particle: Particle) -> bool {
+        let Some(index) = self.index(handle) else {
+            return false;
+        };
+        self.particles.set(index, particle);
+        true
+    }
+
+    /// Attaches `data` to the particle behind `handle`, replacing what it had. It follows the
+    /// particle through reordering; when the particle is merged away or removed, it comes back
+    /// in `Event::ParticlesMerged` or `Event::UserDataRemoved`. Split-off pieces start without
+    /// any. Returns `false` for a stale handle.
+    pub fn set_user_data(&mut self, handle: ParticleHandle, data: impl UserData) -> bool {
+        if self.index(handle).is_none() {
+            return false;
+        }
+        self.user_data.insert(handle, Box::new(data));
+        true
+    }
+
+    /// Data attached to the particle behind `handle`, if there is some of type `T`.
+    pub fn user_data<T: UserData>(&self, handle: ParticleHandle) -> Option<&T> {
+        self.user_data.get(&handle)?.downcast_ref()
+    }
+
+    pub fn user_data_mut<T: UserData>(&mut self, handle: ParticleHandle) -> Option<&mut T> {
+        self.user_data.get_mut(&handle)?.downcast_mut()
+    }
+
+    /// Detaches and returns the data of the particle behind `handle`.
+    pub fn take_user_data(&mut self, handle: ParticleHandle) -> Option<Box<dyn UserData>> {
+        self.user_data.remove(&handle)
+    }
+
+    /// Adds a constraint region and returns its index, i.e. its bit in `Particle::regions`.
+    pub fn add_constraint(&mut self, constraint: Constraint) -> usize {
+        self.constraints.push(constraint);
+        self.constraints.len() - 1
+    }
+
+    pub fn add_obstacle(&mut self, obstacle: Obstacle) -> usize {
+        self.obstacles.push(obstacle);
+        self.obstacles.len() - 1
+    }
+
+    pub fn add_rotor(&mut self, rotor: Rotor) -> usize {
+        self.rotors.push(rotor);
+        self.rotors.len() - 1
+    }
+
+    pub fn add_sph_fluid(&mut self, fluid: SphFluid) -> usize {
+        self.sph_fluids.push(fluid);
+        self.sph_fluids.len() - 1
+    }
+
+    pub fn add_heat_source(&mut self, source: HeatSource) -> usize {
+        self.heat_sources.push(source);
+        self.heat_sources.len() - 1
+    }
+
+    pub fn add_fluid(&mut self, fluid: FluidZone) -> usize {
+        self.fluids.push(fluid);
+        self.fluids.len() - 1
+    }
+
+    /// Links two particles at their current distance.
+    pub fn add_link(&mut self, a: usize, b: usize) -> usize {
+        let link = Link::between(&self.particles, a, b);
+        self.links.push(link);
+        self.links.len() - 1
+    }
+
+    pub fn add_force_field<F: ForceField + 'static>(&mut self, field: F) -> usize {
+        self.force_fields.push(Box::new(field));
+        self.force_fields.len() - 1
+    }
+
+    /// Adds a closure returning the force on a particle, given all particles and its index.
+    pub fn add_force_fn<F>(&mut self, f: F) -> usize
+    where
+        F: Fn(&Particles, usize) -> Vec2 + Send + Sync + 'static,
+    {
+        self.add_force_field(ForceFn(Arc::new(f)))
+    }
+
+    pub fn add_emitter(&mut self, emitter: Emitter) -> usize {
+        self.emitters.push(emitter);
+        self.emitters.len() - 1
+    }
+
+    /// Kicks particles within `falloff` of `center` away from it, e.g. for explosions. The
+    /// impulse is `strength` at the center and fades linearly to zero at `falloff`, so heavy
+    /// particles move less. `dt` is the step size later passed to `step`, which the kick needs
+    /// to become a velocity.
+    pub fn apply_impulse_radial(&mut self, center: Vec2, strength: f32, falloff: f32, dt: f32) {
+        let sub_dt = dt * self.time_scale / self.substeps.max(1) as f32;
+        let particles = &mut self.particles;
+        for i in 0..particles.len() {
+            if particles.pinned[i] || !particles.alive[i] {
+                continue;
+            }
+            let offset = &particles.pos[i] - &center;
+            let dist = offset.len();
+            if dist >= falloff {
+                continue;
+            }
+            // Particles right at the center fly straight up.
+            let dir = if dist > f32::EPSILON {
+                offset / dist
+            } else {
+                Vec2::new(0_f32, 1_f32)
+            };
+            let impulse = strength * (1_f32 - dist / falloff);
+            particles.wake(i);
+            particles.add_velocity(i, dir * (impulse / particles.mass[i]), sub_dt);
+        }
+    }
+
+    /// Pushes particles within `radius` of `center` away from it for one step of `dt`, or pulls
+    /// them in when `strength` is negative. The force is `strength` at the center and fades
+    /// linearly to zero at `radius`; only particles the broadphase finds nearby are visited.
+    pub fn apply_force_radial(&mut self, center: Vec2, strength: f32, radius: f32, dt: f32) {
+        let dt = dt * self.time_scale;
+        let sub_dt = dt / self.substeps.max(1) as f32;
+        let reach = Vec2::new(radius, radius);
+        let hits = self.query_aabb(center.clone() - reach.clone(), center.clone() + reach);
+        let particles = &mut self.particles;
+        for i in hits {
+            if particles.pinned[i] {
+                continue;
+            }
+            let offset = &particles.pos[i] - &center;
+            let dist = offset.len();
+            if dist >= radius || dist <= f32::EPSILON {
+                continue;
+            }
+            let force = strength * (1_f32 - dist / radius);
+            particles.wake(i);
+            let kick = offset / dist * (force / particles.mass[i] * dt);
+            particles.add_velocity(i, kick, sub_dt);
+        }
+    }
+
+    /// Topmost particle whose disc contains `point`.
+    pub fn pick(&self, point: &Vec2) -> Option<usize> {
+        let particles = &self.particles;
+        (0..particles.len()).rposition(|i| {
+            particles.alive[i] && (&particles.pos[i] - point).len() <= particles.radius[i]
+        })
+    }
+
+    /// Indices of the particles carrying `user_data`, in index order.
+    pub fn find_user_data(&self, user_data: u64) -> Vec<usize> {
+        self.particles
+            .user_data
+            .iter()
+            .enumerate()
+            .filter(|&(i, &d)| d == user_data && self.particles.alive[i])
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Pairs `(i, k)` with `i < k` of particles that collide with each other and are touching.
+    pub fn contacts(&mut self) -> Vec<(usize, usize)> {
+        let particles = &self.particles;
+        let mut pairs = self.solver.pairs(particles);
+        pairs.retain(|&(i, k)| particles.collides(i, k));
+        retain_overlapping(particles, &mut pairs);
+        pairs
+    }
+
+    /// Groups of particles touching or linked to each other, as of the last step; see
+    /// `island::islands`.
+    pub fn islands(&mut self) -> Vec<Vec<usize>> {
+        let mut pairs = self.contacts();
+        pairs.extend(self.linked_pairs());
+        islands(&self.particles, &pairs)
+    }
+
+    /// Particles overlapping the rectangle `min`-`max`, in index order, as of the last step.
+    pub fn query_aabb(&self, min: Vec2, max: Vec2) -> Vec<usize> {
+        let aabb = Aabb::new(min, max);
+        let mut found = Vec::new();
+        self.solver
+            .broadphase()
+            .query(&self.particles, &aabb, &mut found);
+        found.sort_unstable();
+        found.dedup();
+        found.retain(|&i| {
+            i < self.particles.len()
+                && self.particles.alive[i]
+                && aabb.overlaps_circle(&self.particles.pos[i], self.particles.radius[i])
+        });
+        found
+    }
+
+    /// The `k` particles whose centers are closest to `point`, nearest first. Searches the
+    /// broadphase index in growing boxes until the k-th match is provably the k-th nearest.
+    pub fn nearest(&self, point: Vec2, k: usize) -> Vec<usize> {
+        let count = self.particle_count();
+        if k == 0 || count == 0 {
+            return Vec::new();
+        }
+        let k = k.min(count);
+        let mut half = f32::max(2_f32 * self.particles.max_radius(), 1_f32);
+        let dist = |i: &usize| (self.particles.pos[*i].clone() - point.clone()).len();
+
+        let mut found = Vec::new();
+        while half.is_finite() {
+            found.clear();
+            let aabb = Aabb::around(&point, half);
+            self.solver
+                .broadphase()
+                .query(&self.particles, &aabb, &mut found);
+            found.sort_unstable();
+            found.dedup();
+            found.retain(|&i| i < self.particles.len() && self.particles.alive[i]);
+
+            found.sort_by(|a, b| dist(a).total_cmp(&dist(b)));
+            let complete = found.len() == count;
+            if complete || (found.len() >= k && dist(&found[k - 1]) <= half) {
+                found.truncate(k);
+                return found;
+            }
+            half *= 2_f32;
+        }
+
+        // The index doesn't cover every particle yet, e.g. before the first step.
+        let mut all: Vec<usize> = (0..self.particles.len())
+            .filter(|&i| self.particles.alive[i])
+            .collect();
+        all.sort_by(|a, b| dist(a).total_cmp(&dist(b)));
+        all.truncate(k);
+        all
+    }
+
+    /// First particle or obstacle hit by the ray from `origin` along `dir`, within `max_dist`.
+    pub fn raycast(&self, origin: Vec2, dir: Vec2, max_dist: f32) -> Option<Hit> {
+        let mut dir = dir;
+        dir.normalize();
+
+        let (pos, radius) = (&self.particles.pos, &self.particles.radius);
+        let particles = (0..self.particles.len())
+            .filter(|&i| self.particles.alive[i])
+            .filter_map(|i| {
+                let t = query::ray_circle(&origin, &dir, &pos[i], radius[i])?;
+                let point = origin.clone() + dir.clone() * t;
+                let normal = (point - pos[i].clone()) / radius[i];
+                Some((t, normal, HitTarget::Particle(i)))
+            });
+        let obstacles = self.obstacles.iter().enumerate().filter_map(|(i, o)| {
+            let (t, normal) = o.raycast(&origin, &dir)?;
+            Some((t, normal, HitTarget::Obstacle(i)))
+        });
+
+        particles
+            .chain(obstacles)
+            .filter(|(t, _, _)| *t <= max_dist)
+            .min_by(|x, y| x.0.total_cmp(&y.0))
+            .map(|(distance, normal, target)| Hit {
+                target,
+                point: origin.clone() + dir.clone() * distance,
+                normal,
+                distance,
+            })
+    }
+
+    /// Adds a tether and returns its index, or `None` if its particle doesn't exist.
+    pub fn add_tether(&mut self, tether: Tether) -> Option<usize> {
+        if tether.particle >= self.particles.len() {
+            return None;
+        }
+        self.particles.wake(tether.particle);
+        self.tethers.push(tether);
+        Some(self.tethers.len() - 1)
+    }
+
+    /// Pins a particle at `point` and returns the pin's index.
+    pub fn pin(&mut self, particle: usize, point: Vec2) -> usize {
+        self.particles.pinned[particle] = true;
+        self.particles.pos[particle] = point.clone();
+        self.particles.pos_last[particle] = point.clone();
+        self.pins.push(Pin::new(particle, point));
+        self.pins.len() - 1
+    }
+
+    /// Removes a pin and releases its particle, unless another pin still holds it.
+    pub fn unpin(&mut self, pin: usize) -> Pin {
+        let removed = self.pins.remove(pin);
+        if !self.pins.iter().any(|p| p.particle == removed.particle) {
+            self.particles.pinned[removed.particle] = false;
+        }
+        removed
+    }
+
+    /// Takes the events emitted since the last call.
+    pub fn drain_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Adds bend constraints over every consecutive triple of `ids`, e.g. a chain, holding the
+    /// current angles.
+    pub fn add_bends(&mut self, ids: &[usize], stiffness: f32) {
+        for w in ids.windows(3) {
+            let bend = Bend::between(&self.particles, w[0], w[1], w[2], stiffness);
+            self.bends.push(bend);
+        }
+    }
+
+    /// Connects two particles with a spring resting at their current distance.
+    pub fn add_spring(&mut self, a: usize, b: usize, stiffness: f32, damping: f32) -> usize {
+        let rest_length = (self.particles.pos[a].clone() - self.particles.pos[b].clone()).len();
+        self.springs
+            .push(Spring::new(a, b, rest_length, stiffness, damping));
+        self.springs.len() - 1
+    }
+
+    /// Hangs a chain of `segments` links from `start` to `end`, pinned at `start`, and returns
+    /// the indices of its particles.
+    pub fn spawn_chain(
+        &mut self,
+        start: Vec2,
+        end: Vec2,
+        segments: usize,
+        radius: f32,
+    ) -> Vec<usize> {
+        let segments = segments.max(1);
+        let step = (end - start.clone()) / segments as f32;
+        let ids: Vec<usize> = (0..=segments)
+            .map(|i| {
+                let mut p = Particle::new(start.clone() + step.clone() * i as f32);
+                p.radius = radius;
+                self.add_particle(p)
+            })
+            .collect();
+        self.pin(ids[0], start);
+        for w in ids.windows(2) {
+            self.add_link(w[0], w[1]);
+        }
+        ids
+    }
+
+    /// Builds a `rows x cols` cloth hanging down from `top_left`, with structural links between
+    /// grid neighbours and shear links across each cell, pinned along the top row. Returns the
+    /// particle indices in row-major order.
+    pub fn spawn_cloth(
+        &mut self,
+        top_left: Vec2,
+        rows: usize,
+        cols: usize,
+        spacing: f32,
+    ) -> Vec<usize> {
+        let mut ids = Vec::with_capacity(rows * cols);
+        for r in 0..rows {
+            for c in 0..cols {
+                let pos = Vec2::new(
+                    top_left.x + c as f32 * spacing,
+                    top_left.y - r as f32 * spacing,
+                );
+                let mut p = Particle::new(pos.clone());
+                p.radius = 0.25_f32 * spacing;
+                let id = self.add_particle(p);
+                if r == 0 {
+                    self.pin(id, pos);
+                }
+                ids.push(id);
+            }
+        }
+
+        let at = |r: usize, c: usize| ids[r * cols + c];
+        for r in 0..rows {
+            for c in 0..cols {
+                if c + 1 < cols {
+                    self.add_link(at(r, c), at(r, c + 1));
+                }
+                if r + 1 < rows {
+                    self.add_link(at(r, c), at(r + 1, c));
+                }
+                if r + 1 < rows && c + 1 < cols {
+                    self.add_link(at(r, c), at(r + 1, c + 1));
+                    self.add_link(at(r, c + 1), at(r + 1, c));
+                }
+            }
+        }
+        ids
+    }
+
+    /// Holds the particles `ids` in their current arrangement and returns the cluster's index.
+    pub fn add_rigid_cluster(&mut self, ids: Vec<usize>, stiffness: f32) -> usize {
+        let cluster = RigidCluster::new(&self.particles, ids, stiffness);
+        self.rigid_clusters.push(cluster);
+        self.rigid_clusters.len() - 1
+    }
+
+    /// Spawns a `cols` by `rows` grid of touching particles centered on `center` and held
+    /// rigid, and returns the cluster's index.
+    pub fn spawn_rigid_box(
+        &mut self,
+        center: Vec2,
+        cols: usize,
+        rows: usize,
+        particle_radius: f32,
+    ) -> usize {
+        let spacing = 2_f32 * particle_radius;
+        let origin = Vec2::new(
+            center.x - 0.5_f32 * (cols.max(1) - 1) as f32 * spacing,
+            center.y - 0.5_f32 * (rows.max(1) - 1) as f32 * spacing,
+        );
+        let mut ids = Vec::with_capacity(rows * cols);
+        for r in 0..rows {
+            for c in 0..cols {
+                let pos = Vec2::new(origin.x + c as f32 * spacing, origin.y + r as f32 * spacing);
+                let mut p = Particle::new(pos);
+                p.radius = particle_radius;
+                ids.push(self.add_particle(p));
+            }
+        }
+        self.add_rigid_cluster(ids, 1_f32)
+    }
+
+    /// Spawns a rigid gear: a ring of touching particles of `particle_radius` at `radius` from
+    /// `center` with `teeth` particles sticking out, and returns the cluster's index.
+    pub fn spawn_rigid_gear(
+        &mut self,
+        center: Vec2,
+        radius: f32,
+        teeth: usize,
+        particle_radius: f32,
+    ) -> usize {
+        let ring = f32::floor(std::f32::consts::PI * radius / particle_radius).max(3_f32) as usize;
+        let teeth = teeth.clamp(1, ring);
+        let mut ids = Vec::with_capacity(ring + teeth + 1);
+        let mut spawn = |world: &mut World, distance: f32, angle: f32| {
+            let pos = Vec2::new(
+                center.x + distance * angle.cos(),
+                center.y + distance * angle.sin(),
+            );
+            let mut p = Particle::new(pos);
+            p.radius = particle_radius;
+            ids.push(world.add_particle(p));
+        };
+        spawn(self, 0_f32, 0_f32);
+        for i in 0..ring {
+            spawn(self, radius, std::f32::consts::TAU * i as f32 / ring as f32);
+        }
+        for i in 0..teeth {
+            let angle = std::f32::consts::TAU * i as f32 / teeth as f32;
+            spawn(self, radius + 2_f32 * particle_radius, angle);
+        }
+        self.add_rigid_cluster(ids, 1_f32)
+    }
+
+    /// Spawns a ring of `segments` particles around `center` held in shape by links and internal
+    /// `pressure`, and returns the soft body's index.
+    pub fn spawn_blob(
+        &mut self,
+        center: Vec2,
+        radius: f32,
+        segments: usize,
+        particle_radius: f32,
+        pressure: f32,
+    ) -> usize {
+        let segments = segments.max(3);
+        let ids: Vec<usize> = (0..segments)
+            .map(|i| {
+                let a = std::f32::consts::TAU * i as f32 / segments as f32;
+                let pos = Vec2::new(center.x + radius * a.cos(), center.y + radius * a.sin());
+                let mut p = Particle::new(pos);
+                p.radius = particle_radius;
+                self.add_particle(p)
+            })
+            .collect();
+        for i in 0..segments {
+            self.add_link(ids[i], ids[(i + 1) % segments]);
+        }
+
+        let mut body = SoftBody::new(ids, 0_f32, pressure);
+        body.rest_area = body.area(&self.particles);
+        self.soft_bodies.push(body);
+        self.soft_bodies.len() - 1
+    }
+
+    #[cfg(feature = "parallel")]
+    pub fn set_thread_count(&mut self, threads: usize) {
+        self.solver.set_thread_count(threads);
+    }
+
+    pub fn run_emitters(&mut self, dt: f32, step_dt: f32) {
+        for e in 0..self.emitters.len() {
+            let room = self.room();
+            let spawned = self.emitters[e].emit(dt, step_dt, room, &mut self.rng);
+            for p in spawned {
+                self.add_particle(p);
+            }
+        }
+    }
+
+    /// Adds the particles of every scheduled spawn that is due, for steps of `step_dt`.
+    pub fn run_schedule(&mut self, step_dt: f32) {
+        let time = self.time;
+        let (due, later): (Vec<SpawnEvent>, Vec<SpawnEvent>) = std::mem::take(&mut self.schedule)
+            .into_iter()
+            .partition(|e| e.at <= time);
+        self.schedule = later;
+        for e in due {
+            for p in e.spawn(step_dt).into_iter().take(self.room()) {
+                self.add_particle(p);
+            }
+        }
+    }
+
+    /// How many more particles emitters and scheduled spawns may add, see `max_particles`.
+    fn room(&self) -> usize {
+        self.max_particles
+            .map_or(usize::MAX, |max| max.saturating_sub(self.particle_count()))
+    }
+
+    pub fn apply_gravity(&mut self) {
+        match &self.attractor {
+            Some(attractor) => {
+                let particles = &mut self.particles;
+                for i in 0..particles.len() {
+                    let acc =
+                        attractor.acceleration(&particles.pos[i]) * particles.gravity_scale[i];
+                    particles.accelerate(i, acc);
+                }
+            }
+            None => {
+                let particles = &mut self.particles;
+                for i in 0..particles.len() {
+                    particles.accelerate(i, &self.gravity * particles.gravity_scale[i]);
+                }
+            }
+        }
+        if let Some(n_body) = &self.n_body {
+            n_body.apply(&mut self.particles);
+        }
+    }
+
+    pub fn apply_force_fields(&mut self) {
+        for field in self.force_fields.iter() {
+            for i in 0..self.particles.len() {
+                let force = field.force(&self.particles, i);
+                self.particles.apply_force(i, force);
+            }
+        }
+    }
+
+    /// Buoyancy and drag from fluid zones, pushing against the uniform `gravity`.
+    pub fn apply_fluids(&mut self, dt: f32) {
+        for fluid in self.fluids.iter() {
+            fluid.apply(&mut self.particles, &self.gravity, dt);
+        }
+    }
+
+    pub fn apply_sph(&mut self, dt: f32) {
+        for (i, fluid) in self.sph_fluids.iter().enumerate() {
+            fluid.apply(i, &mut self.particles, dt);
+        }
+    }
+
+    pub fn apply_heat(&mut self, dt: f32) {
+        for source in self.heat_sources.iter() {
+            source.apply(&mut self.particles, dt);
+        }
+    }
+
+    pub fn apply_pressure(&mut self) {
+        for body in self.soft_bodies.iter() {
+            body.apply_pressure(&mut self.particles);
+        }
+    }
+
+    /// Despawns particles whose lifetime ran out.
+    pub fn despawn_expired(&mut self) {
+        self.retain_particles(|particles, i| !particles.is_expired(i));
+    }
+
+    /// Replaces particle `index` with `count` smaller ones of the same total mass and area,
+    /// see `merge::split`, and returns their indices. The first piece keeps `index` and
+    /// anything attached to the particle.
+    pub fn split_particle(&mut self, index: usize, count: usize) -> Vec<usize> {
+        let mut pieces = merge::split(&self.particles.particle(index), count).into_iter();
+        let mut ids = vec![index];
+        if let Some(first) = pieces.next() {
+            self.particles.set(index, first);
+        }
+        ids.extend(pieces.map(|p| self.add_particle(p)));
+        ids
+    }
+
+    /// Advances the `merging` rule by a step of `dt` and merges every pair that has rested
+    /// together long enough, each particle at most once per call. Velocities are taken over the
+    /// step's last substep of `sub_dt`. The merged particle takes the lower index.
+    pub fn merge_resting(&mut self, dt: f32, sub_dt: f32) {
+        let Some(rules) = &self.merging else {
+            self.resting.clear();
+            return;
+        };
+        let mut attached = vec![false; self.particles.len()];
+        let held = self
+            .links
+            .iter()
+            .flat_map(|l| [l.a, l.b])
+            .chain(self.springs.iter().flat_map(|s| [s.a, s.b]))
+            .chain(self.bends.iter().flat_map(|b| [b.a, b.b, b.c]))
+            .chain(self.pins.iter().map(|p| p.particle))
+            .chain(self.tethers.iter().map(|t| t.particle))
+            .chain(
+                self.soft_bodies
+                    .iter()
+                    .flat_map(|b| b.particles.iter().copied()),
+            )
+            .chain(
+                self.rigid_clusters
+                    .iter()
+                    .flat_map(|c| c.particles.iter().copied()),
+            );
+        for i in held {
+            attached[i] = true;
+        }
+        let particles = &self.particles;
+        let free =
+            |i: usize| !attached[i] && !particles.pinned[i] && particles.sph_fluid[i].is_none();
+
+        let mut resting = BTreeMap::new();
+        for (i, k) in self.solver.pairs(particles) {
+            if free(i)
+                && free(k)
+                && merge::merged_radius(particles, i, k) <= rules.max_radius
+                && merge::resting(particles, i, k, rules.max_speed, sub_dt)
+            {
+                let time = self.resting.get(&(i, k)).copied().unwrap_or(0_f32) + dt;
+                resting.insert((i, k), time);
+            }
+        }
+        self.resting = resting;
+
+        let mut merged = vec![false; self.particles.len()];
+        let mut removed = vec![false; self.particles.len()];
+        for (&(i, k), &time) in self.resting.iter() {
+            if time < rules.contact_time || merged[i] || merged[k] {
+                continue;
+            }
+            let merged_particle =
+                merge::merge(&self.particles.particle(i), &self.particles.particle(k));
+            self.particles.set(i, merged_particle);
+            merged[i] = true;
+            merged[k] = true;
+            removed[k] = true;
+            if let (Some(kept), Some(absorbed)) = (self.handle(i), self.handle(k)) {
+                let data = self.user_data.remove(&absorbed);
+                self.events.push(Event::ParticlesMerged {
+                    kept,
+                    absorbed,
+                    data,
+                });
+            }
+        }
+        self.retain_particles(|_, i| !removed[i]);
+    }
+
+    /// Keeps only the live particles matching `keep`, compacting storage and remapping every
+    /// index held by links, springs, bends, pins, tethers, soft bodies and rigid clusters.
+    /// Anything that referenced a removed particle is dropped, except that rigid clusters just
+    /// lose the removed members. A pooled world marks the removed particles dead instead and
+    /// frees their slots, so no index changes.
+    fn retain_particles<F: Fn(&Particles, usize) -> bool>(&mut self, keep: F) {
+        let particles = &self.particles;
+        if self.pool {
+            let died: Vec<usize> = (0..particles.len())
+                .filter(|&i| particles.alive[i] && !keep(particles, i))
+                .collect();
+            if died.is_empty() {
+                return;
+            }
+            let mut remap: Vec<Option<usize>> = (0..particles.len()).map(Some).collect();
+            for &i in died.iter() {
+                self.particles.alive[i] = false;
+                remap[i] = None;
+            }
+            self.remap_references(remap);
+            self.free.extend(died.into_iter().rev());
+            return;
+        }
+        let kept: Vec<bool> = (0..particles.len())
+            .map(|i| particles.alive[i] && keep(particles, i))
+            .collect();
+        if kept.iter().all(|&k| k) {
+            return;
+        }
+        let mut remap = Vec::with_capacity(kept.len());
+        let mut next = 0;
+        for &k in kept.iter() {
+            if k {
+                remap.push(Some(next));
+                next += 1;
+            } else {
+                remap.push(None);
+            }
+        }
+        self.particles.retain_mask(&kept);
+        self.free.clear();
+        self.remap_references(remap);
+    }
+
+    /// Reorders `particles` along a Z-order curve, so particles close in space are close in
+    /// memory too. Every index the world holds is remapped and `Event::ParticlesRemapped`
+    /// reports the move; handles keep pointing at their particles.
+    pub fn sort_spatially(&mut self) {
+        let cell = 2_f32 * self.particles.max_radius() + COLLISION_MARGIN;
+        let order = morton::order(&self.particles, cell);
+        if order.iter().enumerate().all(|(new, &old)| new == old) {
+            return;
+        }
+        let mut remap = vec![None; order.len()];
+        for (new, &old) in order.iter().enumerate() {
+            remap[old] = Some(new);
+        }
+        // Follow each cycle of the permutation, swapping particles into place.
+        let mut dest: Vec<usize> = remap.iter().flatten().copied().collect();
+        let mut i = 0;
+        while i < dest.len() {
+            let j = dest[i];
+            if j == i {
+                i += 1;
+            } else {
+                self.particles.swap(i, j);
+                dest.swap(i, j);
+            }
+        }
+        self.remap_references(remap);
+    }
+
+    /// Points every index held by links, springs, bends, pins, tethers, soft bodies, rigid
+    /// clusters and handles at `remap[index]`, dropping what referenced a removed particle as
+    /// `retain_particles` describes.
+    fn remap_references(&mut self, remap: Vec<Option<usize>>) {
+        self.solver.clear_contact_cache();
+        let map = |i: &mut usize| match remap[*i] {
+            Some(n) => {
+                *i = n;
+                true
+            }
+            None => false,
+        };
+        self.links.retain_mut(|l| map(&mut l.a) & map(&mut l.b));
+        self.springs.retain_mut(|s| map(&mut s.a) & map(&mut s.b));
+        self.bends
+            .retain_mut(|b| map(&mut b.a) & map(&mut b.b) & map(&mut b.c));
+        self.pins.retain_mut(|p| map(&mut p.particle));
+        self.tethers.retain_mut(|t| map(&mut t.particle));
+        self.free.retain_mut(map);
+        self.soft_bodies
+            .retain_mut(|b| b.particles.iter_mut().fold(true, |ok, i| map(i) & ok));
+        for cluster in self.rigid_clusters.iter_mut() {
+            let members = std::mem::take(&mut cluster.particles);
+            let rest = std::mem::take(&mut cluster.rest);
+            (cluster.particles, cluster.rest) = members
+                .into_iter()
+                .zip(rest)
+                .filter_map(|(i, r)| Some((remap[i]?, r)))
+                .unzip();
+        }
+        self.rigid_clusters.retain(|c| c.particles.len() > 1);
+        self.resting = std::mem::take(&mut self.resting)
+            .into_iter()
+            .filter_map(|((i, k), time)| {
+                let (i, k) = (remap[i]?, remap[k]?);
+                Some(((i.min(k), i.max(k)), time))
+            })
+            .collect();
+        let dropped: Vec<_> = remap
+            .iter()
+            .enumerate()
+            .filter(|(_, new)| new.is_none())
+            .filter_map(|(i, _)| self.handles.handle(i))
+            .filter_map(|h| Some((h, self.user_data.remove(&h)?)))
+            .collect();
+        self.handles.remap(&remap, self.particles.len());
+
+        self.events.push(Event::ParticlesRemapped(remap));
+        if !dropped.is_empty() {
+            self.events.push(Event::UserDataRemoved(dropped));
+        }
+    }
+
+    /// Removes links and springs stretched past their break threshold.
+    pub fn break_constraints(&mut self) {
+        let particles = &self.particles;
+        let events = &mut self.events;
+        self.links.retain(|l| {
+            let broken = l.is_broken(particles);
+            if broken {
+                events.push(Event::ConstraintBroken(BrokenConstraint::Link(l.clone())));
+            }
+            !broken
+        });
+        self.springs.retain(|s| {
+            let broken = s.is_broken(particles);
+            if broken {
+                events.push(Event::ConstraintBroken(BrokenConstraint::Spring(s.clone())));
+            }
+            !broken
+        });
+    }
+
+    pub fn apply_springs(&mut self, dt: f32) {
+        for spring in self.springs.iter() {
+            spring.apply(&mut self.particles, dt);
+        }
+        for tether in self.tethers.iter() {
+            tether.apply(&mut self.particles, dt);
+        }
+    }
+
+    /// Pairs held together by a link or spring, which don't collide.
+    fn linked_pairs(&self) -> HashSet<(usize, usize)> {
+        self.links
+            .iter()
+            .map(|l| l.key())
+            .chain(self.springs.iter().map(|s| s.key()))
+            .collect()
+    }
+
+    fn update_sleep(&mut self) {
+        match &self.solver.sleep {
+            Some(sleep) if sleep.islands => {
+                let islands = self.islands();
+                self.solver
+                    .update_island_sleep(&mut self.particles, &islands);
+            }
+            _ => self.solver.update_sleep(&mut self.particles),
+        }
+    }
+
+    pub fn solve_collisions(&mut self, dt: f32) {
+        let linked = self.linked_pairs();
+        self.solver
+            .solve_collisions(&mut self.particles, &linked, dt);
+        if let Some(periodic) = &self.periodic {
+            self.solver
+                .solve_periodic_collisions(&mut self.particles, periodic, &linked, dt);
+        }
+    }
+
+    /// Brings particles that left the periodic box back in through the opposite side.
+    pub fn wrap_periodic(&mut self) {
+        if let Some(periodic) = &self.periodic {
+            (0..self.particles.len()).for_each(|i| periodic.wrap(&mut self.particles, i));
+        }
+    }
+
+    /// Continuous collision pass over the last integration step. Every particle that moved
+    /// further than its radius is rewound to its first impact with an obstacle or another
+    /// particle along the way and bounces there, so fast particles can't tunnel. The rest of
+    /// the move is dropped.
+    pub fn solve_ccd(&mut self) {
+        let fast: Vec<usize> = (0..self.particles.len())
+            .filter(|&i| {
+                let p = &self.particles;
+                p.moves(i) && (&p.pos[i] - &p.pos_last[i]).len() > p.radius[i]
+            })
+            .collect();
+        if fast.is_empty() {
+            return;
+        }
+        self.solver.refresh_index(&self.particles);
+        let linked = self.linked_pairs();
+        let mut near = Vec::new();
+        for i in fast {
+            let p = &self.particles;
+            let (from, to) = (p.pos_last[i].clone(), p.pos[i].clone());
+            let mut first: Option<(f32, Option<usize>, Vec2)> = None;
+            let mut hit = |t: f32, other: Option<usize>, n: Vec2| {
+                if first.as_ref().is_none_or(|f| t < f.0) {
+                    first = Some((t, other, n));
+                }
+            };
+            for o in self.obstacles.iter() {
+                if let Some((t, n)) = ccd::sweep_obstacle(o, &from, &to, p.radius[i]) {
+                    hit(t, None, n);
+                }
+            }
+
+            let reach = p.radius[i] + COLLISION_MARGIN;
+            let min = Vec2::new(from.x.min(to.x) - reach, from.y.min(to.y) - reach);
+            let max = Vec2::new(from.x.max(to.x) + reach, from.y.max(to.y) + reach);
+            near.clear();
+            self.solver
+                .broadphase()
+                .query(&self.particles, &Aabb::new(min, max), &mut near);
+            near.sort_unstable();
+            near.dedup();
+            for &k in near.iter() {
+                let fluid = p.sph_fluid[i].is_some() && p.sph_fluid[i] == p.sph_fluid[k];
+                if k == i || fluid || !p.collides(i, k) {
+                    continue;
+                }
+                if linked.contains(&(i.min(k), i.max(k))) {
+                    continue;
+                }
+                let reach = p.radius[i] + p.radius[k] + COLLISION_MARGIN;
+                let (q_last, q_pos) = (&p.pos_last[k], &p.pos[k]);
+                if let Some(t) = ccd::sweep_circles(&from, &to, q_last, q_pos, reach) {
+                    let a = from.clone() + (to.clone() - from.clone()) * t;
+                    let b = q_last + &((q_pos - q_last) * t);
+                    let mut n = a - b;
+                    n.normalize();
+                    hit(t, Some(k), n);
+                }
+            }
+
+            match first {
+                Some((t, None, n)) => ccd::rewind_wall(&mut self.particles, i, t, &n),
+                Some((t, Some(k), n)) => ccd::rewind_pair(&mut self.particles, i, k, t, &n),
+                None => {}
+            }
+        }
+    }
+
+    pub fn solve_links(&mut self, dt: f32) {
+        match self.solver.projection {
+            Projection::Relaxation => {
+                for link in self.links.iter() {
+                    link.solve(&mut self.particles);
+                }
+            }
+            Projection::Xpbd { .. } => {
+                self.link_lambdas.resize(self.links.len(), 0_f32);
+                for (link, lambda) in self.links.iter().zip(self.link_lambdas.iter_mut()) {
+                    link.solve_xpbd(&mut self.particles, dt, lambda);
+                }
+            }
+        }
+    }
+
+    pub fn solve_bends(&mut self) {
+        for bend in self.bends.iter() {
+            bend.solve(&mut self.particles);
+        }
+    }
+
+    pub fn solve_rigid_clusters(&mut self) {
+        for cluster in self.rigid_clusters.iter() {
+            cluster.solve(&mut self.particles);
+        }
+    }
+
+    pub fn apply_obstacles(&mut self, dt: f32) {
+        for o in self.obstacles.iter() {
+            o.apply(&mut self.particles, dt);
+        }
+    }
+
+    pub fn apply_rotors(&mut self, dt: f32) {
+        for rotor in self.rotors.iter() {
+            rotor.apply(&mut self.particles, dt);
+        }
+    }
+
+    pub fn apply_constraints(&mut self) {
+        constraint::apply_all(&self.constraints, &mut self.particles);
+    }
+
+    pub fn apply_pins(&mut self) {
+        for pin in self.pins.iter() {
+            pin.apply(&mut self.particles);
+        }
+    }
+
+    /// Accumulates every force on the particles: gravity, force fields, fluids, pressure and
+    /// springs.
+    pub fn apply_forces(&mut self, dt: f32) {
+        self.apply_gravity();
+        self.apply_force_fields();
+        self.apply_fluids(dt);
+        self.apply_sph(dt);
+        self.apply_pressure();
+        self.apply_springs(dt);
+    }
+
+    /// Integrates the accumulated accelerations, then applies `max_displacement`. Integrators
+    /// that sample forces at trial states get them from `apply_forces` on those states.
+    pub fn update(&mut self, dt: f32) {
+        let (integrator, damping) = (self.integrator.clone(), self.damping);
+        let mut particles = std::mem::take(&mut self.particles);
+        let mut forces = |trial: &mut Particles| {
+            std::mem::swap(&mut self.particles, trial);
+            self.apply_forces(dt);
+            std::mem::swap(&mut self.particles, trial);
+        };
+        integrator.integrate(&mut particles, dt, damping, &mut forces);
+        if let Some(max) = self.max_displacement {
+            (0..particles.len()).for_each(|i| particles.limit_step(i, max));
+        }
+        self.particles = particles;
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Runs every later `step` for `scale` times the `dt` it is given, e.g. `0.2` for bullet
+    /// time or `4` for fast forward, while the caller keeps stepping as before. Implicit
+    /// velocities are rescaled so particles keep their speed. Scales are kept positive, as a
+    /// step of zero length would lose the velocities; pause by not stepping instead.
+    pub fn set_time_scale(&mut self, scale: f32) {
+        let scale = scale.max(MIN_TIME_SCALE);
+        let ratio = scale / self.time_scale;
+        rescale_steps(&mut self.particles, ratio);
+        self.last_sub_dt *= ratio;
+        self.time_scale = scale;
+    }
+
+    /// Changes the substep count, rescaling the implicit velocities that are stored per
+    /// substep so particles keep their speed.
+    pub fn set_substeps(&mut self, substeps: usize) {
+        let substeps = substeps.max(1);
+        let current = self.substeps.max(1);
+        if substeps != current {
+            let ratio = current as f32 / substeps as f32;
+            rescale_steps(&mut self.particles, ratio);
+            self.last_sub_dt *= ratio;
+        }
+        self.substeps = substeps;
+    }
+
+    /// Seconds the last substep of `step` covered, `dt * time_scale / substeps`; `0` before
+    /// the first step.
+    pub fn last_sub_dt(&self) -> f32 {
+        self.last_sub_dt
+    }
+
+    /// Velocity of particle `i` in units per second over the last substep. Zero before the
+    /// first step.
+    pub fn velocity(&self, i: usize) -> Vec2 {
+        if self.last_sub_dt <= 0_f32 {
+            return Vec2::zero();
+        }
+        self.particles.velocity(i, self.last_sub_dt)
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        if let Some(adaptive) = &self.adaptive_substeps {
+            let substeps = adaptive.substeps(&self.particles, self.substeps);
+            self.set_substeps(substeps);
+        }
+        let dt = dt * self.time_scale;
+        let mut timings = StepTimings::default();
+        let mut clock = Clock::new(self.profile);
+        let mut total = Clock::new(self.profile);
+        self.solver.broadphase_time = self.profile.then_some(Duration::ZERO);
+
+        let substeps = self.substeps.max(1);
+        let sub_dt = dt / substeps as f32;
+        self.run_emitters(dt, sub_dt);
+        self.run_schedule(sub_dt);
+        clock.skip();
+        for _ in 0..substeps {
+            self.break_constraints();
+            self.link_lambdas.clear();
+            clock.lap(&mut timings.constraints);
+            self.apply_forces(sub_dt);
+            self.apply_heat(sub_dt);
+            self.rotors.iter_mut().for_each(|r| r.advance(sub_dt));
+            self.solver.warm_start_contacts(&mut self.particles);
+            clock.lap(&mut timings.integrate);
+            for _ in 0..self.solver_iterations.max(1) {
+                self.solve_collisions(sub_dt);
+                clock.lap(&mut timings.narrowphase);
+                self.solve_links(sub_dt);
+                self.solve_bends();
+                self.solve_rigid_clusters();
+                self.apply_obstacles(sub_dt);
+                self.apply_rotors(sub_dt);
+                self.apply_constraints();
+                self.apply_pins();
+                clock.lap(&mut timings.constraints);
+            }
+            // Twice what the particle's own gravity adds per substep, so resting contacts
+            // don't keep bouncing. Pinned particles aren't pulled at all.
+            let (gravity, attractor) = (&self.gravity, &self.attractor);
+            let rest_speed = |particles: &Particles, i: usize| {
+                if particles.pinned[i] {
+                    return 0_f32;
+                }
+                let pull = match attractor {
+                    Some(attractor) => attractor.acceleration(&particles.pos[i]).len(),
+                    None => gravity.len(),
+                };
+                2_f32 * pull * particles.gravity_scale[i].abs() * sub_dt * sub_dt
+            };
+            self.solver
+                .solve_velocities(&mut self.particles, rest_speed);
+            self.update_sleep();
+            clock.lap(&mut timings.narrowphase);
+            self.update(sub_dt);
+            clock.lap(&mut timings.integrate);
+            if self.ccd {
+                self.solve_ccd();
+            }
+            self.wrap_periodic();
+            clock.lap(&mut timings.narrowphase);
+        }
+        self.merge_resting(dt, sub_dt);
+        self.despawn_expired();
+        if let Some(interval) = self.spatial_sort_interval.filter(|_| !self.pool) {
+            self.steps_since_sort += 1;
+            if self.steps_since_sort >= interval {
+                self.steps_since_sort = 0;
+                self.sort_spatially();
+            }
+        }
+        clock.skip();
+        self.solver.refresh_index(&self.particles);
+        clock.lap(&mut timings.broadphase);
+        self.handles.track(self.particles.len());
+        // Particles truncated off `particles` directly take their data with them.
+        let handles = &self.handles;
+        self.user_data.retain(|&h, _| handles.index(h).is_some());
+        self.time += dt;
+        self.last_sub_dt = sub_dt;
+
+        if let Some(pairs) = self.solver.broadphase_time.take() {
+            // Pair finding runs inside the contact solve.
+            timings.narrowphase = timings.narrowphase.saturating_sub(pairs);
+            timings.broadphase += pairs;
+            total.lap(&mut timings.total);
+            self.timings = timings;
+        }
+    }
+}
+
+/// Scales every particle's implicit step `pos - pos_last` by `ratio`.
+fn rescale_steps(particles: &mut Particles, ratio: f32) {
+    for i in 0..particles.len() {
+        let step = &particles.pos[i] - &particles.pos_last[i];
+        particles.pos_last[i] = &particles.pos[i] - &(step * ratio);
+    }
+}
@@ -0,0 +1,43 @@
+//! Broadphases agree on the candidate pairs, and the grid survives cell sizes that make no
+//! sense.
+
+use verlet_core::broadphase::{Aabb, BruteForce, Quadtree, SpatialGrid};
+use verlet_core::{Broadphase, Particle, Particles, Vec2};
+
+#[test]
+fn degenerate_cell_sizes_still_find_pairs() {
+    let particles = Particles::from(vec![
+        Particle::new(Vec2::new(0_f32, 0_f32)),
+        Particle::new(Vec2::new(30_f32, 0_f32)),
+        Particle::new(Vec2::new(500_f32, 0_f32)),
+    ]);
+    for size in [0_f32, -5_f32, f32::NAN, 0.001_f32] {
+        let mut grid = SpatialGrid::new(size);
+        assert_eq!(grid.pairs(&particles), vec![(0, 1)]);
+        assert!(grid.current_cell_size() >= 1_f32);
+    }
+}
+
+#[test]
+fn loose_quadtree_sinks_straddling_particles() {
+    // Mixed sizes on a lattice finer than the particles, so many boxes straddle splits.
+    let particles: Particles = (0..400)
+        .map(|i| {
+            let pos = Vec2::new((i % 20) as f32 * 17_f32, (i / 20) as f32 * 13_f32);
+            Particle::with_mass(pos, 4_f32 + (i % 7) as f32 * 3_f32, 1_f32)
+        })
+        .collect();
+    let mut tree = Quadtree::new(4, 8);
+    let mut found = tree.pairs(&particles);
+    found.sort_unstable();
+    let overlapping: Vec<_> = BruteForce
+        .pairs(&particles)
+        .into_iter()
+        .filter(|&(i, k)| {
+            Aabb::of_particle(&particles, i).overlaps(&Aabb::of_particle(&particles, k))
+        })
+        .collect();
+    assert_eq!(found, overlapping);
+    // The root comes first and holds only what fits no quadrant's loose bounds.
+    assert_eq!(tree.cells()[0].1, 0);
+}
@@ -0,0 +1,53 @@
+//! Seeded worlds repeat their runs exactly, randomized spawns included.
+
+use verlet_core::{Color, Constraint, Emitter, Vec2, World};
+
+/// A world whose emitter randomizes directions, speeds, radii and colors.
+fn spray(seed: u64) -> World {
+    let mut world = World::new(
+        Vec2::new(0_f32, -1000_f32),
+        vec![Constraint::circle(Vec2::zero(), 300_f32)],
+    );
+    let mut emitter = Emitter::new(Vec2::new(0_f32, 200_f32), 30_f32);
+    emitter.velocity = Vec2::new(200_f32, 0_f32);
+    emitter.spread = 1_f32;
+    emitter.speed_variation = 0.3_f32;
+    emitter.radius = 5_f32..=15_f32;
+    emitter.palette = vec![
+        Color::rgb(255, 0, 0),
+        Color::rgb(0, 255, 0),
+        Color::rgb(0, 0, 255),
+    ];
+    world.add_emitter(emitter);
+    world.seed(seed);
+    world
+}
+
+fn run(mut world: World) -> World {
+    for _ in 0..240 {
+        world.step(1_f32 / 60_f32);
+    }
+    world
+}
+
+#[test]
+fn same_seed_same_run() {
+    let (a, b) = (run(spray(7)), run(spray(7)));
+    assert_eq!(a.particles.len(), b.particles.len());
+    for (p, q) in a.particles.iter().zip(b.particles.iter()) {
+        assert_eq!((p.pos.x, p.pos.y), (q.pos.x, q.pos.y));
+        assert_eq!(p.radius, q.radius);
+        assert_eq!(p.color, q.color);
+    }
+}
+
+#[test]
+fn different_seeds_differ() {
+    let (a, b) = (run(spray(7)), run(spray(8)));
+    let same = a
+        .particles
+        .iter()
+        .zip(b.particles.iter())
+        .all(|(p, q)| p.pos.x == q.pos.x && p.pos.y == q.pos.y);
+    assert!(!same);
+}
@@ -0,0 +1,24 @@
+//! Emitters held back by the world's particle cap keep their remaining count.
+
+use verlet_core::{Emitter, Vec2, World};
+
+#[test]
+fn capped_spawns_dont_count_towards_max_count() {
+    let mut world = World::new(Vec2::zero(), Vec::new());
+    world.max_particles = Some(3);
+    let mut emitter = Emitter::new(Vec2::zero(), 60_f32);
+    emitter.max_count = 10;
+    world.add_emitter(emitter);
+
+    for _ in 0..60 {
+        world.step(1_f32 / 60_f32);
+    }
+    assert_eq!(world.particles.len(), 3);
+    assert_eq!(world.emitters[0].emitted(), 3);
+
+    world.max_particles = None;
+    for _ in 0..60 {
+        world.step(1_f32 / 60_f32);
+    }
+    assert_eq!(world.particles.len(), 10);
+}
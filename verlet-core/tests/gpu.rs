@@ -0,0 +1,38 @@
+//! Worlds using features the GPU solve lacks step the same with a GPU set as without.
+
+#![cfg(feature = "gpu")]
+
+use verlet_core::{Constraint, GpuSolver, Particle, Vec2, World};
+
+fn bouncy_pile() -> World {
+    let mut world = World::new(
+        Vec2::new(0_f32, -1000_f32),
+        vec![Constraint::circle(Vec2::zero(), 300_f32)],
+    );
+    for i in 0..100 {
+        let pos = Vec2::new((i % 10) as f32 * 40_f32 - 180_f32, (i / 10) as f32 * 25_f32);
+        let mut p = Particle::new(pos);
+        p.restitution = 0.8_f32;
+        world.add_particle(p);
+    }
+    world
+}
+
+#[test]
+fn restitution_steps_the_same_with_a_gpu() {
+    // Machines without an adapter have nothing to compare.
+    let Ok(gpu) = GpuSolver::new() else {
+        return;
+    };
+    let mut cpu = bouncy_pile();
+    let mut with_gpu = bouncy_pile();
+    with_gpu.solver.set_gpu(Some(gpu));
+    assert!(!with_gpu.solver.gpu_applies(&with_gpu.particles));
+    for _ in 0..120 {
+        cpu.step(1_f32 / 60_f32);
+        with_gpu.step(1_f32 / 60_f32);
+    }
+    for (a, b) in cpu.particles.pos.iter().zip(with_gpu.particles.pos.iter()) {
+        assert_eq!((a.x, a.y), (b.x, b.y));
+    }
+}
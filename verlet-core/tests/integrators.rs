@@ -0,0 +1,76 @@
+//! Energy drift of the integrators in force-driven scenes without contacts.
+
+use verlet_core::{Attractor, IntegratorKind, Particle, Tether, Vec2, World};
+
+const DT: f32 = 1_f32 / 60_f32;
+const STEPS: usize = 6000;
+
+/// Largest relative deviation from the initial energy over the run.
+fn max_drift(mut world: World, energy: impl Fn(&World) -> f32) -> f32 {
+    let e0 = energy(&world);
+    let mut drift = 0_f32;
+    for _ in 0..STEPS {
+        world.step(DT);
+        drift = drift.max((energy(&world) - e0).abs() / e0.abs());
+    }
+    drift
+}
+
+fn kinetic(p: &Particle) -> f32 {
+    let v = p.velocity(DT);
+    0.5_f32 * p.mass * v.dot(&v)
+}
+
+fn spring(kind: IntegratorKind) -> f32 {
+    const STIFFNESS: f32 = 50_f32;
+    let mut world = World::new(Vec2::zero(), Vec::new());
+    world.integrator = kind.build();
+    let i = world.add_particle(Particle::new(Vec2::new(100_f32, 0_f32)));
+    world.add_tether(Tether::new(i, Vec2::zero(), STIFFNESS, 0_f32));
+    max_drift(world, |w| {
+        let p = w.particles.particle(0);
+        kinetic(&p) + 0.5_f32 * STIFFNESS * p.pos.dot(&p.pos)
+    })
+}
+
+fn orbit(kind: IntegratorKind) -> f32 {
+    const GM: f32 = 90_000_000_f32;
+    let mut world = World::new(Vec2::zero(), Vec::new());
+    world.integrator = kind.build();
+    world.attractor = Some(Attractor::new(Vec2::zero(), GM));
+    let mut p = Particle::new(Vec2::new(200_f32, 0_f32));
+    p.set_velocity(Vec2::new(0_f32, 500_f32), DT);
+    world.add_particle(p);
+    max_drift(world, |w| {
+        let p = w.particles.particle(0);
+        kinetic(&p) - p.mass * GM / p.pos.len()
+    })
+}
+
+#[test]
+fn rk4_drifts_less_than_verlet_on_a_spring() {
+    let verlet = spring(IntegratorKind::Verlet);
+    let rk4 = spring(IntegratorKind::Rk4);
+    assert!(rk4 < 1e-3_f32, "rk4 drift {}", rk4);
+    assert!(rk4 * 10_f32 < verlet, "rk4 {} vs verlet {}", rk4, verlet);
+}
+
+#[test]
+fn rk4_drifts_less_than_verlet_in_an_orbit() {
+    let verlet = orbit(IntegratorKind::Verlet);
+    let rk4 = orbit(IntegratorKind::Rk4);
+    assert!(rk4 < 0.02_f32, "rk4 drift {}", rk4);
+    assert!(rk4 * 5_f32 < verlet, "rk4 {} vs verlet {}", rk4, verlet);
+}
+
+#[test]
+fn semi_implicit_euler_matches_verlet_on_a_spring() {
+    let verlet = spring(IntegratorKind::Verlet);
+    let euler = spring(IntegratorKind::SemiImplicitEuler);
+    assert!(
+        (verlet - euler).abs() < 1e-3_f32,
+        "euler {} vs verlet {}",
+        euler,
+        verlet
+    );
+}
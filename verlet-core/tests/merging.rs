@@ -0,0 +1,39 @@
+//! Resting detection of `Merging` with several substeps per step.
+
+use verlet_core::Merging;
+use verlet_core::{Particle, Vec2, World};
+
+const DT: f32 = 1_f32 / 60_f32;
+
+/// Runs two touching particles sliding past each other at `speed` relative to one another
+/// and returns the particle count left.
+fn slide(speed: f32) -> usize {
+    let mut world = World::new(Vec2::zero(), Vec::new());
+    world.substeps = 4;
+    world.merging = Some(Merging {
+        max_speed: 30_f32,
+        contact_time: 0.05_f32,
+        max_radius: 60_f32,
+    });
+    let sub_dt = DT / world.substeps as f32;
+    for (y, vx) in [(0_f32, -0.5_f32 * speed), (22.5_f32, 0.5_f32 * speed)] {
+        let mut p = Particle::new(Vec2::new(0_f32, y));
+        p.radius = 10_f32;
+        p.set_velocity(Vec2::new(vx, 0_f32), sub_dt);
+        world.add_particle(p);
+    }
+    for _ in 0..6 {
+        world.step(DT);
+    }
+    world.particles.len()
+}
+
+#[test]
+fn slow_pairs_merge() {
+    assert_eq!(slide(10_f32), 1);
+}
+
+#[test]
+fn fast_pairs_stay_apart_with_substeps() {
+    assert_eq!(slide(100_f32), 2);
+}
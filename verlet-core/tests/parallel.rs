@@ -0,0 +1,66 @@
+//! Resolving contacts on a thread pool gives the serial result wherever the pair order
+//! doesn't matter, warm starting included.
+
+#![cfg(feature = "parallel")]
+
+use verlet_core::{Constraint, Particle, Vec2, World};
+
+const DT: f32 = 1_f32 / 60_f32;
+
+/// A pile falling into a bowl, solved one island at a time.
+fn pile(threads: usize) -> World {
+    let mut world = World::new(
+        Vec2::new(0_f32, -1000_f32),
+        vec![Constraint::circle(Vec2::zero(), 300_f32)],
+    );
+    world.solver.set_thread_count(threads);
+    world.solver.split_islands = true;
+    world.solver.warm_start = 0.5_f32;
+    for i in 0..10 {
+        for k in 0..10 {
+            let pos = Vec2::new(i as f32 * 40_f32 - 180_f32, k as f32 * 25_f32 - 100_f32);
+            world.add_particle(Particle::new(pos));
+        }
+    }
+    world
+}
+
+/// Overlapping pairs far enough apart not to touch each other, solved in stripes.
+fn pairs(threads: usize) -> World {
+    let mut world = World::new(Vec2::zero(), Vec::new());
+    world.solver.set_thread_count(threads);
+    world.solver.warm_start = 0.5_f32;
+    for i in 0..40 {
+        let x = i as f32 * 100_f32;
+        world.add_particle(Particle::new(Vec2::new(x, 0_f32)));
+        world.add_particle(Particle::new(Vec2::new(x + 15_f32, 3_f32)));
+    }
+    world
+}
+
+fn assert_same_run(scene: fn(usize) -> World) {
+    let (mut serial, mut threaded) = (scene(1), scene(4));
+    assert_eq!(threaded.solver.thread_count(), 4);
+    for _ in 0..240 {
+        serial.step(DT);
+        threaded.step(DT);
+        for (a, b) in serial
+            .particles
+            .pos
+            .iter()
+            .zip(threaded.particles.pos.iter())
+        {
+            assert!((a - b).len() < 1e-3_f32);
+        }
+    }
+}
+
+#[test]
+fn islands_match_serial() {
+    assert_same_run(pile);
+}
+
+#[test]
+fn stripes_match_serial() {
+    assert_same_run(pairs);
+}
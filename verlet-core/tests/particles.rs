@@ -0,0 +1,38 @@
+//! Every column of `Particles` moves together when particles are added, removed or reordered.
+
+use verlet_core::{Particle, Particles, Vec2};
+
+fn numbered(count: usize) -> Particles {
+    (0..count)
+        .map(|i| {
+            let mut p = Particle::new(Vec2::new(i as f32, 0_f32));
+            p.radius = i as f32 + 1_f32;
+            p.user_data = i as u64;
+            p
+        })
+        .collect()
+}
+
+#[test]
+fn columns_stay_aligned() {
+    let mut particles = numbered(6);
+    particles.swap(0, 5);
+    particles.retain_mask(&[true, false, true, false, true, true]);
+    let kept: Vec<u64> = particles.user_data.to_vec();
+    assert_eq!(kept, vec![5, 2, 4, 0]);
+    for (i, p) in particles.iter().enumerate() {
+        assert_eq!(p.pos.x, kept[i] as f32);
+        assert_eq!(p.radius, kept[i] as f32 + 1_f32);
+    }
+    assert_eq!(particles.pos.len(), particles.color.len());
+    assert!(particles.get(4).is_none());
+}
+
+#[test]
+fn converts_to_and_from_rows() {
+    let particles = numbered(3);
+    let rows: Vec<Particle> = particles.clone().into();
+    let back = Particles::from(rows);
+    assert_eq!(back.user_data, particles.user_data);
+    assert_eq!(back.radius, particles.radius);
+}
@@ -0,0 +1,85 @@
+//! A pooled world reuses the slots of removed particles instead of compacting `particles`.
+
+use verlet_core::{Emitter, Event, Particle, Vec2, World, WorldBuilder};
+
+const DT: f32 = 1_f32 / 60_f32;
+
+fn pooled(count: usize) -> World {
+    let (_, mut world) = WorldBuilder::new().capacity(count).pool(true).build();
+    for i in 0..count {
+        world.add_particle(Particle::new(Vec2::new(i as f32 * 50_f32, 0_f32)));
+    }
+    world
+}
+
+#[test]
+fn removed_slots_are_reused() {
+    let mut world = pooled(5);
+    let handles: Vec<_> = (0..5).map(|i| world.handle(i).unwrap()).collect();
+    world.add_link(3, 4);
+    world.drain_events();
+    world.remove(handles[1]);
+    world.remove(handles[3]);
+
+    assert_eq!(world.particles.len(), 5);
+    assert_eq!(world.particle_count(), 3);
+    assert!(world.links.is_empty());
+    for (i, &h) in handles.iter().enumerate() {
+        let alive = i != 1 && i != 3;
+        assert_eq!(world.index(h), alive.then_some(i));
+    }
+    for event in world.drain_events() {
+        if let Event::ParticlesRemapped(remap) = event {
+            assert!(remap
+                .iter()
+                .enumerate()
+                .all(|(i, n)| n.is_none_or(|n| n == i)));
+        }
+    }
+
+    let a = world.add_particle(Particle::new(Vec2::zero()));
+    let b = world.add_particle(Particle::new(Vec2::zero()));
+    let c = world.add_particle(Particle::new(Vec2::zero()));
+    assert_eq!((a, b, c), (3, 1, 5));
+    assert_ne!(world.handle(1), Some(handles[1]));
+    assert_eq!(world.particle_count(), 6);
+}
+
+#[test]
+fn dead_slots_stay_out_of_the_simulation() {
+    let mut world = pooled(2);
+    world.remove(world.handle(0).unwrap());
+    let dead = world.particles.pos[0].clone();
+    for _ in 0..30 {
+        world.step(DT);
+    }
+    assert_eq!(
+        (world.particles.pos[0].x, world.particles.pos[0].y),
+        (dead.x, dead.y)
+    );
+    assert!(world
+        .query_aabb(dead.clone() - Vec2::new(1_f32, 1_f32), dead.clone())
+        .is_empty());
+    assert_eq!(world.pick(&dead), None);
+    assert_eq!(world.islands(), vec![vec![1]]);
+}
+
+#[test]
+fn emitter_churn_keeps_storage_steady() {
+    let mut world = pooled(0);
+    let mut emitter = Emitter::new(Vec2::zero(), 120_f32);
+    emitter.velocity = Vec2::new(0_f32, 300_f32);
+    emitter.radius = 2_f32..=2_f32;
+    emitter.lifetime = Some(0.5_f32);
+    world.add_emitter(emitter);
+    for _ in 0..60 {
+        world.step(DT);
+    }
+    let len = world.particles.len();
+    assert!(world.particle_count() > 0);
+    for _ in 0..120 {
+        world.step(DT);
+        assert_eq!(world.particles.len(), len);
+        assert!(world.particle_count() < len);
+    }
+}
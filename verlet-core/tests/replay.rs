@@ -0,0 +1,47 @@
+//! Replayed commands that name missing indices leave the world alone.
+
+use verlet_core::replay::Command;
+use verlet_core::{Constraint, Tether, Vec2, World};
+
+#[test]
+fn out_of_range_commands_are_ignored() {
+    let mut world = World::new(
+        Vec2::new(0_f32, -1000_f32),
+        vec![Constraint::circle(Vec2::zero(), 300_f32)],
+    );
+    let commands = [
+        Command::MoveConstraint {
+            index: 5,
+            center: Vec2::zero(),
+        },
+        Command::SetConstraint {
+            index: 5,
+            constraint: Constraint::circle(Vec2::zero(), 10_f32),
+        },
+        Command::MoveEmitter {
+            index: 0,
+            position: Vec2::zero(),
+        },
+        Command::SetEmitterRate {
+            index: 0,
+            rate: 1_f32,
+        },
+        Command::AddTether(Tether::new(3, Vec2::zero(), 500_f32, 20_f32)),
+        Command::MoveTether {
+            index: 0,
+            point: Vec2::zero(),
+        },
+        Command::RemoveTether(0),
+        Command::RemoveObstacle(0),
+        Command::RemoveForceField(0),
+        Command::SplitParticle { index: 0, count: 3 },
+        Command::RemoveParticle(0),
+        Command::RemoveParticles(vec![0, 1]),
+    ];
+    for command in &commands {
+        command.apply(&mut world);
+    }
+    assert!(world.particles.is_empty());
+    assert!(world.tethers.is_empty());
+    assert_eq!(world.constraints.len(), 1);
+}
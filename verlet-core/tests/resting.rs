@@ -0,0 +1,41 @@
+//! Contacts stop bouncing below the speed a particle's own pull adds per step, wherever the
+//! pull comes from.
+
+use verlet_core::{Attractor, Particle, Sleep, Vec2, World};
+
+const DT: f32 = 1_f32 / 60_f32;
+
+#[test]
+fn particles_resting_on_a_planet_fall_asleep() {
+    let mut world = World::new(Vec2::zero(), Vec::new());
+    world.attractor = Some(Attractor::new(Vec2::zero(), 90_000_000_f32));
+    world.solver.sleep = Some(Sleep::default());
+    let mut planet = Particle::with_mass(Vec2::zero(), 100_f32, 1_f32);
+    planet.pinned = true;
+    world.add_particle(planet);
+    let mut p = Particle::new(Vec2::new(0_f32, 125_f32));
+    p.restitution = 0.5_f32;
+    world.add_particle(p);
+    for _ in 0..300 {
+        world.step(DT);
+    }
+    assert!(world.particles.asleep[1]);
+}
+
+#[test]
+fn floating_particles_still_bounce() {
+    let mut world = World::new(Vec2::new(0_f32, -1000_f32), Vec::new());
+    let mut wall = Particle::new(Vec2::zero());
+    wall.pinned = true;
+    wall.restitution = 1_f32;
+    world.add_particle(wall);
+    let mut p = Particle::new(Vec2::new(60_f32, 0_f32));
+    p.gravity_scale = 0_f32;
+    p.restitution = 1_f32;
+    p.set_velocity(Vec2::new(-20_f32, 0_f32), DT);
+    world.add_particle(p);
+    for _ in 0..180 {
+        world.step(DT);
+    }
+    assert!(world.velocity(1).x > 10_f32);
+}
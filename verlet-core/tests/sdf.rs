@@ -0,0 +1,28 @@
+//! Sampled distance fields refuse spacings they can't be sampled at.
+
+use verlet_core::{SdfGrid, Vec2};
+
+fn circle(p: &Vec2) -> f32 {
+    p.len() - 50_f32
+}
+
+#[test]
+fn bad_cell_sizes_give_no_grid() {
+    for cell in [0_f32, -1_f32, f32::NAN, f32::INFINITY] {
+        let grid = SdfGrid::from_fn(
+            Vec2::new(-100_f32, -100_f32),
+            Vec2::new(100_f32, 100_f32),
+            cell,
+            circle,
+        );
+        assert!(grid.is_none());
+    }
+    let grid = SdfGrid::from_fn(
+        Vec2::new(-100_f32, -100_f32),
+        Vec2::new(100_f32, 100_f32),
+        10_f32,
+        circle,
+    )
+    .unwrap();
+    assert_eq!((grid.width, grid.height), (21, 21));
+}
@@ -0,0 +1,38 @@
+//! The eight-wide integrator matches `Particles::update` bit for bit.
+
+#![cfg(feature = "simd")]
+
+use verlet_core::{Integrator, Particle, Particles, Vec2, Verlet};
+
+#[test]
+fn simd_integration_matches_scalar() {
+    // Not a multiple of eight, so the scalar remainder runs too.
+    let particles: Particles = (0..21)
+        .map(|i| {
+            let f = i as f32;
+            let mut p = Particle::new(Vec2::new(f * 13.7_f32, -f * 3.1_f32));
+            p.pos_last = Vec2::new(f * 13.5_f32, -f * 2.9_f32 + 0.3_f32);
+            p.acc = Vec2::new(f * 7_f32 - 40_f32, -981_f32);
+            p.damping = f * 0.05_f32;
+            p.pinned = i % 5 == 0;
+            p.asleep = i % 7 == 3;
+            p
+        })
+        .collect();
+    let dt = 1_f32 / 240_f32;
+
+    let mut simd = particles.clone();
+    Verlet.integrate(&mut simd, dt, 0.1_f32, &mut |_: &mut Particles| {});
+    let mut scalar = particles;
+    for i in 0..scalar.len() {
+        scalar.update(i, dt, 0.1_f32);
+    }
+
+    for (a, b) in simd.iter().zip(scalar.iter()) {
+        assert_eq!(a.pos.x.to_bits(), b.pos.x.to_bits());
+        assert_eq!(a.pos.y.to_bits(), b.pos.y.to_bits());
+        assert_eq!(a.pos_last.x.to_bits(), b.pos_last.x.to_bits());
+        assert_eq!(a.pos_last.y.to_bits(), b.pos_last.y.to_bits());
+        assert_eq!(a.age.to_bits(), b.age.to_bits());
+    }
+}
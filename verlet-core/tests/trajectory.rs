@@ -0,0 +1,40 @@
+//! Units of the trajectory export.
+
+use verlet_core::{Particle, TrajectoryWriter, Vec2, World};
+
+const DT: f32 = 1_f32 / 60_f32;
+const G: f32 = 1000_f32;
+
+#[test]
+fn exported_velocity_is_per_second_with_substeps() {
+    let mut world = World::new(Vec2::new(0_f32, -G), Vec::new());
+    world.substeps = 4;
+    world.add_particle(Particle::new(Vec2::zero()));
+    let mut out = Vec::new();
+    {
+        let mut writer = TrajectoryWriter::new(&mut out).unwrap();
+        for _ in 0..60 {
+            world.step(DT);
+            writer.record(&world).unwrap();
+        }
+    }
+
+    let text = String::from_utf8(out).unwrap();
+    let last: Vec<f32> = text
+        .lines()
+        .last()
+        .unwrap()
+        .split(',')
+        .map(|field| field.parse().unwrap())
+        .collect();
+    let (time, vy) = (last[1], last[5]);
+    assert!((time - world.time).abs() < 1e-6_f32);
+    // The velocity spans the last substep, so it lags `g * t` by half of one.
+    let expected = -G * time;
+    assert!(
+        (vy - expected).abs() < G * world.last_sub_dt(),
+        "vy {} expected {}",
+        vy,
+        expected
+    );
+}
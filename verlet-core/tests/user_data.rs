@@ -0,0 +1,98 @@
+//! Data attached to particles through `World::set_user_data`.
+
+use verlet_core::{Event, Merging, Particle, Vec2, World};
+
+const DT: f32 = 1_f32 / 60_f32;
+
+#[test]
+fn data_follows_its_particle() {
+    let mut world = World::new(Vec2::new(0_f32, 100_f32), Vec::new());
+    let handles: Vec<_> = (0..20)
+        .map(|i| world.insert(Particle::new(Vec2::new((19 - i) as f32 * 50_f32, 0_f32))))
+        .collect();
+    for (i, &h) in handles.iter().enumerate() {
+        assert!(world.set_user_data(h, format!("p{i}")));
+    }
+    world.step(DT);
+    world.remove(handles[0]);
+    world.step(DT);
+
+    for (i, &h) in handles.iter().enumerate().skip(1) {
+        assert_eq!(world.user_data::<String>(h), Some(&format!("p{i}")));
+        assert_eq!(world.user_data::<u32>(h), None);
+    }
+    assert!(!world.set_user_data(handles[0], 7_u32));
+}
+
+#[test]
+fn removed_data_comes_back_in_an_event() {
+    let mut world = World::new(Vec2::zero(), Vec::new());
+    let kept = world.insert(Particle::new(Vec2::zero()));
+    let mut short = Particle::new(Vec2::new(50_f32, 0_f32));
+    short.lifetime = Some(DT * 0.5_f32);
+    let expiring = world.insert(short);
+    world.set_user_data(kept, 1_u32);
+    world.set_user_data(expiring, 2_u32);
+    world.drain_events();
+    world.step(DT);
+
+    let removed: Vec<_> = world
+        .drain_events()
+        .into_iter()
+        .filter_map(|e| match e {
+            Event::UserDataRemoved(removed) => Some(removed),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].0, expiring);
+    assert_eq!(removed[0].1.downcast_ref::<u32>(), Some(&2));
+    assert_eq!(world.user_data::<u32>(kept), Some(&1));
+}
+
+#[test]
+fn merged_data_comes_back_in_an_event() {
+    let mut world = World::new(Vec2::zero(), Vec::new());
+    world.merging = Some(Merging {
+        max_speed: 30_f32,
+        contact_time: 0.05_f32,
+        max_radius: 60_f32,
+    });
+    let handles: Vec<_> = [0_f32, 20_f32]
+        .into_iter()
+        .map(|y| {
+            let mut p = Particle::new(Vec2::new(0_f32, y));
+            p.radius = 10_f32;
+            world.insert(p)
+        })
+        .collect();
+    for (i, &h) in handles.iter().enumerate() {
+        world.set_user_data(h, format!("p{i}"));
+    }
+    let mut merged = None;
+    for _ in 0..10 {
+        world.step(DT);
+        for event in world.drain_events() {
+            if let Event::ParticlesMerged {
+                kept,
+                absorbed,
+                data,
+            } = event
+            {
+                merged = Some((kept, absorbed, data));
+            }
+        }
+    }
+
+    let (kept, absorbed, data) = merged.expect("resting pair merges");
+    assert_eq!(world.particles.len(), 1);
+    assert_eq!(world.index(kept), Some(0));
+    assert_eq!(world.index(absorbed), None);
+    let name = |h| format!("p{}", handles.iter().position(|&x| x == h).unwrap());
+    assert_eq!(world.user_data::<String>(kept), Some(&name(kept)));
+    assert_eq!(
+        data.as_deref().and_then(|d| d.downcast_ref::<String>()),
+        Some(&name(absorbed))
+    );
+}
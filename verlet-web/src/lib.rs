@@ -0,0 +1,237 @@
+use std::f64::consts::TAU;
+
+use wasm_bindgen::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+use verlet_core::{
+    Color, Constraint, Emitter, FixedTimestep, Obstacle, ParticleHandle, Periodic, Tether, Vec2,
+    World,
+};
+
+/// Browser front end for the demo. The page calls `frame` from `requestAnimationFrame` and
+/// forwards pointer events in canvas pixels.
+#[wasm_bindgen]
+pub struct Demo {
+    world: World,
+    timestep: FixedTimestep,
+    width: f32,
+    height: f32,
+    /// Particle held by the pointer; its tether is looked up again on every use.
+    grabbed: Option<ParticleHandle>,
+    dragging: bool,
+}
+
+#[wasm_bindgen]
+impl Demo {
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: f32, height: f32) -> Demo {
+        let mut world = World::default();
+        let mut emitter = Emitter::new(Vec2::new(100_f32, 200_f32), 2_f32);
+        emitter.max_count = 20;
+        world.add_emitter(emitter);
+        Demo {
+            world,
+            timestep: FixedTimestep::default(),
+            width,
+            height,
+            grabbed: None,
+            dragging: false,
+        }
+    }
+
+    /// Reseeds the simulation's random number generator, so the page can replay a run.
+    pub fn seed(&mut self, seed: u64) {
+        self.world.seed(seed);
+    }
+
+    /// Advances the simulation by `elapsed` seconds of wall time and draws it.
+    pub fn frame(&mut self, elapsed: f32, ctx: &CanvasRenderingContext2d) -> Result<(), JsValue> {
+        let steps = self.timestep.advance(elapsed);
+        for _ in 0..steps {
+            self.world.step(self.timestep.dt);
+        }
+        self.world.drain_events();
+        if self.grab_tether().is_none() {
+            self.grabbed = None;
+        }
+        self.draw(ctx)
+    }
+
+    /// Grabs the particle under the pointer, or starts dragging the container.
+    pub fn pointer_down(&mut self, x: f32, y: f32) {
+        let cursor = self.to_world(x, y);
+        self.grabbed = self.world.pick(&cursor).and_then(|i| {
+            let tether = Tether::new(i, cursor, 500_f32, 20_f32);
+            self.world.add_tether(tether)?;
+            self.world.handle(i)
+        });
+        self.dragging = true;
+    }
+
+    pub fn pointer_move(&mut self, x: f32, y: f32) {
+        if !self.dragging {
+            return;
+        }
+        let point = self.to_world(x, y);
+        match self.grabbed {
+            Some(_) => {
+                if let Some(t) = self.grab_tether() {
+                    self.world.tethers[t].point = point;
+                }
+            }
+            None => {
+                if let Some(c) = self.world.constraints.first_mut() {
+                    c.move_to(point);
+                }
+            }
+        }
+    }
+
+    pub fn pointer_up(&mut self) {
+        if let Some(t) = self.grab_tether() {
+            self.world.tethers.remove(t);
+        }
+        self.grabbed = None;
+        self.dragging = false;
+    }
+
+    pub fn particle_count(&self) -> usize {
+        self.world.particle_count()
+    }
+}
+
+impl Demo {
+    /// Index of the pointer's tether, if its particle is still there.
+    fn grab_tether(&self) -> Option<usize> {
+        let particle = self.world.index(self.grabbed?)?;
+        self.world
+            .tethers
+            .iter()
+            .rposition(|t| t.particle == particle)
+    }
+
+    /// Canvas pixels have the origin top left with y down; the world is centered with y up.
+    fn to_world(&self, x: f32, y: f32) -> Vec2 {
+        Vec2::new(x - 0.5_f32 * self.width, 0.5_f32 * self.height - y)
+    }
+
+    fn draw(&self, ctx: &CanvasRenderingContext2d) -> Result<(), JsValue> {
+        let (w, h) = (self.width as f64, self.height as f64);
+        ctx.set_transform(1_f64, 0_f64, 0_f64, 1_f64, 0_f64, 0_f64)?;
+        ctx.set_fill_style_str("black");
+        ctx.fill_rect(0_f64, 0_f64, w, h);
+        ctx.set_transform(1_f64, 0_f64, 0_f64, -1_f64, 0.5_f64 * w, 0.5_f64 * h)?;
+
+        ctx.set_fill_style_str("white");
+        for constraint in self.world.constraints.iter() {
+            match constraint {
+                Constraint::Circle { center, radius } => circle(ctx, center, *radius)?,
+                Constraint::Box { min, max } => ctx.fill_rect(
+                    min.x as f64,
+                    min.y as f64,
+                    (max.x - min.x) as f64,
+                    (max.y - min.y) as f64,
+                ),
+                Constraint::Polygon { points } => polygon(ctx, points),
+                Constraint::Sdf { field } => {
+                    let cell = field.cell as f64;
+                    for c in field.inside_cells() {
+                        let (x, y) = (c.x as f64 - 0.5_f64 * cell, c.y as f64 - 0.5_f64 * cell);
+                        ctx.fill_rect(x, y, cell, cell);
+                    }
+                }
+            }
+        }
+        if let Some(Periodic { min, max }) = &self.world.periodic {
+            ctx.fill_rect(
+                min.x as f64,
+                min.y as f64,
+                (max.x - min.x) as f64,
+                (max.y - min.y) as f64,
+            );
+        }
+
+        ctx.set_fill_style_str("gray");
+        ctx.set_stroke_style_str("gray");
+        ctx.set_line_cap("round");
+        for obstacle in self.world.obstacles.iter() {
+            match obstacle {
+                Obstacle::Circle { center, radius } => circle(ctx, center, *radius)?,
+                Obstacle::Segment { a, b, .. } => line(ctx, a, b, 2_f32),
+                Obstacle::Capsule { a, b, radius, .. } => line(ctx, a, b, 2_f32 * radius),
+                Obstacle::Polyline { points, .. } => {
+                    for w in points.windows(2) {
+                        line(ctx, &w[0], &w[1], 2_f32);
+                    }
+                }
+                Obstacle::Sdf { field } => {
+                    let cell = field.cell as f64;
+                    for c in field.inside_cells() {
+                        let (x, y) = (c.x as f64 - 0.5_f64 * cell, c.y as f64 - 0.5_f64 * cell);
+                        ctx.fill_rect(x, y, cell, cell);
+                    }
+                }
+            }
+        }
+        for rotor in self.world.rotors.iter() {
+            for (a, b) in rotor.segments() {
+                line(ctx, &a, &b, 2_f32 * rotor.thickness);
+            }
+        }
+
+        let alpha = self.timestep.alpha();
+        ctx.set_stroke_style_str("lightgray");
+        for link in self.world.links.iter() {
+            let a = self.world.particles.interpolated(link.a, alpha);
+            let b = self.world.particles.interpolated(link.b, alpha);
+            line(ctx, &a, &b, 2_f32);
+        }
+
+        let particles = &self.world.particles;
+        for i in (0..particles.len()).filter(|&i| particles.alive[i]) {
+            ctx.set_global_alpha(particles.life_fraction(i) as f64);
+            ctx.set_fill_style_str(&css(particles.color[i]));
+            circle(ctx, &particles.interpolated(i, alpha), particles.radius[i])?;
+        }
+        ctx.set_global_alpha(1_f64);
+
+        ctx.set_stroke_style_str("yellow");
+        for tether in self.world.tethers.iter() {
+            let p = self.world.particles.interpolated(tether.particle, alpha);
+            line(ctx, &p, &tether.point, 1_f32);
+        }
+        Ok(())
+    }
+}
+
+fn css(c: Color) -> String {
+    format!("rgb({}, {}, {})", c.r, c.g, c.b)
+}
+
+fn circle(ctx: &CanvasRenderingContext2d, center: &Vec2, radius: f32) -> Result<(), JsValue> {
+    ctx.begin_path();
+    ctx.arc(center.x as f64, center.y as f64, radius as f64, 0_f64, TAU)?;
+    ctx.fill();
+    Ok(())
+}
+
+fn polygon(ctx: &CanvasRenderingContext2d, points: &[Vec2]) {
+    ctx.begin_path();
+    for (i, p) in points.iter().enumerate() {
+        if i == 0 {
+            ctx.move_to(p.x as f64, p.y as f64);
+        } else {
+            ctx.line_to(p.x as f64, p.y as f64);
+        }
+    }
+    ctx.close_path();
+    ctx.fill();
+}
+
+fn line(ctx: &CanvasRenderingContext2d, a: &Vec2, b: &Vec2, width: f32) {
+    ctx.set_line_width(width as f64);
+    ctx.begin_path();
+    ctx.move_to(a.x as f64, a.y as f64);
+    ctx.line_to(b.x as f64, b.y as f64);
+    ctx.stroke();
+}
@@ -0,0 +1,39 @@
+use utils::vec::Vec2;
+
+const MIN_ZOOM: f32 = 0.05_f32;
+const MAX_ZOOM: f32 = 20_f32;
+
+/// Window view of the world: the world point `center` is drawn at the middle of the window,
+/// scaled by `zoom`.
+pub struct Camera {
+    pub center: Vec2,
+    pub zoom: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            center: Vec2::zero(),
+            zoom: 1_f32,
+        }
+    }
+}
+
+impl Camera {
+    /// World position under the window point `screen`, given relative to the window center.
+    pub fn to_world(&self, screen: &Vec2) -> Vec2 {
+        screen.clone() / self.zoom + self.center.clone()
+    }
+
+    /// Moves the view with a cursor drag of `delta` window units, so the world follows it.
+    pub fn pan(&mut self, delta: &Vec2) {
+        self.center -= delta.clone() / self.zoom;
+    }
+
+    /// Scales the zoom by `factor`, keeping the world point under `screen` in place.
+    pub fn zoom_at(&mut self, screen: &Vec2, factor: f32) {
+        let before = self.to_world(screen);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.center += before - self.to_world(screen);
+    }
+}
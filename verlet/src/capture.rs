@@ -0,0 +1,63 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use nannou::image::gif::{GifEncoder, Repeat};
+use nannou::image::{self, Delay, Frame};
+
+use crate::cli::Args;
+
+/// Saves every `every`-th rendered frame to `dir` as numbered PNGs and, when `gif` is set,
+/// joins them into a looping animated GIF on exit.
+pub struct FrameCapture {
+    pub dir: PathBuf,
+    pub every: u64,
+    pub gif: Option<PathBuf>,
+}
+
+impl FrameCapture {
+    /// `--frames <dir>` with optional `--every <n>` and `--gif <file>`.
+    pub fn from_args(args: &Args) -> Option<Self> {
+        Some(FrameCapture {
+            dir: args.frames.clone()?,
+            every: args.every.max(1),
+            gif: args.gif.clone(),
+        })
+    }
+
+    /// Where to save rendered frame number `frame`, if it is one to keep.
+    pub fn frame_path(&self, frame: u64) -> Option<PathBuf> {
+        frame.is_multiple_of(self.every).then(|| {
+            self.dir
+                .join(format!("frame-{:05}.png", frame / self.every))
+        })
+    }
+
+    /// Encodes the saved frames, in order, into the GIF at `gif`, each shown for as long as
+    /// `every` frames at `fps`.
+    pub fn write_gif(&self, fps: f32) -> Result<usize, Box<dyn Error>> {
+        let Some(gif) = &self.gif else {
+            return Ok(0);
+        };
+        let mut frames: Vec<PathBuf> = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("frame-") && n.ends_with(".png"))
+            })
+            .collect();
+        frames.sort();
+
+        let mut encoder = GifEncoder::new(BufWriter::new(File::create(gif)?));
+        encoder.set_repeat(Repeat::Infinite)?;
+        let millis = (1000_f32 * self.every as f32 / fps).round() as u32;
+        for path in frames.iter() {
+            let buffer = image::open(path)?.to_rgba8();
+            let delay = Delay::from_numer_denom_ms(millis, 1);
+            encoder.encode_frame(Frame::from_parts(buffer, 0, 0, delay))?;
+        }
+        Ok(frames.len())
+    }
+}
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use utils::vec::Vec2;
+
+use crate::config::CONFIG_PATH;
+
+/// Verlet particle sandbox.
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Runs the scene without a window for this many simulated seconds and prints statistics.
+    #[arg(long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "10")]
+    pub headless: Option<f32>,
+
+    /// Loads a RON or JSON scene instead of the built-in one.
+    #[arg(long, value_name = "FILE")]
+    pub scene: Option<String>,
+
+    /// Reads settings from this TOML file; a missing file means defaults.
+    #[arg(long, value_name = "FILE", default_value = CONFIG_PATH)]
+    pub config: PathBuf,
+
+    /// How many particles the built-in emitter spawns.
+    #[arg(long, value_name = "COUNT")]
+    pub particles: Option<usize>,
+
+    /// Overrides gravity, given as `x,y`.
+    #[arg(long, value_name = "X,Y", value_parser = parse_vec2, allow_hyphen_values = true)]
+    pub gravity: Option<Vec2>,
+
+    /// Adds the opaque pixels of this PNG as a static obstacle.
+    #[arg(long, value_name = "FILE")]
+    pub mask: Option<PathBuf>,
+
+    /// World units per pixel of `--mask`.
+    #[arg(long, value_name = "SCALE", default_value_t = 1_f32, requires = "mask")]
+    pub mask_scale: f32,
+
+    /// Seeds the RNG and steps once per frame, so runs repeat exactly.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Same as `--seed`, with seed 0 when none is given.
+    #[arg(long, value_name = "SEED", num_args = 0..=1, default_missing_value = "0")]
+    pub deterministic: Option<u64>,
+
+    /// Saves the run to this file on exit.
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<String>,
+
+    /// Plays back a run saved with `--record`.
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<String>,
+
+    /// Saves rendered frames to this directory as numbered PNGs.
+    #[arg(long, value_name = "DIR")]
+    pub frames: Option<PathBuf>,
+
+    /// Keeps only every this many frames with `--frames`.
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub every: u64,
+
+    /// Joins the frames saved with `--frames` into this GIF on exit.
+    #[arg(long, value_name = "FILE", requires = "frames")]
+    pub gif: Option<PathBuf>,
+
+    /// Logs every particle's position and velocity each step to this CSV file.
+    #[arg(long, value_name = "FILE")]
+    pub trajectories: Option<String>,
+}
+
+impl Args {
+    /// The RNG seed from `--seed` or `--deterministic`, if either was given.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed.or(self.deterministic)
+    }
+}
+
+fn parse_vec2(s: &str) -> Result<Vec2, String> {
+    let (x, y) = s
+        .split_once(',')
+        .ok_or_else(|| format!("expected x,y but got {}", s))?;
+    let parse = |v: &str| v.trim().parse::<f32>().map_err(|e| e.to_string());
+    Ok(Vec2::new(parse(x)?, parse(y)?))
+}
@@ -0,0 +1,182 @@
+use std::error::Error;
+use std::path::Path;
+
+use serde::Deserialize;
+use utils::vec::Vec2;
+use verlet_core::{color, Color, Constraint, Emitter, FixedTimestep, World, WorldBuilder};
+
+use crate::cli::Args;
+
+/// Read at startup when `--config` isn't given.
+pub const CONFIG_PATH: &str = "sim.toml";
+
+/// Demo settings from a TOML file; anything left out keeps its default.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SimConfig {
+    pub gravity: Vec2,
+    pub dt: f32,
+    /// Runs exactly one step per frame instead of keeping up with the wall clock.
+    pub lockstep: bool,
+    pub substeps: usize,
+    /// Seeds the simulation's random number generator and locks stepping to the frame rate,
+    /// so runs repeat exactly. `--seed` and `--deterministic` override it.
+    pub seed: Option<u64>,
+    /// Particles to reserve storage for at startup, so busy emitters don't reallocate.
+    pub capacity: usize,
+    pub spawn: SpawnConfig,
+    pub container: Container,
+    pub colors: Colors,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        let world = World::default();
+        SimConfig {
+            gravity: world.gravity,
+            dt: FixedTimestep::default().dt,
+            lockstep: false,
+            substeps: world.substeps,
+            seed: None,
+            capacity: 0,
+            spawn: SpawnConfig::default(),
+            container: Container::default(),
+            colors: Colors::default(),
+        }
+    }
+}
+
+/// The built-in scene's emitter.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SpawnConfig {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    /// Particles per second.
+    pub rate: f32,
+    pub max_count: usize,
+    pub min_radius: f32,
+    pub max_radius: f32,
+    /// See `Emitter::spread`.
+    pub spread: f32,
+    /// See `Emitter::speed_variation`.
+    pub speed_variation: f32,
+    /// Colors picked at random for spawned particles; `colors.particle` when empty.
+    pub palette: Vec<Color>,
+}
+
+impl Default for SpawnConfig {
+    fn default() -> Self {
+        let emitter = Emitter::default();
+        SpawnConfig {
+            position: Vec2::new(100_f32, 200_f32),
+            velocity: emitter.velocity,
+            rate: 2_f32,
+            max_count: 20,
+            min_radius: *emitter.radius.start(),
+            max_radius: *emitter.radius.end(),
+            spread: emitter.spread,
+            speed_variation: emitter.speed_variation,
+            palette: Vec::new(),
+        }
+    }
+}
+
+/// The built-in scene's container, e.g. `shape = "box"` with `min` and `max`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "shape", rename_all = "lowercase")]
+pub enum Container {
+    Circle { center: Vec2, radius: f32 },
+    Box { min: Vec2, max: Vec2 },
+}
+
+impl Default for Container {
+    fn default() -> Self {
+        Container::Circle {
+            center: Vec2::zero(),
+            radius: 300_f32,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Colors {
+    pub background: Color,
+    pub container: Color,
+    pub obstacle: Color,
+    /// Particles from the built-in scene's emitter.
+    pub particle: Color,
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Colors {
+            background: Color::rgb(0, 0, 0),
+            container: Color::rgb(255, 255, 255),
+            obstacle: Color::rgb(128, 128, 128),
+            particle: color::STEELBLUE,
+        }
+    }
+}
+
+impl SimConfig {
+    /// The config at `path`, or the defaults if there is no such file.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(toml::from_str(&text)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(SimConfig::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The `--config` file with `--particles`, `--gravity` and the seed options applied on
+    /// top. Exits on a malformed file.
+    pub fn from_args(args: &Args) -> Self {
+        let mut config = SimConfig::load(&args.config).unwrap_or_else(|e| {
+            eprintln!("failed to load config {}: {}", args.config.display(), e);
+            std::process::exit(1);
+        });
+        if let Some(count) = args.particles {
+            config.spawn.max_count = count;
+        }
+        if let Some(gravity) = &args.gravity {
+            config.gravity = gravity.clone();
+        }
+        if let Some(seed) = args.seed() {
+            config.seed = Some(seed);
+        }
+        config
+    }
+
+    pub fn timestep(&self) -> FixedTimestep {
+        if self.lockstep || self.seed.is_some() {
+            FixedTimestep::lockstep(self.dt)
+        } else {
+            FixedTimestep::new(self.dt)
+        }
+    }
+
+    /// The built-in scene: one container with an emitter inside.
+    pub fn builder(&self) -> WorldBuilder {
+        let spawn = &self.spawn;
+        let mut emitter = Emitter::new(spawn.position.clone(), spawn.rate);
+        emitter.velocity = spawn.velocity.clone();
+        emitter.max_count = spawn.max_count;
+        emitter.radius = spawn.min_radius..=spawn.max_radius.max(spawn.min_radius);
+        emitter.spread = spawn.spread;
+        emitter.speed_variation = spawn.speed_variation;
+        emitter.color = self.colors.particle;
+        emitter.palette = spawn.palette.clone();
+        let container = match &self.container {
+            Container::Circle { center, radius } => Constraint::circle(center.clone(), *radius),
+            Container::Box { min, max } => Constraint::rect(min.clone(), max.clone()),
+        };
+        WorldBuilder::new()
+            .gravity(self.gravity.clone())
+            .substeps(self.substeps)
+            .capacity(self.capacity)
+            .constraint(container)
+            .emitter(emitter)
+    }
+}
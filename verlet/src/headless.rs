@@ -0,0 +1,83 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::time::Instant;
+
+use verlet_core::{Event, TrajectoryWriter, World};
+
+/// Steps `world` for `seconds` of simulated time at a fixed `dt` without opening a window and
+/// prints summary statistics, logging every step to `trajectories` if given. Returns `false` if
+/// any particle ended up at a non-finite position.
+pub fn run(
+    mut world: World,
+    seconds: f32,
+    dt: f32,
+    mut trajectories: Option<TrajectoryWriter<BufWriter<File>>>,
+) -> bool {
+    let steps = f32::ceil(seconds / dt) as usize;
+    let mut broken = 0;
+    let start = Instant::now();
+    for _ in 0..steps {
+        world.step(dt);
+        log_trajectories(&mut trajectories, &world);
+        broken += world
+            .drain_events()
+            .iter()
+            .filter(|e| matches!(e, Event::ConstraintBroken(_)))
+            .count();
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    if let Some(Err(e)) = trajectories.as_mut().map(|t| t.flush()) {
+        println!("trajectory export failed: {}", e);
+    }
+
+    let speeds: Vec<f32> = (0..world.particles.len())
+        .map(|i| world.velocity(i).len())
+        .collect();
+    let max_speed = speeds.iter().copied().fold(0_f32, f32::max);
+    let energy: f32 = world
+        .particles
+        .mass
+        .iter()
+        .zip(speeds.iter())
+        .map(|(m, v)| 0.5_f32 * m * v * v)
+        .sum();
+    let asleep = world.particles.asleep.iter().filter(|&&a| a).count();
+    let finite = world
+        .particles
+        .pos
+        .iter()
+        .all(|p| p.x.is_finite() && p.y.is_finite());
+
+    println!(
+        "simulated   {:.2}s in {} steps of {:.4}s",
+        seconds, steps, dt
+    );
+    println!(
+        "wall time   {:.3}s ({:.3} ms/step)",
+        elapsed,
+        1000_f64 * elapsed / steps.max(1) as f64
+    );
+    println!("particles   {} ({} asleep)", world.particle_count(), asleep);
+    println!(
+        "links       {} ({} constraints broken)",
+        world.links.len(),
+        broken
+    );
+    println!("max speed   {:.2}", max_speed);
+    println!("kinetic     {:.2}", energy);
+    if !finite {
+        println!("error: non-finite particle positions");
+    }
+    finite
+}
+
+/// Appends a step to the trajectory log, dropping the log after the first write error.
+pub fn log_trajectories(
+    trajectories: &mut Option<TrajectoryWriter<BufWriter<File>>>,
+    world: &World,
+) {
+    if let Some(Err(e)) = trajectories.as_mut().map(|t| t.record(world)) {
+        println!("trajectory export failed: {}", e);
+        *trajectories = None;
+    }
+}
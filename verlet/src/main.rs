@@ -1,191 +1,1423 @@
-use std::time::{self, SystemTime};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufWriter;
 
 use nannou::color::*;
 use nannou::event::*;
 use nannou::prelude::*;
+use nannou::winit::event::WindowEvent as RawWindowEvent;
+use nannou_egui::Egui;
 
 use utils::vec::Vec2;
+use verlet_core::{
+    Attractor, BroadphaseKind, Color, Command, Constraint, Event as WorldEvent, FixedTimestep,
+    ForceFieldKind, Gradient, History, IntegratorKind, Merging, NBody, Obstacle, Particle,
+    ParticleHandle, Periodic, Playback, Projection, Recorder, Recording, Scene, Sleep, Tether,
+    TrajectoryWriter, Turbulence, Wind, World,
+};
 
-#[derive(Clone, Debug)]
-pub struct Particle {
-    pub pos: Vec2,
-    pub pos_last: Vec2,
-    pub acc: Vec2,
-    pub radius: f32,
-    pub color: nannou::color::Rgb8,
+mod camera;
+mod capture;
+mod cli;
+mod config;
+mod headless;
+mod mask;
+mod panel;
+mod presets;
+mod tools;
+mod trails;
+
+use camera::Camera;
+use capture::FrameCapture;
+use clap::Parser;
+use cli::Args;
+use config::{Colors, SimConfig};
+use tools::{MouseTracker, Tool};
+use trails::Trails;
+
+const SNAPSHOT_PATH: &str = "snapshot.json";
+const FRAME_RATE: f32 = 60_f32;
+/// Seconds of history kept for rewinding.
+const REWIND_SECONDS: f32 = 5_f32;
+/// Matches the default gravity at 300 units from the attractor.
+const ATTRACTOR_STRENGTH: f32 = 90_000_000_f32;
+const N_BODY_STRENGTH: f32 = 1_000_000_f32;
+/// Right-click explosion: impulse at the cursor and the radius it fades out over.
+const EXPLOSION_STRENGTH: f32 = 2000_f32;
+const EXPLOSION_RADIUS: f32 = 200_f32;
+/// Positions kept per particle trail, one per frame.
+const TRAIL_LENGTH: usize = 30;
+/// Seconds of travel the debug overlay's velocity arrows stand for.
+const VELOCITY_ARROW_SECONDS: f32 = 0.05_f32;
+/// Per-step travel limit of the built-in scene, well above what explosions reach.
+const MAX_DISPLACEMENT: f32 = 60_f32;
+/// Zoom factor per scroll-wheel line.
+const ZOOM_STEP: f32 = 1.1_f32;
+/// Right-button drags shorter than this, in window units, count as clicks and explode.
+const CLICK_SLOP: f32 = 4_f32;
+/// Radius of particles painted with the spawn tool.
+const SPAWN_RADIUS: f32 = 10_f32;
+/// Eraser and force brush radii selectable with `[` and `]`.
+const MIN_BRUSH: f32 = 5_f32;
+const MAX_BRUSH: f32 = 200_f32;
+/// Force brush strengths stepped through with Q and E, halving or doubling.
+const MIN_FORCE: f32 = 1000_f32;
+const MAX_FORCE: f32 = 64_000_f32;
+/// Shorter drags are taken as clicks and draw no wall.
+const MIN_WALL: f32 = 5_f32;
+/// Time scales stepped through with `-` and `=`.
+const TIME_SCALES: [f32; 4] = [0.1_f32, 0.5_f32, 1_f32, 2_f32];
+
+struct Model {
+    world: World,
+    timestep: FixedTimestep,
+    mouse_pressed: bool,
+    /// Particle held by the grab tool. Removals shift tether indices, so its tether is looked
+    /// up again on every use.
+    grabbed: Option<ParticleHandle>,
+    tool: Tool,
+    mouse: MouseTracker,
+    /// Where the spawn tool last painted a particle during the current drag.
+    last_spawn: Option<Vec2>,
+    /// Radius of the eraser and force tools.
+    brush_radius: f32,
+    /// Force at the center of the force tool's brush.
+    force_strength: f32,
+    /// Where the wall being dragged out starts.
+    wall_start: Option<Vec2>,
+    /// Obstacle indices of the walls drawn so far, oldest first, for undo.
+    walls: Vec<usize>,
+    fade: bool,
+    /// Keeps the first emitter above the first container, as in the built-in scene.
+    follow_emitter: bool,
+    /// Output path and recorder when running with `--record`.
+    recorder: Option<(String, Recorder)>,
+    playback: Option<Playback>,
+    history: History,
+    /// Set while the rewind key is held; each frame steps one snapshot back.
+    rewinding: bool,
+    /// Colors particles by temperature, speed or energy instead of their own color when set.
+    color_view: Option<ColorView>,
+    /// Touching pairs drawn by the debug overlay, refreshed every frame while it is shown.
+    debug_contacts: Option<Vec<(usize, usize)>>,
+    /// Fading paths drawn behind particles when set.
+    trails: Option<Trails>,
+    /// Draws the broadphase cells with their particle counts.
+    show_broadphase: bool,
+    /// Shows counts, FPS and step phase timings in a corner.
+    hud: bool,
+    camera: Camera,
+    /// Last cursor position and distance dragged so far while the right button is held.
+    right_drag: Option<(Vec2, f32)>,
+    egui: Egui,
+    window: WindowId,
+    /// Frames being saved with `--frames`.
+    capture: Option<FrameCapture>,
+    /// Per-step particle log written with `--trajectories`.
+    trajectories: Option<TrajectoryWriter<BufWriter<File>>>,
+    colors: Colors,
 }
 
-impl Particle {
-    pub fn new(pos: Vec2) -> Self {
-        Particle {
-            pos: pos.clone(),
-            pos_last: pos.clone(),
-            acc: Vec2::zero(),
-            radius: 20_f32,
-            color: nannou::color::STEELBLUE,
+fn main() {
+    let args = Args::parse();
+    if let Some(seconds) = args.headless {
+        let config = SimConfig::from_args(&args);
+        let dt = config.dt;
+        let mut world = initial_world(&args, &config, dt);
+        seed_world(&config, &mut world);
+        if !headless::run(world, seconds, dt, trajectory_writer(&args)) {
+            std::process::exit(1);
         }
+        return;
     }
+    nannou::app(model)
+        .update(update)
+        .event(events)
+        .exit(exit)
+        .run();
+}
 
-    pub fn update(&mut self, dt: f32) {
-        let delta = self.pos.clone() - self.pos_last.clone();
-        self.pos_last = self.pos.clone();
-        self.pos += delta + self.acc.clone() * dt * dt;
-        self.acc = Vec2::zero();
+fn model(app: &App) -> Model {
+    app.set_loop_mode(LoopMode::rate_fps(FRAME_RATE as f64));
+    let window = app
+        .new_window()
+        .view(view)
+        .raw_event(raw_window_event)
+        .build()
+        .unwrap();
+    let egui = Egui::from_window(&app.window(window).unwrap());
+    // Already parsed once in `main`, so this can't fail.
+    let args = Args::parse();
+    let scene = args.scene.as_ref();
+    let record = args.record.as_ref();
+    let capture = FrameCapture::from_args(&args);
+    if let Some(capture) = &capture {
+        if let Err(e) = std::fs::create_dir_all(&capture.dir) {
+            eprintln!("failed to create {}: {}", capture.dir.display(), e);
+            std::process::exit(1);
+        }
     }
 
-    pub fn accelerate(&mut self, acc: Vec2) {
-        self.acc += acc;
-    }
+    let config = SimConfig::from_args(&args);
+    let mut timestep = config.timestep();
+    let playback = args.replay.as_ref().map(|path| {
+        let recording = load_recording(path).unwrap_or_else(|e| {
+            eprintln!("failed to load recording {}: {}", path, e);
+            std::process::exit(1);
+        });
+        timestep = FixedTimestep::lockstep(recording.dt);
+        Playback::new(recording)
+    });
 
-    pub fn set_velocity(mut self, v: Vec2, dt: f32) {
-        self.pos_last = self.pos - (v * dt);
+    let mut world = match &playback {
+        Some(playback) => playback.world(),
+        None => initial_world(&args, &config, timestep.dt),
+    };
+    if seed_world(&config, &mut world) {
+        timestep = FixedTimestep::lockstep(timestep.dt);
     }
-
-    pub fn add_velocity(mut self, v: Vec2, dt: f32) {
-        self.pos_last -= v * dt;
+    // Recordings replay one step per frame, so record that way too.
+    if record.is_some() {
+        timestep = FixedTimestep::lockstep(timestep.dt);
     }
 
-    pub fn velocity(self, dt: f32) -> Vec2 {
-        (self.pos - self.pos_last) / dt
+    // Scenes with heat sources start out showing temperatures.
+    let color_view = (!world.heat_sources.is_empty()).then_some(ColorView::Temperature);
+    Model {
+        recorder: record.map(|path| (path.clone(), Recorder::new(&world, timestep.dt))),
+        history: History::with_duration(REWIND_SECONDS, timestep.dt),
+        world,
+        timestep,
+        mouse_pressed: false,
+        grabbed: None,
+        tool: Tool::Grab,
+        mouse: MouseTracker::default(),
+        last_spawn: None,
+        brush_radius: 30_f32,
+        force_strength: 8000_f32,
+        wall_start: None,
+        walls: Vec::new(),
+        fade: true,
+        follow_emitter: scene.is_none() && playback.is_none(),
+        playback,
+        rewinding: false,
+        color_view,
+        debug_contacts: None,
+        trails: None,
+        show_broadphase: false,
+        hud: false,
+        camera: Camera::default(),
+        right_drag: None,
+        egui,
+        window,
+        capture,
+        trajectories: trajectory_writer(&args),
+        colors: config.colors,
     }
 }
 
-struct Model {
-    particles: Vec<Particle>,
-    gravity: Vec2,
-    center: Vec2,
-    last_push: SystemTime,
-    mouse_pressed: bool,
+/// A particle property shown through a gradient in place of particle colors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ColorView {
+    Temperature,
+    /// Units per second.
+    Speed,
+    /// `m * v^2 / 2`, so heavy particles stand out over light ones at the same speed.
+    KineticEnergy,
 }
 
-impl Model {
-    pub fn apply_gravity(&mut self) {
-        for m in self.particles.iter_mut() {
-            m.accelerate(self.gravity.clone());
+impl ColorView {
+    fn name(self) -> &'static str {
+        match self {
+            ColorView::Temperature => "temperature",
+            ColorView::Speed => "speed",
+            ColorView::KineticEnergy => "kinetic energy",
         }
     }
-    pub fn update(&mut self, dt: f32) {
-        for m in self.particles.iter_mut() {
-            m.update(dt)
+
+    /// Property values the gradient spreads over from black to white.
+    fn range(self) -> f32 {
+        match self {
+            ColorView::Temperature => 100_f32,
+            ColorView::Speed => 1000_f32,
+            ColorView::KineticEnergy => 500_000_f32,
         }
     }
 
-    pub fn apply_constraints(&mut self) {
-        let constraint_center = self.center.clone();
-        let constraint_radius = 300_f32;
+    fn color(self, world: &World, i: usize) -> Color {
+        let value = match self {
+            ColorView::Temperature => world.particles.temperature[i],
+            ColorView::Speed => world.velocity(i).len(),
+            ColorView::KineticEnergy => {
+                let v = world.velocity(i);
+                0.5_f32 * world.particles.mass[i] * v.dot(&v)
+            }
+        };
+        Gradient::thermal(0_f32, self.range()).sample(value)
+    }
+}
 
-        for m in self.particles.iter_mut() {
-            let v = constraint_center.clone() - m.pos.clone();
-            let dist = v.len();
-            if dist > (constraint_radius - m.radius) {
-                let n = v / dist;
-                m.pos = constraint_center.clone() - n * (constraint_radius - m.radius);
+/// The `--scene` world if given, otherwise the built-in one.
+fn initial_world(args: &Args, config: &SimConfig, dt: f32) -> World {
+    let mut world = match &args.scene {
+        Some(path) => {
+            let mut world = load_scene(path, dt).unwrap_or_else(|e| {
+                eprintln!("failed to load scene {}: {}", path, e);
+                std::process::exit(1);
+            });
+            if let Some(gravity) = &args.gravity {
+                world.gravity = gravity.clone();
+            }
+            world
+        }
+        None => {
+            let (_, world) = config.builder().max_displacement(MAX_DISPLACEMENT).build();
+            world
+        }
+    };
+    if let Some(path) = &args.mask {
+        match mask::load(path, args.mask_scale) {
+            Ok(obstacle) => {
+                world.add_obstacle(obstacle);
+            }
+            Err(e) => {
+                eprintln!("failed to load mask {}: {}", path.display(), e);
+                std::process::exit(1);
             }
         }
     }
+    world
+}
+
+/// The `--trajectories <file>` CSV writer, if requested.
+fn trajectory_writer(args: &Args) -> Option<TrajectoryWriter<BufWriter<File>>> {
+    let path = args.trajectories.as_ref()?;
+    let created = File::create(path).and_then(|file| TrajectoryWriter::new(BufWriter::new(file)));
+    match created {
+        Ok(writer) => Some(writer),
+        Err(e) => {
+            eprintln!("failed to create {}: {}", path, e);
+            std::process::exit(1);
+        }
+    }
+}
 
-    pub fn solve_collisions(&mut self) {
-        let response_coef = 0.8_f32;
-        for i in 0..self.particles.len() {
-            let o_1 = &self.particles[i].clone();
-            for k in (i + 1)..self.particles.len() {
-                let o_2 = self.particles[k].clone();
-                let v = o_1.pos.clone() - o_2.pos.clone();
-                let dist2 = v.x * v.x + v.y * v.y;
-                let min_dist = o_1.radius + o_2.radius + 2_f32;
-                if dist2 < min_dist * min_dist {
-                    let dist = f32::sqrt(dist2);
-                    let n = v / dist;
-                    let mass_ratio_1 = o_1.radius / (o_1.radius + o_2.radius);
-                    let mass_ratio_2 = o_2.radius / (o_1.radius + o_2.radius);
-                    let delta = 0.5_f32 * response_coef * (dist - min_dist);
+/// Applies the configured seed, or `--seed` or `--deterministic`, returning whether there was
+/// one.
+fn seed_world(config: &SimConfig, world: &mut World) -> bool {
+    let Some(seed) = config.seed else {
+        return false;
+    };
+    world.seed(seed);
+    true
+}
 
-                    self.particles[i].pos -= n.clone() * (mass_ratio_2 * delta);
-                    self.particles[k].pos += n * (mass_ratio_1 * delta);
-                }
+fn load_scene(path: &str, dt: f32) -> Result<World, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(path)?;
+    let scene: Scene = if path.ends_with(".ron") {
+        ron::from_str(&text)?
+    } else {
+        serde_json::from_str(&text)?
+    };
+    Ok(scene.build(dt))
+}
+
+fn load_recording(path: &str) -> Result<Recording, Box<dyn std::error::Error>> {
+    let file = std::fs::File::open(path)?;
+    Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+}
+
+fn exit(app: &App, model: Model) {
+    if let Some(Err(e)) = model.trajectories.map(|mut t| t.flush()) {
+        println!("trajectory export failed: {}", e);
+    }
+    if let Some(capture) = &model.capture {
+        if let Some(window) = app.window(model.window) {
+            if let Err(e) = window.await_capture_frame_jobs() {
+                println!("frame capture failed: {:?}", e);
             }
         }
+        match capture.write_gif(FRAME_RATE) {
+            Ok(frames) if capture.gif.is_some() => println!("wrote a gif of {} frames", frames),
+            Ok(_) => {}
+            Err(e) => println!("gif export failed: {}", e),
+        }
+    }
+    let Some((path, recorder)) = model.recorder else {
+        return;
+    };
+    let result = std::fs::File::create(&path).map(std::io::BufWriter::new);
+    match result.map(|file| serde_json::to_writer(file, recorder.recording())) {
+        Ok(Ok(())) => println!("recorded {} steps to {}", recorder.recording().len(), path),
+        Ok(Err(e)) => println!("recording failed: {}", e),
+        Err(e) => println!("recording failed: {}", e),
     }
 }
 
-fn main() {
-    nannou::app(model)
-        .simple_window(view)
-        .update(update)
-        .event(events)
-        .run();
+/// Applies a command to the world, recording it if a recording is running.
+fn apply(model: &mut Model, command: Command) {
+    match &mut model.recorder {
+        Some((_, recorder)) => recorder.apply(&mut model.world, command),
+        None => command.apply(&mut model.world),
+    }
 }
 
-fn model(app: &App) -> Model {
-    app.set_loop_mode(LoopMode::rate_fps(60.0));
-    Model {
-        particles: Vec::new(),
-        gravity: Vec2::new(0_f32, -1000_f32),
-        center: Vec2::new(0_f32, 0_f32),
-        last_push: time::SystemTime::now(),
-        mouse_pressed: false,
+/// Index of the grab tool's tether, if its particle is still there.
+fn grab_tether(model: &Model) -> Option<usize> {
+    let particle = model.world.index(model.grabbed?)?;
+    model
+        .world
+        .tethers
+        .iter()
+        .rposition(|t| t.particle == particle)
+}
+
+/// Lets go of the grabbed particle, removing its tether.
+fn release_grab(model: &mut Model) {
+    if let Some(t) = grab_tether(model) {
+        apply(model, Command::RemoveTether(t));
+    }
+    model.grabbed = None;
+}
+
+/// World position under the mouse cursor.
+fn cursor(app: &App, model: &Model) -> Vec2 {
+    model.camera.to_world(&Vec2::new(app.mouse.x, app.mouse.y))
+}
+
+/// Scroll-wheel zoom and right-drag panning. Returns whether the event was used up; a right
+/// click that didn't drag is left to `events`.
+fn camera_events(app: &App, model: &mut Model, event: &Event) -> bool {
+    let Event::WindowEvent {
+        simple: Some(event),
+        ..
+    } = event
+    else {
+        return false;
+    };
+    match event {
+        WindowEvent::MouseWheel(delta, _) => {
+            let lines = match delta {
+                MouseScrollDelta::LineDelta(_, y) => *y,
+                MouseScrollDelta::PixelDelta(p) => p.y as f32 / 40_f32,
+            };
+            let screen = Vec2::new(app.mouse.x, app.mouse.y);
+            model.camera.zoom_at(&screen, ZOOM_STEP.powf(lines));
+            true
+        }
+        WindowEvent::MousePressed(MouseButton::Right) => {
+            model.right_drag = Some((Vec2::new(app.mouse.x, app.mouse.y), 0_f32));
+            true
+        }
+        WindowEvent::MouseMoved(p) => match model.right_drag.take() {
+            Some((last, dragged)) => {
+                let screen = Vec2::new(p[0], p[1]);
+                let delta = screen.clone() - last;
+                model.camera.pan(&delta);
+                model.right_drag = Some((screen, dragged + delta.len()));
+                true
+            }
+            None => false,
+        },
+        WindowEvent::MouseReleased(MouseButton::Right) => model
+            .right_drag
+            .take()
+            .is_some_and(|(_, dragged)| dragged >= CLICK_SLOP),
+        _ => false,
+    }
+}
+
+/// Space pauses and resumes, `.` runs a single step while paused and `-` and `=` step down
+/// and up through the time scales. Returns whether the event was used up.
+fn time_events(model: &mut Model, event: &Event) -> bool {
+    let Event::WindowEvent {
+        simple: Some(WindowEvent::KeyPressed(key)),
+        ..
+    } = event
+    else {
+        return false;
+    };
+    let timestep = &mut model.timestep;
+    let scale = match key {
+        Key::Space => {
+            timestep.paused = !timestep.paused;
+            println!("paused: {}", timestep.paused);
+            return true;
+        }
+        Key::Period => {
+            timestep.single_step();
+            return true;
+        }
+        Key::Minus | Key::Equals => {
+            let current = TIME_SCALES
+                .iter()
+                .position(|&s| s >= timestep.time_scale)
+                .unwrap_or(TIME_SCALES.len() - 1);
+            let next = match key {
+                Key::Minus => current.saturating_sub(1),
+                _ => (current + 1).min(TIME_SCALES.len() - 1),
+            };
+            TIME_SCALES[next]
+        }
+        _ => return false,
+    };
+    timestep.time_scale = scale;
+    println!("time scale: {}", scale);
+    true
+}
+
+fn raw_window_event(_app: &App, model: &mut Model, event: &RawWindowEvent) {
+    model.egui.handle_raw_event(event);
+}
+
+/// Whether `event` is meant for the parameter panel rather than the world. Releases always
+/// pass, so drags started over the world still end.
+fn over_panel(model: &Model, event: &Event) -> bool {
+    let ctx = model.egui.ctx();
+    match event {
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(_) | WindowEvent::KeyReleased(_)),
+            ..
+        } => ctx.wants_keyboard_input(),
+        Event::WindowEvent {
+            simple: Some(WindowEvent::MousePressed(_) | WindowEvent::MouseWheel(..)),
+            ..
+        } => ctx.is_pointer_over_area(),
+        _ => false,
     }
 }
 
-fn events(_app: &App, model: &mut Model, event: Event) {
+fn events(app: &App, model: &mut Model, event: Event) {
+    if over_panel(model, &event) {
+        return;
+    }
+    if camera_events(app, model, &event) || time_events(model, &event) {
+        return;
+    }
+    // F12 saves the next rendered frame, also during playback.
+    if let Event::WindowEvent {
+        simple: Some(WindowEvent::KeyPressed(Key::F12)),
+        ..
+    } = event
+    {
+        let path = screenshot_path();
+        app.main_window().capture_frame(&path);
+        println!("saved {}", path);
+        return;
+    }
+    // Playback ignores input, the recording already holds it.
+    if model.playback.is_some() {
+        return;
+    }
+    if let Event::WindowEvent {
+        simple: Some(WindowEvent::MouseMoved(p)),
+        ..
+    } = event
+    {
+        model
+            .mouse
+            .moved(model.camera.to_world(&Vec2::new(p[0], p[1])));
+    }
     match event {
         Event::WindowEvent {
-            id: id,
+            simple: Some(WindowEvent::KeyPressed(Key::Tab)),
+            ..
+        } => {
+            model.tool = model.tool.next();
+            println!("tool: {:?}", model.tool);
+        }
+
+        // Shrinks and grows the eraser and force brushes.
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(key @ (Key::LBracket | Key::RBracket))),
+            ..
+        } => {
+            let factor = if key == Key::LBracket {
+                0.8_f32
+            } else {
+                1.25_f32
+            };
+            model.brush_radius = (model.brush_radius * factor).clamp(MIN_BRUSH, MAX_BRUSH);
+        }
+
+        // Weakens and strengthens the force brush.
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(key @ (Key::Q | Key::E))),
+            ..
+        } => {
+            let factor = if key == Key::Q { 0.5_f32 } else { 2_f32 };
+            model.force_strength = (model.force_strength * factor).clamp(MIN_FORCE, MAX_FORCE);
+            println!("force: {}", model.force_strength);
+        }
+
+        Event::WindowEvent {
             simple: Some(WindowEvent::MouseMoved(p)),
-        } if model.mouse_pressed => {
-            model.center.x = p[0];
-            model.center.y = p[1];
+            ..
+        } if model.mouse_pressed && model.tool == Tool::Erase => {
+            erase_at(model, model.camera.to_world(&Vec2::new(p[0], p[1])));
+        }
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::MouseMoved(p)),
+            ..
+        } if model.mouse_pressed && model.tool == Tool::Spawn => {
+            let point = model.camera.to_world(&Vec2::new(p[0], p[1]));
+            let spaced = model
+                .last_spawn
+                .as_ref()
+                .is_none_or(|last| (point.clone() - last.clone()).len() >= 2_f32 * SPAWN_RADIUS);
+            if spaced {
+                spawn_at(model, point);
+            }
+        }
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::MouseMoved(p)),
+            ..
+        } if model.mouse_pressed && model.tool == Tool::Grab => {
+            let point = model.camera.to_world(&Vec2::new(p[0], p[1]));
+            match model.grabbed {
+                Some(_) => {
+                    if let Some(index) = grab_tether(model) {
+                        apply(model, Command::MoveTether { index, point });
+                    }
+                }
+                None if !model.world.constraints.is_empty() => apply(
+                    model,
+                    Command::MoveConstraint {
+                        index: 0,
+                        center: point,
+                    },
+                ),
+                None => {}
+            }
         }
 
         Event::WindowEvent {
-            id: id,
             simple: Some(WindowEvent::MousePressed(MouseButton::Left)),
-        } => model.mouse_pressed = true,
+            ..
+        } => {
+            let cursor = cursor(app, model);
+            match model.tool {
+                Tool::Grab => {
+                    if let Some(i) = model.world.pick(&cursor) {
+                        let tether = Tether::new(i, cursor, 500_f32, 20_f32);
+                        apply(model, Command::AddTether(tether));
+                        model.grabbed = model.world.handle(i);
+                    }
+                }
+                Tool::Spawn => spawn_at(model, cursor),
+                Tool::Erase => erase_at(model, cursor),
+                Tool::Wall => model.wall_start = Some(cursor),
+                Tool::Force => {}
+            }
+            model.mouse_pressed = true;
+        }
 
         Event::WindowEvent {
-            id: id,
             simple: Some(WindowEvent::MouseReleased(MouseButton::Left)),
-        } => model.mouse_pressed = false,
+            ..
+        } => {
+            release_grab(model);
+            if let Some(start) = model.wall_start.take() {
+                let end = cursor(app, model);
+                if (end.clone() - start.clone()).len() >= MIN_WALL {
+                    apply(model, Command::AddObstacle(Obstacle::segment(start, end)));
+                    model.walls.push(model.world.obstacles.len() - 1);
+                }
+            }
+            model.last_spawn = None;
+            model.mouse_pressed = false;
+        }
+
+        // Undoes the last drawn wall.
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::Z)),
+            ..
+        } => {
+            if let Some(index) = model.walls.pop() {
+                if index < model.world.obstacles.len() {
+                    apply(model, Command::RemoveObstacle(index));
+                }
+            }
+        }
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::MouseReleased(MouseButton::Right)),
+            ..
+        } => apply(
+            model,
+            Command::ImpulseRadial {
+                center: cursor(app, model),
+                strength: EXPLOSION_STRENGTH,
+                falloff: EXPLOSION_RADIUS,
+                dt: model.timestep.dt,
+            },
+        ),
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::B)),
+            ..
+        } => {
+            let next = match model.world.solver.broadphase().kind() {
+                Some(BroadphaseKind::SpatialGrid { .. }) => BroadphaseKind::Quadtree {
+                    node_capacity: 8,
+                    max_depth: 8,
+                },
+                Some(BroadphaseKind::Quadtree { .. }) => BroadphaseKind::SweepAndPrune,
+                Some(BroadphaseKind::SweepAndPrune) => BroadphaseKind::BruteForce,
+                _ => BroadphaseKind::SpatialGrid { cell_size: None },
+            };
+            apply(model, Command::SetBroadphase(next));
+            println!("broadphase: {}", model.world.solver.broadphase().name());
+        }
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::I)),
+            ..
+        } => {
+            let next = match model.world.integrator.kind() {
+                Some(IntegratorKind::Verlet) => IntegratorKind::SemiImplicitEuler,
+                Some(IntegratorKind::SemiImplicitEuler) => IntegratorKind::Rk4,
+                _ => IntegratorKind::Verlet,
+            };
+            apply(model, Command::SetIntegrator(next));
+            println!("integrator: {}", model.world.integrator.name());
+        }
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::S)),
+            ..
+        } => {
+            let sleep = match model.world.solver.sleep {
+                Some(_) => None,
+                None => Some(Sleep::default()),
+            };
+            apply(model, Command::SetSleep(sleep));
+        }
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::X)),
+            ..
+        } => {
+            let projection = match model.world.solver.projection {
+                Projection::Relaxation => Projection::Xpbd {
+                    contact_compliance: 0_f32,
+                },
+                Projection::Xpbd { .. } => Projection::Relaxation,
+            };
+            println!("projection: {:?}", projection);
+            apply(model, Command::SetProjection(projection));
+        }
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::M)),
+            ..
+        } => {
+            let merging = match model.world.merging {
+                Some(_) => None,
+                None => Some(Merging::default()),
+            };
+            println!("merging: {}", merging.is_some());
+            apply(model, Command::SetMerging(merging));
+        }
+
+        // Deletes the particle under the cursor.
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::Delete)),
+            ..
+        } => {
+            let cursor = cursor(app, model);
+            if let Some(index) = model.world.pick(&cursor) {
+                apply(model, Command::RemoveParticle(index));
+            }
+        }
+
+        // Splits the particle under the cursor in three.
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::P)),
+            ..
+        } => {
+            let cursor = cursor(app, model);
+            if let Some(index) = model.world.pick(&cursor) {
+                apply(model, Command::SplitParticle { index, count: 3 });
+            }
+        }
+
+        // Switches between uniform gravity and a pull towards the first container's center.
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::G)),
+            ..
+        } => {
+            let attractor = match model.world.attractor {
+                Some(_) => None,
+                None => {
+                    let center = match model.world.constraints.first() {
+                        Some(c) => c.center(),
+                        None => Vec2::zero(),
+                    };
+                    Some(Attractor::new(center, ATTRACTOR_STRENGTH))
+                }
+            };
+            apply(model, Command::SetAttractor(attractor));
+        }
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::N)),
+            ..
+        } => {
+            let n_body = match model.world.n_body {
+                Some(_) => None,
+                None => Some(NBody::new(N_BODY_STRENGTH)),
+            };
+            apply(model, Command::SetNBody(n_body));
+        }
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::W)),
+            ..
+        } => toggle_force_field(
+            model,
+            ForceFieldKind::Wind(Wind::new(Vec2::new(300_f32, 0_f32))),
+        ),
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::T)),
+            ..
+        } => toggle_force_field(
+            model,
+            ForceFieldKind::Turbulence(Turbulence::new(1500_f32, 150_f32)),
+        ),
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::F)),
+            ..
+        } => model.fade = !model.fade,
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::H)),
+            ..
+        } => {
+            model.color_view = match model.color_view {
+                Some(ColorView::Temperature) => None,
+                _ => Some(ColorView::Temperature),
+            }
+        }
+
+        // Cycles between own colors, speed and kinetic energy.
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::V)),
+            ..
+        } => {
+            model.color_view = match model.color_view {
+                Some(ColorView::Speed) => Some(ColorView::KineticEnergy),
+                Some(ColorView::KineticEnergy) => None,
+                _ => Some(ColorView::Speed),
+            };
+        }
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::F1)),
+            ..
+        } => model.hud = !model.hud,
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::O)),
+            ..
+        } => model.show_broadphase = !model.show_broadphase,
+
+        // Toggles particle trails.
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::L)),
+            ..
+        } => {
+            model.trails = match model.trails {
+                Some(_) => None,
+                None => Some(Trails::new(TRAIL_LENGTH)),
+            }
+        }
+
+        // Toggles the velocity and contact overlay.
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::D)),
+            ..
+        } => {
+            model.debug_contacts = match model.debug_contacts {
+                Some(_) => None,
+                None => Some(model.world.contacts()),
+            }
+        }
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::C)),
+            ..
+        } if !model.world.constraints.is_empty() => {
+            let center = model.world.constraints[0].center();
+            let constraint = match model.world.constraints[0] {
+                Constraint::Circle { .. } => Constraint::rect(
+                    center.clone() - 300_f32,
+                    center + Vec2::new(300_f32, 300_f32),
+                ),
+                Constraint::Box { .. } => Constraint::regular_polygon(center, 300_f32, 6),
+                Constraint::Polygon { .. } | Constraint::Sdf { .. } => {
+                    Constraint::circle(center, 300_f32)
+                }
+            };
+            apply(
+                model,
+                Command::SetConstraint {
+                    index: 0,
+                    constraint,
+                },
+            );
+        }
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::R)),
+            ..
+        } => {
+            release_grab(model);
+            model.rewinding = true;
+        }
+
+        // Resume from wherever the rewind stopped; the recorder sees it as a restore.
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyReleased(Key::R)),
+            ..
+        } if model.rewinding => {
+            model.rewinding = false;
+            let world = model.world.clone();
+            apply(model, Command::Restore(Box::new(world)));
+        }
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::F5)),
+            ..
+        } => match save_snapshot(&model.world) {
+            Ok(()) => println!("saved {}", SNAPSHOT_PATH),
+            Err(e) => println!("save failed: {}", e),
+        },
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(Key::F9)),
+            ..
+        } => match load_snapshot() {
+            Ok(world) => {
+                apply(model, Command::Restore(Box::new(world)));
+                model.grabbed = None;
+                model.walls.clear();
+                println!("loaded {}", SNAPSHOT_PATH);
+            }
+            Err(e) => println!("load failed: {}", e),
+        },
+
+        Event::WindowEvent {
+            simple: Some(WindowEvent::KeyPressed(key)),
+            ..
+        } => {
+            if let Some(index) = preset_key(key) {
+                load_preset(model, index);
+            }
+        }
         _ => {}
     }
 }
 
-fn update(app: &App, model: &mut Model, upd: Update) {
-    let now = time::SystemTime::now();
+/// Index into `presets::PRESETS` picked by number key `key`.
+fn preset_key(key: Key) -> Option<usize> {
+    let keys = [
+        Key::Key1,
+        Key::Key2,
+        Key::Key3,
+        Key::Key4,
+        Key::Key5,
+        Key::Key6,
+        Key::Key7,
+        Key::Key8,
+        Key::Key9,
+    ];
+    keys.iter().position(|&k| k == key)
+}
 
-    let elapsed = now.duration_since(model.last_push).unwrap().as_millis();
-    if elapsed > 500 && model.particles.len() < 20 {
-        model.particles.push(Particle::new(Vec2::new(
-            model.center.x + 100_f32,
-            model.center.y + 200_f32,
-        )));
-        model.last_push = now;
+/// Replaces the world with preset `index`, if there is one.
+fn load_preset(model: &mut Model, index: usize) {
+    let Some(world) = presets::build(index, model.timestep.dt) else {
+        return;
+    };
+    apply(model, Command::Restore(Box::new(world)));
+    model.grabbed = None;
+    model.walls.clear();
+    model.follow_emitter = false;
+    if model.trails.is_some() {
+        model.trails = Some(Trails::new(TRAIL_LENGTH));
     }
+    println!("preset {}: {}", index + 1, presets::PRESETS[index]);
+}
 
-    let dt = upd.since_last.as_secs_f32();
+/// Removes every particle overlapping the eraser brush around `point`.
+fn erase_at(model: &mut Model, point: Vec2) {
+    let r = model.brush_radius;
+    let min = Vec2::new(point.x - r, point.y - r);
+    let max = Vec2::new(point.x + r, point.y + r);
+    let mut hits = model.world.query_aabb(min, max);
+    hits.retain(|&i| {
+        let p = &model.world.particles;
+        (&p.pos[i] - &point).len() < r + p.radius[i]
+    });
+    if !hits.is_empty() {
+        apply(model, Command::RemoveParticles(hits));
+    }
+}
+
+/// Adds a particle at `point` moving with the cursor.
+fn spawn_at(model: &mut Model, point: Vec2) {
+    let mut particle = Particle::new(point.clone());
+    particle.radius = SPAWN_RADIUS;
+    particle.set_velocity(model.mouse.velocity(), model.timestep.dt);
+    apply(model, Command::AddParticle(particle));
+    model.last_spawn = Some(point);
+}
 
-    model.apply_gravity();
-    model.solve_collisions();
-    model.apply_constraints();
-    model.update(dt);
+/// Removes the world's field of the same kind as `field`, or adds `field` if there is none.
+fn toggle_force_field(model: &mut Model, field: ForceFieldKind) {
+    let existing = model.world.force_fields.iter().position(|f| {
+        f.kind()
+            .is_some_and(|k| std::mem::discriminant(&k) == std::mem::discriminant(&field))
+    });
+    match existing {
+        Some(index) => apply(model, Command::RemoveForceField(index)),
+        None => apply(model, Command::AddForceField(field)),
+    }
+}
+
+/// `screenshot-<unix milliseconds>.png` in the working directory.
+fn screenshot_path() -> String {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    format!("screenshot-{}.png", millis)
+}
+
+fn save_snapshot(world: &World) -> Result<(), Box<dyn std::error::Error>> {
+    let file = std::fs::File::create(SNAPSHOT_PATH)?;
+    serde_json::to_writer(std::io::BufWriter::new(file), world)?;
+    Ok(())
+}
+
+fn load_snapshot() -> Result<World, Box<dyn std::error::Error>> {
+    let file = std::fs::File::open(SNAPSHOT_PATH)?;
+    Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+}
+
+fn update(app: &App, model: &mut Model, upd: Update) {
+    model.egui.set_elapsed_time(upd.since_start);
+    let ctx = model.egui.begin_frame();
+    // Step timings are only measured while the HUD shows them.
+    model.world.profile = model.hud;
+    let commands = panel::show(&ctx, &model.world, app.fps());
+    drop(ctx);
+    if model.playback.is_none() {
+        for command in commands {
+            apply(model, command);
+        }
+    }
+
+    if let Some(playback) = &mut model.playback {
+        for _ in 0..model.timestep.advance(upd.since_last.as_secs_f32()) {
+            if playback.step(&mut model.world) && playback.is_finished() {
+                println!("replay finished after {} steps", playback.position());
+            }
+        }
+    } else if model.rewinding {
+        if let Some(world) = model.history.pop() {
+            model.world = world;
+        }
+    } else {
+        if model.follow_emitter {
+            let center = model.world.constraints[0].center();
+            let position = Vec2::new(center.x + 100_f32, center.y + 200_f32);
+            let current = &model.world.emitters[0].position;
+            if current.x != position.x || current.y != position.y {
+                apply(model, Command::MoveEmitter { index: 0, position });
+            }
+        }
+        let steps = model.timestep.advance(upd.since_last.as_secs_f32());
+        for _ in 0..steps {
+            if model.mouse_pressed && model.tool == Tool::Force {
+                // Pulls by default, Shift pushes.
+                let sign = if app.keys.mods.shift() { 1_f32 } else { -1_f32 };
+                let command = Command::ForceRadial {
+                    center: cursor(app, model),
+                    strength: sign * model.force_strength,
+                    radius: model.brush_radius,
+                    dt: model.timestep.dt,
+                };
+                apply(model, command);
+            }
+            model.history.push(&model.world);
+            match &mut model.recorder {
+                Some((_, recorder)) => recorder.step(&mut model.world),
+                None => model.world.step(model.timestep.dt),
+            }
+            headless::log_trajectories(&mut model.trajectories, &model.world);
+        }
+    }
+    if let Some(contacts) = &mut model.debug_contacts {
+        *contacts = model.world.contacts();
+    }
+    if let Some(trails) = &mut model.trails {
+        trails.record(&model.world);
+    }
+    for event in model.world.drain_events() {
+        match event {
+            WorldEvent::ConstraintBroken(c) => println!("broken: {:?}", c),
+            // The grab tether is dropped along with its particle.
+            WorldEvent::ParticlesRemapped(_) => {
+                if grab_tether(model).is_none() {
+                    model.grabbed = None;
+                }
+            }
+            WorldEvent::ParticlesMerged { .. } | WorldEvent::UserDataRemoved(_) => {}
+        }
+    }
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
-    let draw = app.draw();
-    draw.background().color(BLACK);
+    let camera = &model.camera;
+    let draw = app
+        .draw()
+        .scale(camera.zoom)
+        .xy(pt2(-camera.center.x, -camera.center.y));
+    let colors = &model.colors;
+    let rgb = |c: &Color| rgb8(c.r, c.g, c.b);
+    draw.background().color(rgb(&colors.background));
+
+    for constraint in model.world.constraints.iter() {
+        match constraint {
+            Constraint::Circle { center, radius } => {
+                draw.ellipse()
+                    .x(center.x)
+                    .y(center.y)
+                    .color(rgb(&colors.container))
+                    .radius(*radius);
+            }
+            Constraint::Box { min, max } => {
+                draw.rect()
+                    .x((min.x + max.x) / 2_f32)
+                    .y((min.y + max.y) / 2_f32)
+                    .w(max.x - min.x)
+                    .h(max.y - min.y)
+                    .color(rgb(&colors.container));
+            }
+            Constraint::Polygon { points } => {
+                draw.polygon()
+                    .color(rgb(&colors.container))
+                    .points(points.iter().map(|p| pt2(p.x, p.y)));
+            }
+            Constraint::Sdf { field } => {
+                for c in field.inside_cells() {
+                    draw.rect()
+                        .x_y(c.x, c.y)
+                        .w_h(field.cell, field.cell)
+                        .color(rgb(&colors.container));
+                }
+            }
+        }
+    }
+    if let Some(Periodic { min, max }) = &model.world.periodic {
+        draw.rect()
+            .x((min.x + max.x) / 2_f32)
+            .y((min.y + max.y) / 2_f32)
+            .w(max.x - min.x)
+            .h(max.y - min.y)
+            .color(rgb(&colors.container));
+    }
+
+    for fluid in model.world.fluids.iter() {
+        draw.rect()
+            .x((fluid.min.x + fluid.max.x) / 2_f32)
+            .y((fluid.min.y + fluid.max.y) / 2_f32)
+            .w(fluid.max.x - fluid.min.x)
+            .h(fluid.max.y - fluid.min.y)
+            .color(rgba(0.2_f32, 0.4_f32, 0.9_f32, 0.5_f32));
+    }
+
+    for obstacle in model.world.obstacles.iter() {
+        match obstacle {
+            Obstacle::Circle { center, radius } => {
+                draw.ellipse()
+                    .x(center.x)
+                    .y(center.y)
+                    .color(rgb(&colors.obstacle))
+                    .radius(*radius);
+            }
+            Obstacle::Segment { a, b, .. } => {
+                draw.line()
+                    .start(pt2(a.x, a.y))
+                    .end(pt2(b.x, b.y))
+                    .weight(2_f32)
+                    .color(rgb(&colors.obstacle));
+            }
+            Obstacle::Capsule { a, b, radius, .. } => {
+                draw.line()
+                    .start(pt2(a.x, a.y))
+                    .end(pt2(b.x, b.y))
+                    .weight(2_f32 * radius)
+                    .caps_round()
+                    .color(rgb(&colors.obstacle));
+            }
+            Obstacle::Sdf { field } => {
+                for c in field.inside_cells() {
+                    draw.rect()
+                        .x_y(c.x, c.y)
+                        .w_h(field.cell, field.cell)
+                        .color(rgb(&colors.obstacle));
+                }
+            }
+            Obstacle::Polyline { points, .. } => {
+                draw.polyline()
+                    .weight(2_f32)
+                    .points(points.iter().map(|p| pt2(p.x, p.y)))
+                    .color(rgb(&colors.obstacle));
+            }
+        }
+    }
+    for rotor in model.world.rotors.iter() {
+        for (a, b) in rotor.segments() {
+            draw.line()
+                .start(pt2(a.x, a.y))
+                .end(pt2(b.x, b.y))
+                .weight(2_f32 * rotor.thickness)
+                .caps_round()
+                .color(rgb(&colors.obstacle));
+        }
+    }
+
+    let alpha = model.timestep.alpha();
+    if let Some(trails) = &model.trails {
+        draw_trails(&draw, &model.world, trails);
+    }
+    let mut in_body = HashSet::new();
+    for body in model.world.soft_bodies.iter() {
+        let c = body.color;
+        draw.polygon().color(rgb8(c.r, c.g, c.b)).points(
+            body.particles
+                .iter()
+                .map(|&i| model.world.particles.interpolated(i, alpha))
+                .map(|p| pt2(p.x, p.y)),
+        );
+        in_body.extend(body.particles.iter().copied());
+    }
+    for link in model.world.links.iter() {
+        if in_body.contains(&link.a) && in_body.contains(&link.b) {
+            continue;
+        }
+        let a = model.world.particles.interpolated(link.a, alpha);
+        let b = model.world.particles.interpolated(link.b, alpha);
+        draw.line()
+            .start(pt2(a.x, a.y))
+            .end(pt2(b.x, b.y))
+            .weight(2_f32)
+            .color(LIGHTGRAY);
+    }
+
+    for spring in model.world.springs.iter() {
+        let a = model.world.particles.interpolated(spring.a, alpha);
+        let b = model.world.particles.interpolated(spring.b, alpha);
+        draw.line()
+            .start(pt2(a.x, a.y))
+            .end(pt2(b.x, b.y))
+            .weight(1_f32)
+            .color(ORANGE);
+    }
+
+    let particles = &model.world.particles;
+    for i in 0..particles.len() {
+        if !particles.alive[i] || in_body.contains(&i) {
+            continue;
+        }
+        let pos = particles.interpolated(i, alpha);
+        let opacity = if model.fade {
+            particles.life_fraction(i)
+        } else {
+            1_f32
+        };
+        let color = match model.color_view {
+            Some(view) => view.color(&model.world, i),
+            None => particles.color[i],
+        };
+        draw.ellipse()
+            .color(rgba8(color.r, color.g, color.b, (opacity * 255_f32) as u8))
+            .x(pos.x)
+            .y(pos.y)
+            .radius(particles.radius[i]);
+    }
 
-    draw.ellipse()
-        .x(model.center.x)
-        .y(model.center.y)
-        .color(WHITE)
-        .radius(300_f32);
+    for tether in model.world.tethers.iter() {
+        let p = model.world.particles.interpolated(tether.particle, alpha);
+        draw.line()
+            .start(pt2(p.x, p.y))
+            .end(pt2(tether.point.x, tether.point.y))
+            .weight(1_f32)
+            .color(YELLOW);
+    }
 
-    for m in model.particles.iter() {
+    if model.show_broadphase {
+        draw_broadphase(&draw, &model.world);
+    }
+    if let Some(contacts) = &model.debug_contacts {
+        draw_debug(&draw, &model.world, contacts, alpha);
+    }
+
+    for pin in model.world.pins.iter() {
         draw.ellipse()
-            .color(m.color)
-            .x(m.pos.x)
-            .y(m.pos.y)
-            .radius(m.radius);
+            .color(RED)
+            .x(pin.point.x)
+            .y(pin.point.y)
+            .radius(3_f32);
+    }
+    if let Some(start) = &model.wall_start {
+        let c = cursor(app, model);
+        draw.line()
+            .start(pt2(start.x, start.y))
+            .end(pt2(c.x, c.y))
+            .weight(2_f32)
+            .color(WHITE);
+    }
+    if matches!(model.tool, Tool::Erase | Tool::Force) {
+        let c = cursor(app, model);
+        draw.ellipse()
+            .x_y(c.x, c.y)
+            .radius(model.brush_radius)
+            .no_fill()
+            .stroke_weight(1_f32)
+            .stroke(WHITE);
+    }
+    if model.hud {
+        draw_hud(app, &app.draw(), &model.world, model.color_view);
     }
     draw.to_frame(app, &frame).unwrap();
+    model.egui.draw_to_frame(&frame).unwrap();
+
+    if let Some(path) = model
+        .capture
+        .as_ref()
+        .and_then(|c| c.frame_path(app.elapsed_frames()))
+    {
+        app.main_window().capture_frame(path);
+    }
+}
+
+/// Velocity arrows for every particle and contact normals as lines between touching centers;
+/// links show as thin lines so the ones hidden inside soft bodies are visible too.
+fn draw_debug(draw: &Draw, world: &World, contacts: &[(usize, usize)], alpha: f32) {
+    for link in world.links.iter() {
+        let a = world.particles.interpolated(link.a, alpha);
+        let b = world.particles.interpolated(link.b, alpha);
+        draw.line()
+            .start(pt2(a.x, a.y))
+            .end(pt2(b.x, b.y))
+            .weight(1_f32)
+            .color(CYAN);
+    }
+    for &(i, k) in contacts {
+        let a = world.particles.interpolated(i, alpha);
+        let b = world.particles.interpolated(k, alpha);
+        draw.line()
+            .start(pt2(a.x, a.y))
+            .end(pt2(b.x, b.y))
+            .weight(1_f32)
+            .color(RED);
+    }
+    for i in 0..world.particles.len() {
+        let arrow = world.velocity(i) * VELOCITY_ARROW_SECONDS;
+        // Resting particles would get degenerate zero-length arrows.
+        if !world.particles.alive[i] || arrow.len() < 1_f32 {
+            continue;
+        }
+        let pos = world.particles.interpolated(i, alpha);
+        let tip = pos.clone() + arrow;
+        draw.arrow()
+            .start(pt2(pos.x, pos.y))
+            .end(pt2(tip.x, tip.y))
+            .weight(1_f32)
+            .color(LIME);
+    }
+}
+
+/// Each trail as a polyline in its particle's color, fading out towards the oldest point.
+fn draw_trails(draw: &Draw, world: &World, trails: &Trails) {
+    for (handle, trail) in trails.iter() {
+        let Some(i) = world.index(handle) else {
+            continue;
+        };
+        if trail.len() < 2 {
+            continue;
+        }
+        let c = world.particles.color[i];
+        let n = trail.len() as f32;
+        draw.polyline()
+            .weight(2_f32)
+            .points_colored(trail.iter().enumerate().map(|(i, q)| {
+                let opacity = (i + 1) as f32 / n;
+                (
+                    pt2(q.x, q.y),
+                    rgba8(c.r, c.g, c.b, (opacity * 255_f32) as u8),
+                )
+            }));
+    }
+}
+
+/// Outlines of the broadphase cells or nodes, each labelled with its particle count.
+fn draw_broadphase(draw: &Draw, world: &World) {
+    for (cell, count) in world.solver.broadphase().cells() {
+        let (w, h) = (cell.max.x - cell.min.x, cell.max.y - cell.min.y);
+        let (x, y) = (cell.min.x + w / 2_f32, cell.min.y + h / 2_f32);
+        draw.rect()
+            .x_y(x, y)
+            .w_h(w, h)
+            .no_fill()
+            .stroke_weight(1_f32)
+            .stroke(DARKGREEN);
+        if count > 0 {
+            draw.text(&count.to_string())
+                .x_y(x, y)
+                .font_size(10)
+                .color(GREEN);
+        }
+    }
+}
+
+/// Counts, FPS and the last step's phase timings, in the window's top left corner.
+fn draw_hud(app: &App, draw: &Draw, world: &World, color_view: Option<ColorView>) {
+    let constraints = world.links.len()
+        + world.springs.len()
+        + world.bends.len()
+        + world.pins.len()
+        + world.tethers.len()
+        + world.soft_bodies.len();
+    let t = world.timings();
+    let ms = |d: std::time::Duration| 1000_f64 * d.as_secs_f64();
+    let text = format!(
+        "fps {:.0}\nparticles {}\nconstraints {}\ncolors {}\nstep {:.2} ms\n  broadphase {:.2}\n  \
+         narrowphase {:.2}\n  constraints {:.2}\n  integrate {:.2}",
+        app.fps(),
+        world.particle_count(),
+        constraints,
+        color_view.map_or("own", ColorView::name),
+        ms(t.total),
+        ms(t.broadphase),
+        ms(t.narrowphase),
+        ms(t.constraints),
+        ms(t.integrate),
+    );
+    let corner = app.window_rect().pad(10_f32);
+    let (w, h) = (160_f32, 145_f32);
+    draw.text(&text)
+        .x_y(corner.left() + w / 2_f32, corner.top() - h / 2_f32)
+        .w_h(w, h)
+        .left_justify()
+        .align_text_top()
+        .font_size(12)
+        .color(WHITE);
 }
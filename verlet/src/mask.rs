@@ -0,0 +1,28 @@
+use std::error::Error;
+use std::path::Path;
+
+use nannou::image;
+use utils::vec::Vec2;
+use verlet_core::{Obstacle, SdfGrid};
+
+/// Pixels at least this opaque are solid.
+const SOLID_ALPHA: u8 = 128;
+
+/// Static obstacle made of the opaque pixels of the image at `path`, centered on the origin
+/// with each pixel `scale` units wide.
+pub fn load(path: &Path, scale: f32) -> Result<Obstacle, Box<dyn Error>> {
+    let image = image::open(path)?.to_rgba8();
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    // Image rows run top to bottom, the field's rows bottom to top.
+    let inside: Vec<bool> = (0..height)
+        .rev()
+        .flat_map(|row| (0..width).map(move |x| (x, row)))
+        .map(|(x, row)| image.get_pixel(x as u32, row as u32)[3] >= SOLID_ALPHA)
+        .collect();
+    let origin = Vec2::new(
+        -0.5_f32 * (width as f32 - 1_f32) * scale,
+        -0.5_f32 * (height as f32 - 1_f32) * scale,
+    );
+    let field = SdfGrid::from_mask(origin, scale, width, height, &inside);
+    Ok(Obstacle::sdf(field))
+}
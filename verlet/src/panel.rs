@@ -0,0 +1,78 @@
+use nannou_egui::egui::{self, CtxRef, Slider};
+use utils::vec::Vec2;
+use verlet_core::{AdaptiveSubsteps, Command, Constraint, World};
+
+/// Shows the tuning panel for `world` and returns the commands for whatever was changed this
+/// frame, so edits are recorded like any other input.
+pub fn show(ctx: &CtxRef, world: &World, fps: f32) -> Vec<Command> {
+    let mut commands = Vec::new();
+    egui::Window::new("Parameters").show(ctx, |ui| {
+        ui.label(format!("particles: {}", world.particle_count()));
+        ui.label(format!("fps: {:.0}", fps));
+
+        let (mut gx, mut gy) = (world.gravity.x, world.gravity.y);
+        let gravity_x = ui.add(Slider::new(&mut gx, -3000_f32..=3000_f32).text("gravity x"));
+        let gravity_y = ui.add(Slider::new(&mut gy, -3000_f32..=3000_f32).text("gravity y"));
+        if gravity_x.changed() || gravity_y.changed() {
+            commands.push(Command::SetGravity(Vec2::new(gx, gy)));
+        }
+
+        let mut coef = world.solver.response_coef;
+        if ui
+            .add(Slider::new(&mut coef, 0_f32..=1_f32).text("response coef"))
+            .changed()
+        {
+            commands.push(Command::SetResponseCoef(coef));
+        }
+
+        let mut substeps = world.substeps;
+        if ui
+            .add(Slider::new(&mut substeps, 1..=16).text("substeps"))
+            .changed()
+        {
+            commands.push(Command::SetSubsteps(substeps));
+        }
+        let mut adaptive = world.adaptive_substeps.is_some();
+        if ui.checkbox(&mut adaptive, "adaptive substeps").changed() {
+            let adaptive = adaptive.then(AdaptiveSubsteps::default);
+            commands.push(Command::SetAdaptiveSubsteps(adaptive));
+        }
+
+        // Scales the simulated time per step, as opposed to the steps per frame on `-` and `=`.
+        let mut time_scale = world.time_scale();
+        if ui
+            .add(
+                Slider::new(&mut time_scale, 0.05_f32..=4_f32)
+                    .logarithmic(true)
+                    .text("time scale"),
+            )
+            .changed()
+        {
+            commands.push(Command::SetTimeScale(time_scale));
+        }
+
+        if let Some(emitter) = world.emitters.first() {
+            let mut rate = emitter.rate;
+            if ui
+                .add(Slider::new(&mut rate, 0_f32..=100_f32).text("spawn rate"))
+                .changed()
+            {
+                commands.push(Command::SetEmitterRate { index: 0, rate });
+            }
+        }
+
+        if let Some(Constraint::Circle { center, radius }) = world.constraints.first() {
+            let mut radius = *radius;
+            if ui
+                .add(Slider::new(&mut radius, 50_f32..=1000_f32).text("container radius"))
+                .changed()
+            {
+                commands.push(Command::SetConstraint {
+                    index: 0,
+                    constraint: Constraint::circle(center.clone(), radius),
+                });
+            }
+        }
+    });
+    commands
+}
@@ -0,0 +1,132 @@
+use utils::vec::Vec2;
+use verlet_core::{
+    color, Color, Constraint, Emitter, Obstacle, Particle, Scene, World, WorldBuilder,
+};
+
+/// Built-in scenes, picked with the number keys in this order.
+pub const PRESETS: [&str; 7] = [
+    "empty container",
+    "dense pile",
+    "hanging chain",
+    "cloth",
+    "fountain",
+    "orbit",
+    "rigid bodies",
+];
+
+/// A fresh world for preset `index` into `PRESETS`, with velocities set for steps of `dt`.
+pub fn build(index: usize, dt: f32) -> Option<World> {
+    let world = match index {
+        0 => empty(),
+        1 => pile(),
+        2 => chain(),
+        3 => cloth(),
+        4 => fountain(),
+        5 => orbit(dt),
+        6 => rigid(),
+        _ => return None,
+    };
+    Some(world)
+}
+
+fn square(half: f32) -> Constraint {
+    Constraint::rect(Vec2::new(-half, -half), Vec2::new(half, half))
+}
+
+fn empty() -> World {
+    let (_, world) = WorldBuilder::new()
+        .constraint(Constraint::circle(Vec2::zero(), 300_f32))
+        .build();
+    world
+}
+
+fn pile() -> World {
+    let (_, mut world) = WorldBuilder::new()
+        .constraint(square(300_f32))
+        .substeps(4)
+        .build();
+    world.solver_iterations = 2;
+    for row in 0..20 {
+        for col in 0..20 {
+            let pos = Vec2::new(
+                -170_f32 + col as f32 * 18_f32 + (row % 2) as f32 * 4_f32,
+                -280_f32 + row as f32 * 18_f32,
+            );
+            world.add_particle(Particle::new(pos));
+            let last = world.particles.len() - 1;
+            world.particles.radius[last] = 8_f32;
+        }
+    }
+    world
+}
+
+fn chain() -> World {
+    let (_, mut world) = WorldBuilder::new()
+        .constraint(square(350_f32))
+        .substeps(4)
+        .build();
+    world.solver_iterations = 4;
+    world.spawn_chain(
+        Vec2::new(-250_f32, 250_f32),
+        Vec2::new(150_f32, 250_f32),
+        25,
+        6_f32,
+    );
+    world
+}
+
+fn cloth() -> World {
+    let (_, mut world) = WorldBuilder::new()
+        .constraint(square(350_f32))
+        .substeps(4)
+        .build();
+    world.solver_iterations = 4;
+    world.spawn_cloth(Vec2::new(-150_f32, 250_f32), 12, 16, 20_f32);
+    world
+}
+
+fn fountain() -> World {
+    let mut emitter = Emitter::new(Vec2::new(0_f32, -250_f32), 60_f32);
+    emitter.velocity = Vec2::new(0_f32, 900_f32);
+    emitter.spread = 0.4_f32;
+    emitter.speed_variation = 0.15_f32;
+    emitter.radius = 4_f32..=7_f32;
+    emitter.lifetime = Some(4_f32);
+    emitter.palette = vec![
+        color::STEELBLUE,
+        Color::rgb(135, 206, 235),
+        Color::rgb(255, 255, 255),
+    ];
+    let (_, world) = WorldBuilder::new()
+        .constraint(square(350_f32))
+        .emitter(emitter)
+        .substeps(2)
+        .max_particles(600)
+        .build();
+    world
+}
+
+fn orbit(dt: f32) -> World {
+    let scene: Scene =
+        ron::from_str(include_str!("../scenes/orbit.ron")).expect("the bundled orbit scene parses");
+    scene.build(dt)
+}
+
+fn rigid() -> World {
+    let (_, mut world) = WorldBuilder::new()
+        .constraint(square(350_f32))
+        .substeps(4)
+        .build();
+    world.solver_iterations = 4;
+    world.add_obstacle(Obstacle::segment(
+        Vec2::new(-350_f32, 50_f32),
+        Vec2::new(150_f32, -150_f32),
+    ));
+    for i in 0..3 {
+        let x = -250_f32 + i as f32 * 120_f32;
+        world.spawn_rigid_box(Vec2::new(x, 250_f32 - i as f32 * 40_f32), 4, 2, 8_f32);
+    }
+    world.spawn_rigid_gear(Vec2::new(200_f32, 200_f32), 30_f32, 8, 6_f32);
+    world.spawn_rigid_gear(Vec2::new(250_f32, 50_f32), 20_f32, 6, 5_f32);
+    world
+}
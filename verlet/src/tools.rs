@@ -0,0 +1,71 @@
+use std::time::Instant;
+
+use utils::vec::Vec2;
+
+/// What the left mouse button does, cycled with Tab.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tool {
+    /// Grabs the particle under the cursor, or drags the first container.
+    Grab,
+    /// Paints particles that move off with the cursor's velocity.
+    Spawn,
+    /// Removes particles under the brush while held.
+    Erase,
+    /// Drags out static wall segments.
+    Wall,
+    /// Pulls particles under the brush towards the cursor while held, or pushes them away
+    /// with Shift.
+    Force,
+}
+
+impl Tool {
+    pub fn next(self) -> Tool {
+        match self {
+            Tool::Grab => Tool::Spawn,
+            Tool::Spawn => Tool::Erase,
+            Tool::Erase => Tool::Wall,
+            Tool::Wall => Tool::Force,
+            Tool::Force => Tool::Grab,
+        }
+    }
+}
+
+/// Cursor velocity in world units per second, from the last two mouse moves.
+pub struct MouseTracker {
+    last: Option<(Vec2, Instant)>,
+    velocity: Vec2,
+}
+
+impl Default for MouseTracker {
+    fn default() -> Self {
+        MouseTracker {
+            last: None,
+            velocity: Vec2::zero(),
+        }
+    }
+}
+
+impl MouseTracker {
+    /// Cursors that stop for longer than this count as still.
+    const STILL_SECONDS: f32 = 0.1_f32;
+
+    pub fn moved(&mut self, point: Vec2) {
+        let now = Instant::now();
+        if let Some((last, at)) = &self.last {
+            let dt = now.duration_since(*at).as_secs_f32();
+            if dt > 0_f32 {
+                self.velocity = (point.clone() - last.clone()) / dt;
+            }
+        }
+        self.last = Some((point, now));
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        match &self.last {
+            Some((_, at)) if at.elapsed().as_secs_f32() < Self::STILL_SECONDS => {
+                self.velocity.clone()
+            }
+            _ => Vec2::zero(),
+        }
+    }
+}
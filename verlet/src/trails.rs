@@ -0,0 +1,45 @@
+use std::collections::{HashMap, VecDeque};
+
+use utils::vec::Vec2;
+use verlet_core::{ParticleHandle, World};
+
+/// Recent positions of every particle, oldest first, keyed by handle so trails follow their
+/// particle when others are removed.
+pub struct Trails {
+    length: usize,
+    points: HashMap<ParticleHandle, VecDeque<Vec2>>,
+}
+
+impl Trails {
+    /// Trails of up to `length` positions each.
+    pub fn new(length: usize) -> Self {
+        Trails {
+            length,
+            points: HashMap::new(),
+        }
+    }
+
+    /// Appends every particle's current position and forgets removed particles.
+    pub fn record(&mut self, world: &World) {
+        self.points
+            .retain(|&handle, _| world.index(handle).is_some());
+        for (i, pos) in world.particles.pos.iter().enumerate() {
+            let Some(handle) = world.handle(i) else {
+                continue;
+            };
+            let trail = self.points.entry(handle).or_default();
+            // Paused frames would pile up copies of the same point.
+            if trail.back().is_some_and(|q| q.x == pos.x && q.y == pos.y) {
+                continue;
+            }
+            if trail.len() == self.length {
+                trail.pop_front();
+            }
+            trail.push_back(pos.clone());
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ParticleHandle, &VecDeque<Vec2>)> {
+        self.points.iter().map(|(&handle, trail)| (handle, trail))
+    }
+}